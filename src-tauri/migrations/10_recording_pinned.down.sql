@@ -0,0 +1,2 @@
+ALTER TABLE recordings
+DROP COLUMN pinned;
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE recordings
+ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0 CHECK (pinned IN (0, 1));
\ No newline at end of file
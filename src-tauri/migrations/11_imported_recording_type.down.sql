@@ -0,0 +1,19 @@
+UPDATE recordings
+SET type = NULL
+WHERE type = 'imported';
+
+PRAGMA writable_schema = ON;
+
+UPDATE sqlite_master
+SET sql = replace(
+    sql,
+    'CHECK (type IN (''screen'', ''region'', ''window'', ''imported''))',
+    'CHECK (type IN (''screen'', ''region'', ''window''))'
+  )
+WHERE type = 'table'
+  AND name = 'recordings';
+
+-- Also reloads the definition on this connection, later migrations read it
+PRAGMA writable_schema = RESET;
+
+DROP INDEX IF EXISTS idx_recordings_type;
\ No newline at end of file
@@ -0,0 +1,19 @@
+-- SQLite cannot alter a CHECK constraint. Rebuilding the table would cascade
+-- deletes to markers, captions and tags, so the stored definition is rewritten
+-- instead, which is safe as no existing row can violate the relaxed constraint.
+PRAGMA writable_schema = ON;
+
+UPDATE sqlite_master
+SET sql = replace(
+    sql,
+    'CHECK (type IN (''screen'', ''region'', ''window''))',
+    'CHECK (type IN (''screen'', ''region'', ''window'', ''imported''))'
+  )
+WHERE type = 'table'
+  AND name = 'recordings';
+
+-- Also reloads the definition on this connection, later migrations read it
+PRAGMA writable_schema = RESET;
+
+-- Any schema change bumps the schema version, so other open connections reload the definition
+CREATE INDEX IF NOT EXISTS idx_recordings_type ON recordings (type);
\ No newline at end of file
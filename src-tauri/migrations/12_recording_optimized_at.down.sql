@@ -0,0 +1,2 @@
+ALTER TABLE recordings
+DROP COLUMN optimized_at;
\ No newline at end of file
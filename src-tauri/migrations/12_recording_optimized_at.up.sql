@@ -0,0 +1,2 @@
+ALTER TABLE recordings
+ADD COLUMN optimized_at DATETIME;
\ No newline at end of file
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS recording_projects;
\ No newline at end of file
@@ -0,0 +1,10 @@
+CREATE TABLE
+  IF NOT EXISTS recording_projects (
+    recording_id INTEGER PRIMARY KEY REFERENCES recordings (id) ON DELETE CASCADE,
+    -- JSON, see `project::models::Project`
+    project TEXT NOT NULL,
+    -- JSON arrays of projects, most recent last
+    undo_stack TEXT NOT NULL DEFAULT '[]',
+    redo_stack TEXT NOT NULL DEFAULT '[]',
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+  );
\ No newline at end of file
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_caption_segments_recording_id;
+
+DROP TABLE IF EXISTS caption_segments;
\ No newline at end of file
@@ -0,0 +1,11 @@
+CREATE TABLE
+  IF NOT EXISTS caption_segments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    recording_id INTEGER NOT NULL REFERENCES recordings (id) ON DELETE CASCADE,
+    -- milliseconds
+    start_ms INTEGER NOT NULL,
+    end_ms INTEGER NOT NULL,
+    text TEXT NOT NULL
+  );
+
+CREATE INDEX IF NOT EXISTS idx_caption_segments_recording_id ON caption_segments (recording_id);
\ No newline at end of file
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_markers_recording_id;
+
+DROP TABLE IF EXISTS markers;
\ No newline at end of file
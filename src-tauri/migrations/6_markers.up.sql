@@ -0,0 +1,11 @@
+CREATE TABLE
+  IF NOT EXISTS markers (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    recording_id INTEGER NOT NULL REFERENCES recordings (id) ON DELETE CASCADE,
+    -- milliseconds from start of recording, excluding paused time
+    position_ms INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+  );
+
+CREATE INDEX IF NOT EXISTS idx_markers_recording_id ON markers (recording_id);
\ No newline at end of file
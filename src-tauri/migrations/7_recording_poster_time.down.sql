@@ -0,0 +1,2 @@
+ALTER TABLE recordings
+DROP COLUMN poster_time_ms;
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE recordings
+ADD COLUMN poster_time_ms INTEGER;
\ No newline at end of file
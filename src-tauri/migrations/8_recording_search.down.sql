@@ -0,0 +1,15 @@
+DROP INDEX IF EXISTS idx_recordings_length;
+
+DROP INDEX IF EXISTS idx_recordings_size;
+
+DROP INDEX IF EXISTS idx_recordings_last_opened_at;
+
+DROP INDEX IF EXISTS idx_recordings_created_at;
+
+DROP TRIGGER IF EXISTS recordings_fts_update;
+
+DROP TRIGGER IF EXISTS recordings_fts_delete;
+
+DROP TRIGGER IF EXISTS recordings_fts_insert;
+
+DROP TABLE IF EXISTS recordings_fts;
\ No newline at end of file
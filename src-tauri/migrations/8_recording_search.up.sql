@@ -0,0 +1,34 @@
+CREATE VIRTUAL TABLE IF NOT EXISTS recordings_fts USING fts5 (
+  name,
+  content = 'recordings',
+  content_rowid = 'id'
+);
+
+INSERT INTO recordings_fts (rowid, name)
+SELECT id, name
+FROM recordings;
+
+CREATE TRIGGER IF NOT EXISTS recordings_fts_insert AFTER INSERT ON recordings BEGIN
+INSERT INTO recordings_fts (rowid, name)
+VALUES (new.id, new.name);
+END;
+
+CREATE TRIGGER IF NOT EXISTS recordings_fts_delete AFTER DELETE ON recordings BEGIN
+INSERT INTO recordings_fts (recordings_fts, rowid, name)
+VALUES ('delete', old.id, old.name);
+END;
+
+CREATE TRIGGER IF NOT EXISTS recordings_fts_update AFTER UPDATE OF name ON recordings BEGIN
+INSERT INTO recordings_fts (recordings_fts, rowid, name)
+VALUES ('delete', old.id, old.name);
+INSERT INTO recordings_fts (rowid, name)
+VALUES (new.id, new.name);
+END;
+
+CREATE INDEX IF NOT EXISTS idx_recordings_created_at ON recordings (created_at, id);
+
+CREATE INDEX IF NOT EXISTS idx_recordings_last_opened_at ON recordings (last_opened_at, id);
+
+CREATE INDEX IF NOT EXISTS idx_recordings_size ON recordings (size, id);
+
+CREATE INDEX IF NOT EXISTS idx_recordings_length ON recordings (length, id);
\ No newline at end of file
@@ -0,0 +1,128 @@
+DROP INDEX IF EXISTS idx_recordings_folder_id;
+
+-- SQLite cannot drop a column with a REFERENCES constraint, so the table is
+-- rebuilt. Dropping the old table cascades deletes to markers and captions,
+-- which are kept aside and restored.
+CREATE TEMP TABLE markers_backup AS
+SELECT *
+FROM markers;
+
+CREATE TEMP TABLE caption_segments_backup AS
+SELECT *
+FROM caption_segments;
+
+CREATE TABLE
+  recordings_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    recording_directory TEXT NOT NULL UNIQUE,
+    origin_x REAL NOT NULL,
+    origin_y REAL NOT NULL,
+    scale_factor REAL NOT NULL,
+    has_camera INTEGER NOT NULL DEFAULT 0 CHECK (has_camera IN (0, 1)),
+    has_system_audio INTEGER NOT NULL DEFAULT 0 CHECK (has_system_audio IN (0, 1)),
+    has_microphone INTEGER NOT NULL DEFAULT 0 CHECK (has_microphone IN (0, 1)),
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    has_system_cursor INTEGER NOT NULL DEFAULT 0 CHECK (has_system_cursor IN (0, 1)),
+    name TEXT NOT NULL DEFAULT 'Unnamed Recording',
+    type TEXT CHECK (type IN ('screen', 'region', 'window')),
+    length INTEGER,
+    size INTEGER,
+    deleted_at DATETIME,
+    last_opened_at DATETIME,
+    poster_time_ms INTEGER
+  );
+
+INSERT INTO
+  recordings_new (
+    id,
+    recording_directory,
+    origin_x,
+    origin_y,
+    scale_factor,
+    has_camera,
+    has_system_audio,
+    has_microphone,
+    created_at,
+    has_system_cursor,
+    name,
+    type,
+    length,
+    size,
+    deleted_at,
+    last_opened_at,
+    poster_time_ms
+  )
+SELECT
+  id,
+  recording_directory,
+  origin_x,
+  origin_y,
+  scale_factor,
+  has_camera,
+  has_system_audio,
+  has_microphone,
+  created_at,
+  has_system_cursor,
+  name,
+  type,
+  length,
+  size,
+  deleted_at,
+  last_opened_at,
+  poster_time_ms
+FROM recordings;
+
+-- Drops the search triggers and indexes along with the old table
+DROP TABLE recordings;
+
+ALTER TABLE recordings_new
+RENAME TO recordings;
+
+INSERT INTO markers
+SELECT *
+FROM markers_backup;
+
+INSERT INTO caption_segments
+SELECT *
+FROM caption_segments_backup;
+
+DROP TABLE markers_backup;
+
+DROP TABLE caption_segments_backup;
+
+CREATE TRIGGER IF NOT EXISTS recordings_fts_insert AFTER INSERT ON recordings BEGIN
+INSERT INTO recordings_fts (rowid, name)
+VALUES (new.id, new.name);
+END;
+
+CREATE TRIGGER IF NOT EXISTS recordings_fts_delete AFTER DELETE ON recordings BEGIN
+INSERT INTO recordings_fts (recordings_fts, rowid, name)
+VALUES ('delete', old.id, old.name);
+END;
+
+CREATE TRIGGER IF NOT EXISTS recordings_fts_update AFTER UPDATE OF name ON recordings BEGIN
+INSERT INTO recordings_fts (recordings_fts, rowid, name)
+VALUES ('delete', old.id, old.name);
+INSERT INTO recordings_fts (rowid, name)
+VALUES (new.id, new.name);
+END;
+
+CREATE INDEX IF NOT EXISTS idx_recordings_created_at ON recordings (created_at, id);
+
+CREATE INDEX IF NOT EXISTS idx_recordings_last_opened_at ON recordings (last_opened_at, id);
+
+CREATE INDEX IF NOT EXISTS idx_recordings_size ON recordings (size, id);
+
+CREATE INDEX IF NOT EXISTS idx_recordings_length ON recordings (length, id);
+
+DROP INDEX IF EXISTS idx_folders_parent_id_name;
+
+DROP INDEX IF EXISTS idx_folders_parent_id;
+
+DROP TABLE IF EXISTS folders;
+
+DROP INDEX IF EXISTS idx_recording_tags_tag_id;
+
+DROP TABLE IF EXISTS recording_tags;
+
+DROP TABLE IF EXISTS tags;
\ No newline at end of file
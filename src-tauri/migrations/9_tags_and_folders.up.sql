@@ -0,0 +1,34 @@
+CREATE TABLE
+  IF NOT EXISTS tags (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+  );
+
+CREATE TABLE
+  IF NOT EXISTS recording_tags (
+    recording_id INTEGER NOT NULL REFERENCES recordings (id) ON DELETE CASCADE,
+    tag_id INTEGER NOT NULL REFERENCES tags (id) ON DELETE CASCADE,
+    PRIMARY KEY (recording_id, tag_id)
+  );
+
+CREATE INDEX IF NOT EXISTS idx_recording_tags_tag_id ON recording_tags (tag_id);
+
+CREATE TABLE
+  IF NOT EXISTS folders (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    -- NULL for top level folders
+    parent_id INTEGER REFERENCES folders (id) ON DELETE CASCADE,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+  );
+
+CREATE INDEX IF NOT EXISTS idx_folders_parent_id ON folders (parent_id);
+
+-- NULLs are distinct in a UNIQUE constraint, which would allow duplicate top level folders
+CREATE UNIQUE INDEX IF NOT EXISTS idx_folders_parent_id_name ON folders (COALESCE(parent_id, 0), name);
+
+ALTER TABLE recordings
+ADD COLUMN folder_id INTEGER REFERENCES folders (id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_recordings_folder_id ON recordings (folder_id);
\ No newline at end of file
@@ -0,0 +1,64 @@
+use sqlx::{Pool, Sqlite};
+use tauri::State;
+
+use crate::{
+  analysis::{
+    models::{DeadAirOptions, TimeRange},
+    service::{detect_input_idleness, detect_silence, intersect_ranges, suggest_edits},
+  },
+  db,
+  export::models::EditRange,
+  recording::{ffmpeg::shortest_video_length, models::RecordingFile},
+};
+
+/// Suggest cuts and speed-ups for ranges with no audio and no mouse input.
+///
+/// The result can be used directly as `edits` of the recording `Project`.
+#[tauri::command]
+pub async fn suggest_dead_air_edits(
+  pool: State<'_, Pool<Sqlite>>,
+  recording_id: i64,
+  options: Option<DeadAirOptions>,
+) -> Result<Vec<EditRange>, String> {
+  let recording_directory = db::recordings::get_recording_directory(&pool, recording_id)
+    .await
+    .map_err(|e| e.to_string())?;
+  let options = options.unwrap_or_default();
+
+  tauri::async_runtime::spawn_blocking(move || {
+    let length_ms = shortest_video_length(vec![
+      recording_directory.join(RecordingFile::Screen.as_ref())
+    ])
+    .ok_or("Failed to read recording length")?;
+
+    let mut dead_air = vec![TimeRange {
+      start_ms: 0,
+      end_ms: length_ms,
+    }];
+
+    // Only silent when every audio track is silent
+    for audio_file in [RecordingFile::Microphone, RecordingFile::SystemAudio] {
+      let audio_path = audio_file.resolve(&recording_directory);
+      if audio_path.exists() {
+        let silence = detect_silence(
+          &audio_path,
+          options.noise_threshold_db,
+          options.min_duration_ms,
+          length_ms,
+        )?;
+        dead_air = intersect_ranges(&dead_air, &silence);
+      }
+    }
+
+    let idleness = detect_input_idleness(
+      &recording_directory.join(RecordingFile::MouseEvents.as_ref()),
+      options.min_duration_ms,
+      length_ms,
+    );
+    dead_air = intersect_ranges(&dead_air, &idleness);
+
+    Ok(suggest_edits(&dead_air, &options))
+  })
+  .await
+  .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,40 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DeadAirOptions {
+  /// Audio below this level is considered silent
+  pub noise_threshold_db: f64,
+  /// Shortest dead air range worth suggesting
+  pub min_duration_ms: u64,
+  /// Kept either side of a suggestion so speech isn't clipped
+  pub padding_ms: u64,
+  /// Ranges shorter than this are sped up rather than cut
+  pub cut_threshold_ms: u64,
+  pub speed_up_factor: f64,
+}
+
+impl Default for DeadAirOptions {
+  fn default() -> Self {
+    DeadAirOptions {
+      noise_threshold_db: -35.0,
+      min_duration_ms: 1500,
+      padding_ms: 250,
+      cut_threshold_ms: 5000,
+      speed_up_factor: 4.0,
+    }
+  }
+}
+
+/// Half-open `[start_ms, end_ms)` range of the recording timeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeRange {
+  pub start_ms: u64,
+  pub end_ms: u64,
+}
+
+impl TimeRange {
+  pub fn duration_ms(&self) -> u64 {
+    self.end_ms.saturating_sub(self.start_ms)
+  }
+}
@@ -0,0 +1,167 @@
+use std::{
+  io::{BufRead, BufReader},
+  path::Path,
+};
+
+use ffmpeg_sidecar::command::FfmpegCommand;
+
+use crate::{
+  analysis::models::{DeadAirOptions, TimeRange},
+  export::models::{EditAction, EditRange},
+  recording::input_events::read_mouse_events,
+};
+
+/// Return silent ranges of an audio file using ffmpeg `silencedetect`
+pub fn detect_silence(
+  audio_path: &Path,
+  noise_threshold_db: f64,
+  min_duration_ms: u64,
+  length_ms: u64,
+) -> Result<Vec<TimeRange>, String> {
+  let mut command = FfmpegCommand::new();
+  command
+    .input(audio_path.to_string_lossy())
+    .args([
+      "-af",
+      &format!(
+        "silencedetect=noise={noise_threshold_db}dB:d={:.3}",
+        min_duration_ms as f64 / 1000.0
+      ),
+    ])
+    .format("null")
+    .output("-");
+
+  let mut ffmpeg = command.spawn().map_err(|e| e.to_string())?;
+  let stderr = ffmpeg.take_stderr().unwrap();
+
+  let mut ranges = Vec::new();
+  let mut current_start: Option<u64> = None;
+
+  // e.g. `[silencedetect @ 0x...] silence_start: 12.345`
+  //      `[silencedetect @ 0x...] silence_end: 15.678 | silence_duration: 3.333`
+  for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+    if let Some(value) = line.split("silence_start: ").nth(1) {
+      current_start = parse_seconds_to_milliseconds(value);
+    } else if let Some(value) = line.split("silence_end: ").nth(1) {
+      let end = value
+        .split('|')
+        .next()
+        .and_then(parse_seconds_to_milliseconds);
+      if let (Some(start_ms), Some(end_ms)) = (current_start.take(), end) {
+        ranges.push(TimeRange { start_ms, end_ms });
+      }
+    }
+  }
+
+  let status = ffmpeg.wait().map_err(|e| e.to_string())?;
+  if !status.success() {
+    return Err(format!("Silence detection failed for {audio_path:?}"));
+  }
+
+  // Silent until the end of the file
+  if let Some(start_ms) = current_start {
+    ranges.push(TimeRange {
+      start_ms,
+      end_ms: length_ms,
+    });
+  }
+
+  Ok(ranges)
+}
+
+fn parse_seconds_to_milliseconds(value: &str) -> Option<u64> {
+  value
+    .trim()
+    .parse::<f64>()
+    .ok()
+    .map(|seconds| (seconds.max(0.0) * 1000.0).round() as u64)
+}
+
+/// Return ranges with no mouse input at least `min_duration_ms` long
+pub fn detect_input_idleness(
+  mouse_events_path: &Path,
+  min_duration_ms: u64,
+  length_ms: u64,
+) -> Vec<TimeRange> {
+  let timestamps: Vec<u64> = match read_mouse_events(mouse_events_path) {
+    Ok(events) => events.iter().map(|e| e.elapsed_ms() as u64).collect(),
+    Err(e) => {
+      log::warn!("Failed to read mouse events {mouse_events_path:?}: {e}");
+      // Without input data only audio is taken into account
+      return vec![TimeRange {
+        start_ms: 0,
+        end_ms: length_ms,
+      }];
+    }
+  };
+
+  let mut ranges = Vec::new();
+  let mut previous = 0;
+  for timestamp in timestamps.into_iter().chain(std::iter::once(length_ms)) {
+    let timestamp = timestamp.min(length_ms);
+    if timestamp.saturating_sub(previous) >= min_duration_ms {
+      ranges.push(TimeRange {
+        start_ms: previous,
+        end_ms: timestamp,
+      });
+    }
+    previous = previous.max(timestamp);
+  }
+
+  ranges
+}
+
+/// Intersect two sorted, non-overlapping range lists
+pub fn intersect_ranges(a: &[TimeRange], b: &[TimeRange]) -> Vec<TimeRange> {
+  let mut result = Vec::new();
+  let (mut i, mut j) = (0, 0);
+
+  while i < a.len() && j < b.len() {
+    let start_ms = a[i].start_ms.max(b[j].start_ms);
+    let end_ms = a[i].end_ms.min(b[j].end_ms);
+    if start_ms < end_ms {
+      result.push(TimeRange { start_ms, end_ms });
+    }
+
+    if a[i].end_ms < b[j].end_ms {
+      i += 1;
+    } else {
+      j += 1;
+    }
+  }
+
+  result
+}
+
+/// Turn dead air ranges into edit list suggestions.
+///
+/// Long ranges are cut, short ones sped up, both keeping `padding_ms` either side.
+pub fn suggest_edits(dead_air: &[TimeRange], options: &DeadAirOptions) -> Vec<EditRange> {
+  dead_air
+    .iter()
+    .filter(|range| range.duration_ms() >= options.min_duration_ms)
+    .filter_map(|range| {
+      let padded = TimeRange {
+        start_ms: range.start_ms + options.padding_ms,
+        end_ms: range.end_ms.saturating_sub(options.padding_ms),
+      };
+      if padded.duration_ms() == 0 {
+        return None;
+      }
+
+      let action = if padded.duration_ms() >= options.cut_threshold_ms {
+        EditAction::Cut
+      } else {
+        EditAction::SpeedUp {
+          factor: options.speed_up_factor,
+        }
+      };
+
+      Some(EditRange {
+        start_ms: padded.start_ms,
+        end_ms: padded.end_ms,
+        action,
+      })
+    })
+    .collect()
+}
@@ -0,0 +1,183 @@
+use std::path::{Path, PathBuf};
+
+use sqlx::{Pool, Sqlite};
+use tauri::{AppHandle, State};
+
+use crate::{
+  bundle::{
+    models::{BundleManifest, BundleMarker, BundleRecording, ImportedBundle, BUNDLE_VERSION},
+    service,
+  },
+  db::{self, recordings::NewRecording},
+  export::models::EditRange,
+  project,
+  recording::{
+    commands::spawn_post_processing,
+    file::{create_recording_directory, folder_size_bytes, recordings_root},
+    models::RecordingType,
+  },
+};
+
+/// Export a recording, its metadata and project as a single bundle archive
+#[tauri::command]
+pub async fn export_recording_bundle(
+  pool: State<'_, Pool<Sqlite>>,
+  recording_id: i64,
+  output_path: PathBuf,
+  edits: Option<Vec<EditRange>>,
+) -> Result<(), String> {
+  let row = db::recordings::get_recording_row(&pool, recording_id)
+    .await
+    .map_err(|e| e.to_string())?;
+  let markers = db::markers::list_markers(&pool, recording_id)
+    .await
+    .map_err(|e| e.to_string())?;
+  let captions = db::captions::get_caption_segments(&pool, recording_id)
+    .await
+    .map_err(|e| e.to_string())?;
+  let project = project::service::load_project(&pool, recording_id).await?;
+
+  let manifest = BundleManifest {
+    version: BUNDLE_VERSION,
+    recording: BundleRecording {
+      name: row.name,
+      r#type: row.r#type,
+      origin_x: row.origin_x,
+      origin_y: row.origin_y,
+      scale_factor: row.scale_factor,
+      has_camera: row.has_camera,
+      has_system_audio: row.has_system_audio,
+      has_microphone: row.has_microphone,
+      has_system_cursor: row.has_system_cursor,
+      length_ms: row.length_ms,
+      poster_time_ms: row.poster_time_ms,
+    },
+    markers: markers
+      .into_iter()
+      .map(|marker| BundleMarker {
+        position_ms: marker.position_ms,
+        name: marker.name,
+      })
+      .collect(),
+    captions,
+    edits: edits.unwrap_or_else(|| project.edits.clone()),
+    project: Some(project),
+  };
+
+  let recording_directory = row.recording_directory;
+  tauri::async_runtime::spawn_blocking(move || {
+    service::write_bundle(&manifest, &recording_directory, &output_path)
+  })
+  .await
+  .map_err(|e| e.to_string())?
+}
+
+/// Import a bundle as a new recording with a fresh directory
+#[tauri::command]
+pub async fn import_recording_bundle(
+  app_handle: AppHandle,
+  pool: State<'_, Pool<Sqlite>>,
+  bundle_path: PathBuf,
+) -> Result<ImportedBundle, String> {
+  let recording_dir = create_recording_directory(&recordings_root(&app_handle));
+
+  let extract_dir = recording_dir.clone();
+  let manifest =
+    tauri::async_runtime::spawn_blocking(move || service::read_bundle(&bundle_path, &extract_dir))
+      .await
+      .map_err(|e| e.to_string())?;
+
+  let manifest = match manifest {
+    Ok(manifest) => manifest,
+    Err(e) => {
+      let _ = tokio::fs::remove_dir_all(&recording_dir).await;
+      return Err(e);
+    }
+  };
+
+  let recording_id = match insert_bundle(&pool, &manifest, &recording_dir).await {
+    Ok(recording_id) => recording_id,
+    Err(e) => {
+      let _ = tokio::fs::remove_dir_all(&recording_dir).await;
+      return Err(e);
+    }
+  };
+
+  spawn_post_processing(app_handle, recording_id, recording_dir);
+
+  Ok(ImportedBundle {
+    recording_id,
+    edits: manifest.edits,
+  })
+}
+
+/// Insert the recording of a bundle with its markers, captions and project,
+/// all or nothing
+async fn insert_bundle(
+  pool: &Pool<Sqlite>,
+  manifest: &BundleManifest,
+  recording_dir: &Path,
+) -> Result<i64, String> {
+  let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+
+  let recording = &manifest.recording;
+  let recording_id = db::recordings::insert_recording(
+    &mut *tx,
+    &NewRecording {
+      recording_directory: &recording_dir.to_string_lossy(),
+      origin_x: recording.origin_x,
+      origin_y: recording.origin_y,
+      scale_factor: recording.scale_factor,
+      name: recording.name.clone(),
+      has_camera: recording.has_camera,
+      has_system_audio: recording.has_system_audio,
+      has_microphone: recording.has_microphone,
+      has_system_cursor: recording.has_system_cursor,
+      r#type: recording
+        .r#type
+        .as_ref()
+        .unwrap_or(&RecordingType::Imported),
+    },
+  )
+  .await
+  .map_err(|e| e.to_string())?;
+
+  db::recordings::set_recording_metadata(
+    &mut *tx,
+    recording_id,
+    folder_size_bytes(recording_dir),
+    recording.length_ms,
+  )
+  .await
+  .map_err(|e| e.to_string())?;
+
+  // Kept when thumbnails are generated, see `spawn_post_processing`
+  if let Some(poster_time_ms) = recording.poster_time_ms {
+    db::recordings::set_poster_time(&mut *tx, recording_id, poster_time_ms)
+      .await
+      .map_err(|e| e.to_string())?;
+  }
+
+  for marker in &manifest.markers {
+    db::markers::insert_marker(
+      &mut *tx,
+      recording_id,
+      marker.position_ms,
+      Some(marker.name.clone()),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+  }
+
+  db::captions::replace_caption_segments(&mut *tx, recording_id, &manifest.captions)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  if let Some(project) = &manifest.project {
+    project::service::insert_project(&mut *tx, recording_id, project.clone()).await?;
+  }
+
+  tx.commit().await.map_err(|e| e.to_string())?;
+
+  Ok(recording_id)
+}
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  captions::models::CaptionSegment, export::models::EditRange, project::models::Project,
+  recording::models::RecordingType,
+};
+
+/// Increment when the bundle layout or manifest changes incompatibly
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// `bundle.json` at the root of a bundle archive, recording files are under `files/`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleManifest {
+  pub version: u32,
+  pub recording: BundleRecording,
+  pub markers: Vec<BundleMarker>,
+  pub captions: Vec<CaptionSegment>,
+  /// Editor edit list at the time of export, the saved project's edits by default
+  pub edits: Vec<EditRange>,
+  /// Saved project, without its undo history
+  #[serde(default)]
+  pub project: Option<Project>,
+}
+
+/// Recording metadata, origin and scale factor map mouse events onto the video
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleRecording {
+  pub name: String,
+  pub r#type: Option<RecordingType>,
+  pub origin_x: f64,
+  pub origin_y: f64,
+  pub scale_factor: f64,
+  pub has_camera: bool,
+  pub has_system_audio: bool,
+  pub has_microphone: bool,
+  pub has_system_cursor: bool,
+  pub length_ms: Option<u64>,
+  pub poster_time_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleMarker {
+  pub position_ms: i64,
+  pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedBundle {
+  pub recording_id: i64,
+  pub edits: Vec<EditRange>,
+}
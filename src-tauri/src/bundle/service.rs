@@ -0,0 +1,108 @@
+use std::{
+  fs::File,
+  io::{BufReader, BufWriter, Read},
+  path::{Component, Path},
+};
+
+use tar::{Archive, Builder, Header};
+
+use crate::{
+  bundle::models::{BundleManifest, BUNDLE_VERSION},
+  recording::models::RecordingFile,
+};
+
+const MANIFEST_PATH: &str = "bundle.json";
+const FILES_DIR: &str = "files";
+
+/// Write the manifest and recorded files in `recording_dir` to a bundle archive.
+///
+/// Generated files, e.g. thumbnails and proxies, are left out and regenerated on import.
+pub fn write_bundle(
+  manifest: &BundleManifest,
+  recording_dir: &Path,
+  output_path: &Path,
+) -> Result<(), String> {
+  let file = File::create(output_path).map_err(|e| e.to_string())?;
+  let mut builder = Builder::new(BufWriter::new(file));
+
+  // Manifest first, so importers can validate before extracting media
+  let manifest_bytes = serde_json::to_vec_pretty(manifest).map_err(|e| e.to_string())?;
+  let mut header = Header::new_gnu();
+  header.set_size(manifest_bytes.len() as u64);
+  header.set_mode(0o644);
+  header.set_cksum();
+  builder
+    .append_data(&mut header, MANIFEST_PATH, manifest_bytes.as_slice())
+    .map_err(|e| e.to_string())?;
+
+  for entry in std::fs::read_dir(recording_dir).map_err(|e| e.to_string())? {
+    let path = entry.map_err(|e| e.to_string())?.path();
+    let generated = path
+      .file_name()
+      .and_then(|name| name.to_str()?.parse::<RecordingFile>().ok())
+      .is_some_and(|file| file.is_generated());
+    if !path.is_file() || generated {
+      continue;
+    }
+
+    let name = Path::new(FILES_DIR).join(path.file_name().unwrap());
+    builder
+      .append_path_with_name(&path, name)
+      .map_err(|e| e.to_string())?;
+  }
+
+  builder
+    .into_inner()
+    .and_then(|mut writer| std::io::Write::flush(&mut writer))
+    .map_err(|e| e.to_string())
+}
+
+/// File name of an archive entry under `files/`, rejecting anything that
+/// could escape the recording directory
+fn bundled_file_name(entry_path: &Path) -> Option<&std::ffi::OsStr> {
+  let mut components = entry_path.components();
+
+  match (components.next(), components.next(), components.next()) {
+    (Some(Component::Normal(dir)), Some(Component::Normal(name)), None) if dir == FILES_DIR => {
+      Some(name)
+    }
+    _ => None,
+  }
+}
+
+/// Extract bundle files into `recording_dir` and return the manifest
+pub fn read_bundle(bundle_path: &Path, recording_dir: &Path) -> Result<BundleManifest, String> {
+  let file = File::open(bundle_path).map_err(|e| e.to_string())?;
+  let mut archive = Archive::new(BufReader::new(file));
+  let mut manifest: Option<BundleManifest> = None;
+
+  for entry in archive.entries().map_err(|e| e.to_string())? {
+    let mut entry = entry.map_err(|e| e.to_string())?;
+    let entry_path = entry.path().map_err(|e| e.to_string())?.into_owned();
+
+    if entry_path == Path::new(MANIFEST_PATH) {
+      let mut bytes = Vec::new();
+      entry.read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+      let parsed: BundleManifest = serde_json::from_slice(&bytes).map_err(|e| e.to_string())?;
+
+      if parsed.version > BUNDLE_VERSION {
+        return Err(format!(
+          "Bundle version {} is newer than supported version {BUNDLE_VERSION}",
+          parsed.version
+        ));
+      }
+
+      manifest = Some(parsed);
+    } else if let Some(name) = bundled_file_name(&entry_path) {
+      if !entry.header().entry_type().is_file() {
+        continue;
+      }
+
+      entry
+        .unpack(recording_dir.join(name))
+        .map_err(|e| e.to_string())?;
+    }
+  }
+
+  manifest.ok_or_else(|| "Bundle has no manifest".to_string())
+}
@@ -0,0 +1,98 @@
+use std::path::PathBuf;
+
+use sqlx::{Pool, Sqlite};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{
+  captions::{
+    models::{CaptionSegment, TranscriptionProgress},
+    service::transcribe,
+  },
+  constants::Events,
+  db,
+  recording::models::RecordingFile,
+};
+
+/// Transcribe recording microphone track with a local whisper model.
+///
+/// Runs in the background, emitting `TranscriptionProgress` and finally
+/// `TranscriptionComplete` with the recording id or `TranscriptionFailed`.
+#[tauri::command]
+pub async fn transcribe_recording(
+  app_handle: AppHandle,
+  pool: State<'_, Pool<Sqlite>>,
+  recording_id: i64,
+  model_path: PathBuf,
+  language: Option<String>,
+) -> Result<(), String> {
+  let recording_directory = db::recordings::get_recording_directory(&pool, recording_id)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  let microphone_path = RecordingFile::Microphone.resolve(&recording_directory);
+  if !microphone_path.exists() {
+    return Err("Recording has no microphone track".to_string());
+  }
+
+  if !model_path.exists() {
+    return Err(format!("Model file not found: {}", model_path.display()));
+  }
+
+  tauri::async_runtime::spawn_blocking(move || {
+    log::info!("Transcribing recording {recording_id}");
+
+    let app_handle_for_progress = app_handle.clone();
+    let result = transcribe(&microphone_path, &model_path, language, move |percentage| {
+      let _ = app_handle_for_progress.emit(
+        Events::TranscriptionProgress.as_ref(),
+        TranscriptionProgress {
+          recording_id,
+          percentage,
+        },
+      );
+    });
+
+    let result = result.and_then(|segments| {
+      tauri::async_runtime::block_on(db::captions::replace_caption_segments(
+        app_handle.state::<Pool<Sqlite>>().inner(),
+        recording_id,
+        &segments,
+      ))
+      .map_err(|e| e.to_string())
+    });
+
+    match result {
+      Ok(()) => {
+        log::info!("Transcription complete for recording {recording_id}");
+        let _ = app_handle.emit(Events::TranscriptionComplete.as_ref(), recording_id);
+      }
+      Err(e) => {
+        log::warn!("Transcription failed for recording {recording_id}: {e}");
+        let _ = app_handle.emit(Events::TranscriptionFailed.as_ref(), e);
+      }
+    }
+  });
+
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn get_caption_segments(
+  pool: State<'_, Pool<Sqlite>>,
+  recording_id: i64,
+) -> Result<Vec<CaptionSegment>, String> {
+  db::captions::get_caption_segments(&pool, recording_id)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_caption_segments(
+  pool: State<'_, Pool<Sqlite>>,
+  recording_id: i64,
+  segments: Vec<CaptionSegment>,
+) -> Result<(), String> {
+  db::captions::replace_caption_segments(&*pool, recording_id, &segments)
+    .await
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptionSegment {
+  pub start_ms: i64,
+  pub end_ms: i64,
+  pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleFormat {
+  Srt,
+  Vtt,
+}
+
+impl SubtitleFormat {
+  pub fn extension(&self) -> &'static str {
+    match self {
+      SubtitleFormat::Srt => "srt",
+      SubtitleFormat::Vtt => "vtt",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptionPosition {
+  Top,
+  Bottom,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BurnInStyle {
+  pub font_size: u32,
+  /// `#RRGGBB`
+  pub colour: String,
+  /// `#RRGGBB`
+  pub outline_colour: String,
+  pub position: CaptionPosition,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+  rename_all = "camelCase",
+  rename_all_fields = "camelCase",
+  tag = "mode"
+)]
+pub enum CaptionExportMode {
+  /// Separate subtitle file next to the exported video
+  Sidecar { format: SubtitleFormat },
+  /// Soft subtitle track inside the container
+  Soft,
+  /// Rendered into the video frames
+  BurnIn { style: BurnInStyle },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptionProgress {
+  pub recording_id: i64,
+  pub percentage: i32,
+}
@@ -0,0 +1,205 @@
+use std::{
+  fs::File,
+  io::{BufWriter, Read, Write},
+  path::Path,
+};
+
+use ffmpeg_sidecar::command::FfmpegCommand;
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+use crate::{
+  captions::models::{BurnInStyle, CaptionPosition, CaptionSegment, SubtitleFormat},
+  recording::ffmpeg::capture_ffmpeg_stderr,
+};
+
+/// Sample rate expected by whisper models
+const WHISPER_SAMPLE_RATE: u32 = 16_000;
+
+/// Decode an audio file to 16kHz mono `f32` samples.
+fn decode_for_whisper(audio_path: &Path) -> Result<Vec<f32>, String> {
+  let mut command = FfmpegCommand::new();
+  command
+    .input(audio_path.to_string_lossy())
+    .args(["-ac", "1"])
+    .args(["-ar", &WHISPER_SAMPLE_RATE.to_string()])
+    .format("f32le")
+    .output("-");
+
+  let mut ffmpeg = command.spawn().map_err(|e| e.to_string())?;
+  let stderr_handle =
+    capture_ffmpeg_stderr(ffmpeg.take_stderr().unwrap(), "[captions]".to_string(), 5);
+
+  let mut bytes = Vec::new();
+  ffmpeg
+    .take_stdout()
+    .unwrap()
+    .read_to_end(&mut bytes)
+    .map_err(|e| e.to_string())?;
+
+  let status = ffmpeg.wait().map_err(|e| e.to_string())?;
+  let stderr_tail = stderr_handle.join().unwrap_or_default();
+  if !status.success() {
+    return Err(format!(
+      "Failed to decode audio: {}",
+      stderr_tail.join("\n")
+    ));
+  }
+
+  Ok(
+    bytes
+      .chunks_exact(4)
+      .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+      .collect(),
+  )
+}
+
+/// Transcribe an audio file fully offline with a local whisper model.
+///
+/// `on_progress` receives a percentage between 0 and 100.
+pub fn transcribe(
+  audio_path: &Path,
+  model_path: &Path,
+  language: Option<String>,
+  on_progress: impl FnMut(i32) + 'static,
+) -> Result<Vec<CaptionSegment>, String> {
+  let samples = decode_for_whisper(audio_path)?;
+
+  let context = WhisperContext::new_with_params(
+    &model_path.to_string_lossy(),
+    WhisperContextParameters::default(),
+  )
+  .map_err(|e| format!("Failed to load whisper model: {e}"))?;
+  let mut state = context.create_state().map_err(|e| e.to_string())?;
+
+  let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+  params.set_language(Some(language.as_deref().unwrap_or("auto")));
+  params.set_n_threads(
+    std::thread::available_parallelism()
+      .map(|n| n.get() as i32)
+      .unwrap_or(4),
+  );
+  params.set_print_special(false);
+  params.set_print_progress(false);
+  params.set_print_realtime(false);
+  params.set_print_timestamps(false);
+  params.set_progress_callback_safe(on_progress);
+
+  state.full(params, &samples).map_err(|e| e.to_string())?;
+
+  let segment_count = state.full_n_segments().map_err(|e| e.to_string())?;
+  let mut segments = Vec::with_capacity(segment_count as usize);
+  for i in 0..segment_count {
+    let text = state.full_get_segment_text(i).map_err(|e| e.to_string())?;
+    let text = text.trim();
+    if text.is_empty() {
+      continue;
+    }
+
+    // Whisper timestamps are in centiseconds
+    let t0 = state.full_get_segment_t0(i).map_err(|e| e.to_string())?;
+    let t1 = state.full_get_segment_t1(i).map_err(|e| e.to_string())?;
+    segments.push(CaptionSegment {
+      start_ms: t0 * 10,
+      end_ms: t1 * 10,
+      text: text.to_string(),
+    });
+  }
+
+  Ok(segments)
+}
+
+/// Format milliseconds as `HH:MM:SS{separator}mmm`
+fn format_subtitle_timestamp(ms: i64, separator: char) -> String {
+  let ms = ms.max(0);
+  let hours = ms / 3_600_000;
+  let minutes = (ms % 3_600_000) / 60_000;
+  let seconds = (ms % 60_000) / 1000;
+  let millis = ms % 1000;
+
+  format!("{hours:02}:{minutes:02}:{seconds:02}{separator}{millis:03}")
+}
+
+/// Write caption segments as a SRT or WebVTT file
+pub fn write_subtitles(
+  segments: &[CaptionSegment],
+  format: SubtitleFormat,
+  path: &Path,
+) -> std::io::Result<()> {
+  let mut writer = BufWriter::new(File::create(path)?);
+
+  let separator = match format {
+    SubtitleFormat::Srt => ',',
+    SubtitleFormat::Vtt => {
+      writeln!(writer, "WEBVTT")?;
+      writeln!(writer)?;
+      '.'
+    }
+  };
+
+  for (i, segment) in segments.iter().enumerate() {
+    if format == SubtitleFormat::Srt {
+      writeln!(writer, "{}", i + 1)?;
+    }
+
+    writeln!(
+      writer,
+      "{} --> {}",
+      format_subtitle_timestamp(segment.start_ms, separator),
+      format_subtitle_timestamp(segment.end_ms, separator)
+    )?;
+    writeln!(writer, "{}", segment.text)?;
+    writeln!(writer)?;
+  }
+
+  writer.flush()
+}
+
+/// Convert `#RRGGBB` to ASS `&HBBGGRR` colour
+fn hex_to_ass_colour(hex: &str) -> String {
+  let hex = hex.trim_start_matches('#');
+  if hex.len() != 6 || !hex.is_ascii() {
+    return "&H00FFFFFF".to_string();
+  }
+
+  let (r, g, b) = (&hex[0..2], &hex[2..4], &hex[4..6]);
+  format!("&H00{b}{g}{r}").to_uppercase()
+}
+
+/// Escape a path for use as a filter option value
+pub fn escape_filter_path(path: &Path) -> String {
+  path
+    .to_string_lossy()
+    .replace('\\', "/")
+    .replace(':', "\\:")
+    .replace('\'', "\\'")
+}
+
+/// Return `subtitles` filter burning captions into the video
+pub fn burn_in_filter(subtitles_path: &Path, style: &BurnInStyle) -> String {
+  // ASS numpad alignment
+  let alignment = match style.position {
+    CaptionPosition::Top => 8,
+    CaptionPosition::Bottom => 2,
+  };
+
+  format!(
+    "subtitles=filename='{}':force_style='FontSize={},PrimaryColour={},OutlineColour={},BorderStyle=1,Outline=2,Alignment={alignment},MarginV=24'",
+    escape_filter_path(subtitles_path),
+    style.font_size,
+    hex_to_ass_colour(&style.colour),
+    hex_to_ass_colour(&style.outline_colour),
+  )
+}
+
+/// Return subtitle codec supported by the container of `output_path`
+pub fn soft_subtitle_codec(output_path: &Path) -> &'static str {
+  match output_path
+    .extension()
+    .map(|e| e.to_string_lossy().to_lowercase())
+    .as_deref()
+  {
+    Some("mkv") => "srt",
+    Some("webm") => "webvtt",
+    _ => "mov_text",
+  }
+}
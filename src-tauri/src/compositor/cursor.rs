@@ -0,0 +1,231 @@
+use std::path::Path;
+
+use crate::{
+  compositor::{
+    frame::{Color, Frame},
+    models::RecordingGeometry,
+  },
+  project::models::CursorSettings,
+  recording::{
+    input_events::{read_mouse_events, MouseEventRecord},
+    models::RecordingFile,
+  },
+};
+
+/// Duration of a click ripple
+const RIPPLE_MS: u64 = 400;
+/// Ripple radius in logical pixels at the start and end of the ripple
+const RIPPLE_RADIUS: (f64, f64) = (6.0, 28.0);
+const RIPPLE_WIDTH: f64 = 3.0;
+const RIPPLE_COLOR: Color = [255, 255, 255, 200];
+
+/// Arrow outline and fill in logical pixels, tip at the origin
+const ARROW_OUTLINE: [(f64, f64); 7] = [
+  (0.0, 0.0),
+  (0.0, 19.0),
+  (5.0, 14.5),
+  (8.2, 21.5),
+  (11.6, 20.0),
+  (8.5, 13.3),
+  (14.5, 13.3),
+];
+const ARROW_FILL: [(f64, f64); 7] = [
+  (1.3, 3.2),
+  (1.3, 15.8),
+  (5.4, 12.1),
+  (8.8, 19.7),
+  (10.0, 19.2),
+  (6.6, 11.9),
+  (11.4, 11.9),
+];
+
+/// Mouse events of a recording in screen video pixels
+pub struct CursorTrack {
+  /// `(elapsed_ms, x, y)` ordered by time
+  moves: Vec<(u64, f64, f64)>,
+  /// Times of button presses, ordered
+  clicks: Vec<u64>,
+  draw_cursor: bool,
+  click_ripples: bool,
+  /// Video pixels per logical pixel, including the cursor scale
+  cursor_scale: f64,
+  scale_factor: f64,
+}
+
+impl CursorTrack {
+  /// Load the mouse events of the recording, `None` if there is nothing to draw
+  pub fn load(
+    source_folder_path: &Path,
+    geometry: &RecordingGeometry,
+    settings: &CursorSettings,
+  ) -> Option<Self> {
+    if (!settings.visible || geometry.has_system_cursor) && !settings.click_ripples {
+      return None;
+    }
+
+    let events =
+      match read_mouse_events(&source_folder_path.join(RecordingFile::MouseEvents.as_ref())) {
+        Ok(events) => events,
+        Err(e) => {
+          log::warn!("Failed to read mouse events for compositing: {e}");
+          return None;
+        }
+      };
+
+    Self::from_events(events, geometry, settings)
+  }
+
+  /// Track of mouse events already read, `None` if there is nothing to draw
+  pub fn from_events(
+    events: Vec<MouseEventRecord>,
+    geometry: &RecordingGeometry,
+    settings: &CursorSettings,
+  ) -> Option<Self> {
+    let draw_cursor = settings.visible && !geometry.has_system_cursor;
+    if !draw_cursor && !settings.click_ripples {
+      return None;
+    }
+
+    // Global logical coordinates, relative to the recorded area in video pixels
+    let to_video = |x: f64, y: f64| {
+      (
+        (x - geometry.origin_x) * geometry.scale_factor,
+        (y - geometry.origin_y) * geometry.scale_factor,
+      )
+    };
+
+    let mut moves = Vec::new();
+    let mut clicks = Vec::new();
+    for event in events {
+      match event {
+        MouseEventRecord::Move { elapsed_ms, x, y } => {
+          let (x, y) = to_video(x, y);
+          moves.push((elapsed_ms as u64, x, y));
+        }
+        MouseEventRecord::Down { elapsed_ms, .. } => clicks.push(elapsed_ms as u64),
+        MouseEventRecord::Up { .. } => {}
+      }
+    }
+
+    if moves.is_empty() {
+      return None;
+    }
+
+    moves.sort_by_key(|(elapsed_ms, _, _)| *elapsed_ms);
+    clicks.sort_unstable();
+
+    Some(Self {
+      moves,
+      clicks,
+      draw_cursor,
+      click_ripples: settings.click_ripples,
+      cursor_scale: geometry.scale_factor * settings.scale.clamp(0.25, 8.0),
+      scale_factor: geometry.scale_factor,
+    })
+  }
+
+  /// Cursor position at `time_ms`, interpolated between recorded moves
+  pub fn position(&self, time_ms: u64) -> (f64, f64) {
+    let next = self
+      .moves
+      .partition_point(|(elapsed_ms, _, _)| *elapsed_ms <= time_ms);
+    if next == 0 {
+      let (_, x, y) = self.moves[0];
+      return (x, y);
+    }
+
+    let (start_ms, x0, y0) = self.moves[next - 1];
+    let Some(&(end_ms, x1, y1)) = self.moves.get(next) else {
+      return (x0, y0);
+    };
+
+    let t = (time_ms - start_ms) as f64 / (end_ms - start_ms) as f64;
+    (x0 + (x1 - x0) * t, y0 + (y1 - y0) * t)
+  }
+
+  /// Draw ripples and the cursor at `time_ms`.
+  ///
+  /// `to_frame` maps screen video pixels to frame pixels, magnified by `zoom`.
+  pub fn draw(
+    &self,
+    frame: &mut Frame,
+    time_ms: u64,
+    zoom: f64,
+    to_frame: impl Fn(f64, f64) -> (f64, f64),
+  ) {
+    if self.click_ripples {
+      let first = self
+        .clicks
+        .partition_point(|click_ms| click_ms + RIPPLE_MS <= time_ms);
+      for &click_ms in self.clicks[first..].iter().take_while(|ms| **ms <= time_ms) {
+        let (x, y) = self.position(click_ms);
+        let (x, y) = to_frame(x, y);
+        let progress = (time_ms - click_ms) as f64 / RIPPLE_MS as f64;
+        let scale = self.scale_factor * zoom;
+        draw_ripple(frame, (x, y), progress, scale);
+      }
+    }
+
+    if self.draw_cursor {
+      let (x, y) = self.position(time_ms);
+      let tip = to_frame(x, y);
+      let scale = self.cursor_scale * zoom;
+      fill_polygon(frame, &ARROW_OUTLINE, tip, scale, [255, 255, 255, 255]);
+      fill_polygon(frame, &ARROW_FILL, tip, scale, [0, 0, 0, 255]);
+    }
+  }
+}
+
+fn draw_ripple(frame: &mut Frame, (x, y): (f64, f64), progress: f64, scale: f64) {
+  let (start, end) = RIPPLE_RADIUS;
+  let outer = (start + (end - start) * progress) * scale;
+  let inner = (outer - RIPPLE_WIDTH * scale).max(0.0);
+  let (outer_squared, inner_squared) = (outer * outer, inner * inner);
+
+  let mut color = RIPPLE_COLOR;
+  color[3] = (color[3] as f64 * (1.0 - progress)).round() as u8;
+
+  frame.fill_shape(
+    (x - outer, y - outer, x + outer, y + outer),
+    color,
+    |px, py| {
+      let distance_squared = (px - x) * (px - x) + (py - y) * (py - y);
+      distance_squared <= outer_squared && distance_squared >= inner_squared
+    },
+  );
+}
+
+fn fill_polygon(
+  frame: &mut Frame,
+  points: &[(f64, f64)],
+  (x, y): (f64, f64),
+  scale: f64,
+  color: Color,
+) {
+  let points: Vec<(f64, f64)> = points
+    .iter()
+    .map(|(px, py)| (x + px * scale, y + py * scale))
+    .collect();
+
+  let (mut x0, mut y0, mut x1, mut y1) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+  for &(px, py) in &points {
+    x0 = x0.min(px);
+    y0 = y0.min(py);
+    x1 = x1.max(px);
+    y1 = y1.max(py);
+  }
+
+  frame.fill_shape((x0, y0, x1, y1), color, |px, py| {
+    // Even-odd rule
+    let mut inside = false;
+    let mut previous = points[points.len() - 1];
+    for &current in &points {
+      let ((ax, ay), (bx, by)) = (previous, current);
+      if (ay > py) != (by > py) && px < ax + (py - ay) * (bx - ax) / (by - ay) {
+        inside = !inside;
+      }
+      previous = current;
+    }
+    inside
+  });
+}
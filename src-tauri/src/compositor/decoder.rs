@@ -0,0 +1,89 @@
+use std::{
+  io::{ErrorKind, Read},
+  path::Path,
+  process::ChildStdout,
+  thread::JoinHandle,
+};
+
+use ffmpeg_sidecar::{child::FfmpegChild, command::FfmpegCommand};
+
+use crate::{compositor::frame::Frame, recording::ffmpeg::capture_ffmpeg_stderr};
+
+/// ffmpeg decoding a video to RGBA frames at a constant frame rate
+pub struct FrameDecoder {
+  ffmpeg: FfmpegChild,
+  stdout: ChildStdout,
+  stderr_handle: Option<JoinHandle<Vec<String>>>,
+  width: u32,
+  height: u32,
+  tag: String,
+}
+
+impl FrameDecoder {
+  /// Decode `path` from `seek_ms`, `filter` must produce `width`x`height` frames
+  pub fn spawn(
+    path: &Path,
+    seek_ms: u64,
+    frame_rate: u32,
+    filter: &str,
+    (width, height): (u32, u32),
+  ) -> Result<Self, String> {
+    let tag = format!(
+      "[decoder {}]",
+      path.file_name().unwrap_or_default().to_string_lossy()
+    );
+
+    let mut command = FfmpegCommand::new();
+    if seek_ms > 0 {
+      command.args(["-ss", &format!("{:.3}", seek_ms as f64 / 1000.0)]);
+    }
+    command
+      .input(path.to_string_lossy())
+      .args(["-vf", &format!("fps={frame_rate},{filter},format=rgba")])
+      .arg("-an")
+      .format("rawvideo")
+      .output("-");
+
+    let mut ffmpeg = command.spawn().map_err(|e| e.to_string())?;
+    let stderr_handle = capture_ffmpeg_stderr(ffmpeg.take_stderr().unwrap(), tag.clone(), 5);
+    let stdout = ffmpeg.take_stdout().unwrap();
+
+    Ok(Self {
+      ffmpeg,
+      stdout,
+      stderr_handle: Some(stderr_handle),
+      width,
+      height,
+      tag,
+    })
+  }
+
+  /// Next frame, `None` once the video has ended
+  pub fn next_frame(&mut self) -> Result<Option<Frame>, String> {
+    let mut data = vec![0; Frame::byte_len(self.width, self.height)];
+    match self.stdout.read_exact(&mut data) {
+      Ok(()) => Ok(Some(Frame::from_raw(self.width, self.height, data))),
+      Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(None),
+      Err(e) => Err(format!("{} {e}", self.tag)),
+    }
+  }
+
+  /// Error with the decoder's stderr, for when it produced no frames
+  pub fn failure(mut self) -> String {
+    let _ = self.ffmpeg.wait();
+    let stderr_tail = self
+      .stderr_handle
+      .take()
+      .and_then(|handle| handle.join().ok())
+      .unwrap_or_default();
+
+    format!("{} No frames decoded: {}", self.tag, stderr_tail.join("\n"))
+  }
+}
+
+impl Drop for FrameDecoder {
+  fn drop(&mut self) {
+    let _ = self.ffmpeg.kill();
+    let _ = self.ffmpeg.wait(); // Clean up resources
+  }
+}
@@ -0,0 +1,413 @@
+use rayon::{
+  iter::{IndexedParallelIterator, ParallelIterator},
+  slice::ParallelSliceMut,
+};
+
+/// RGBA colour, not premultiplied
+pub type Color = [u8; 4];
+
+/// Sub-samples per axis when rasterizing shapes, coverage has 16 levels
+const SHAPE_SAMPLES: u32 = 4;
+
+/// Box blur passes, three approximate a gaussian blur
+const BLUR_PASSES: usize = 3;
+
+/// 8-bit RGBA image, rows packed without padding
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+  pub width: u32,
+  pub height: u32,
+  pub data: Vec<u8>,
+}
+
+/// Pixel area of a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+  pub x: u32,
+  pub y: u32,
+  pub width: u32,
+  pub height: u32,
+}
+
+/// Area of a frame in fractional pixels, e.g. a zoomed in part of the screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Region {
+  pub x: f64,
+  pub y: f64,
+  pub width: f64,
+  pub height: f64,
+}
+
+impl Region {
+  pub fn full(width: u32, height: u32) -> Self {
+    Self {
+      x: 0.0,
+      y: 0.0,
+      width: width as f64,
+      height: height as f64,
+    }
+  }
+}
+
+/// Source pixels and weight of the second, out of 256, for one output pixel
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+  index: usize,
+  next: usize,
+  weight: u32,
+}
+
+/// Bilinear samples mapping `count` output pixels onto `[start, start + length)` of the source
+fn samples(start: f64, length: f64, count: u32, limit: u32) -> Vec<Sample> {
+  let step = length / count as f64;
+  let last = limit.saturating_sub(1) as f64;
+
+  (0..count)
+    .map(|i| {
+      let position = (start + (i as f64 + 0.5) * step - 0.5).clamp(0.0, last);
+      let index = position.floor() as usize;
+      Sample {
+        index,
+        next: (index + 1).min(last as usize),
+        weight: ((position - index as f64) * 256.0).round() as u32,
+      }
+    })
+    .collect()
+}
+
+/// Blend `color` over the pixel with `coverage` out of 255
+fn blend(pixel: &mut [u8], color: Color, coverage: u32) {
+  let alpha = (color[3] as u32 * coverage + 127) / 255;
+  if alpha == 0 {
+    return;
+  }
+
+  for (channel, value) in pixel.iter_mut().zip(color).take(3) {
+    *channel = ((value as u32 * alpha + *channel as u32 * (255 - alpha) + 127) / 255) as u8;
+  }
+  pixel[3] = (alpha + (pixel[3] as u32 * (255 - alpha) + 127) / 255) as u8;
+}
+
+/// Box blur `lines` lines of `length` pixels in place, edges extended.
+///
+/// Pixels of a line are `pixel_step` bytes apart, lines `line_step` bytes apart.
+fn box_blur(
+  data: &mut [u8],
+  (lines, line_step): (usize, usize),
+  (length, pixel_step): (usize, usize),
+  radius: usize,
+) {
+  let window = 2 * radius as u32 + 1;
+  let mut line: Vec<[u32; 4]> = vec![[0; 4]; length];
+
+  for l in 0..lines {
+    let start = l * line_step;
+    for (i, pixel) in line.iter_mut().enumerate() {
+      let offset = start + i * pixel_step;
+      *pixel = std::array::from_fn(|channel| data[offset + channel] as u32);
+    }
+
+    let at = |i: isize| line[i.clamp(0, length as isize - 1) as usize];
+    let mut sum = [0; 4];
+    for i in -(radius as isize)..=radius as isize {
+      let pixel = at(i);
+      sum = std::array::from_fn(|channel| sum[channel] + pixel[channel]);
+    }
+
+    for i in 0..length {
+      let offset = start + i * pixel_step;
+      for channel in 0..4 {
+        data[offset + channel] = ((sum[channel] + window / 2) / window) as u8;
+      }
+
+      let (removed, added) = (
+        at(i as isize - radius as isize),
+        at((i + radius + 1) as isize),
+      );
+      sum = std::array::from_fn(|channel| sum[channel] + added[channel] - removed[channel]);
+    }
+  }
+}
+
+/// Coverage out of 255 of a pixel inside a rectangle with rounded corners
+fn rounded_coverage(x: u32, y: u32, width: u32, height: u32, radius: f64) -> u32 {
+  if radius <= 0.0 {
+    return 255;
+  }
+
+  // Distance into the corner square, measured from the pixel centre
+  let corner_offset = |position: u32, size: u32| -> f64 {
+    let centre = position as f64 + 0.5;
+    if centre < radius {
+      radius - centre
+    } else if centre > size as f64 - radius {
+      centre - (size as f64 - radius)
+    } else {
+      0.0
+    }
+  };
+
+  let dx = corner_offset(x, width);
+  let dy = corner_offset(y, height);
+  if dx == 0.0 || dy == 0.0 {
+    return 255;
+  }
+
+  let distance = (dx * dx + dy * dy).sqrt();
+  ((radius + 0.5 - distance).clamp(0.0, 1.0) * 255.0).round() as u32
+}
+
+impl Frame {
+  pub fn new(width: u32, height: u32) -> Self {
+    Self {
+      width,
+      height,
+      data: vec![0; Self::byte_len(width, height)],
+    }
+  }
+
+  pub fn from_raw(width: u32, height: u32, data: Vec<u8>) -> Self {
+    Self {
+      width,
+      height,
+      data,
+    }
+  }
+
+  pub fn byte_len(width: u32, height: u32) -> usize {
+    (width * height * 4) as usize
+  }
+
+  fn stride(&self) -> usize {
+    self.width as usize * 4
+  }
+
+  /// Fill with a gradient from `top` to `bottom`
+  pub fn fill_vertical_gradient(&mut self, top: Color, bottom: Color) {
+    let last_row = self.height.saturating_sub(1).max(1);
+    let stride = self.stride();
+
+    self
+      .data
+      .par_chunks_mut(stride)
+      .enumerate()
+      .for_each(|(y, row)| {
+        let t = y as u32;
+        let color: Color = std::array::from_fn(|channel| {
+          ((top[channel] as u32 * (last_row - t) + bottom[channel] as u32 * t + last_row / 2)
+            / last_row) as u8
+        });
+
+        for pixel in row.chunks_exact_mut(4) {
+          pixel.copy_from_slice(&color);
+        }
+      });
+  }
+
+  /// Scale `region` of `source` into `dest` with bilinear filtering, rounding the
+  /// corners of `dest` by `corner_radius`
+  pub fn draw_scaled(&mut self, source: &Frame, region: Region, dest: Rect, corner_radius: f64) {
+    let dest = self.clip(dest);
+    if dest.width == 0 || dest.height == 0 || source.width == 0 || source.height == 0 {
+      return;
+    }
+
+    let columns = samples(region.x, region.width, dest.width, source.width);
+    let rows = samples(region.y, region.height, dest.height, source.height);
+    let stride = self.stride();
+    let source_stride = source.stride();
+    let start = dest.y as usize * stride;
+    let end = (dest.y + dest.height) as usize * stride;
+
+    self.data[start..end]
+      .par_chunks_mut(stride)
+      .enumerate()
+      .for_each(|(dy, row)| {
+        let sample_row = rows[dy];
+        let top = &source.data[sample_row.index * source_stride..][..source_stride];
+        let bottom = &source.data[sample_row.next * source_stride..][..source_stride];
+        let wy = sample_row.weight;
+
+        for (dx, column) in columns.iter().enumerate() {
+          let coverage =
+            rounded_coverage(dx as u32, dy as u32, dest.width, dest.height, corner_radius);
+          if coverage == 0 {
+            continue;
+          }
+
+          let (a, b) = (column.index * 4, column.next * 4);
+          let wx = column.weight;
+          let color: Color = std::array::from_fn(|channel| {
+            let upper = top[a + channel] as u32 * (256 - wx) + top[b + channel] as u32 * wx;
+            let lower = bottom[a + channel] as u32 * (256 - wx) + bottom[b + channel] as u32 * wx;
+            ((upper * (256 - wy) + lower * wy + 32768) >> 16) as u8
+          });
+
+          let offset = (dest.x as usize + dx) * 4;
+          let pixel = &mut row[offset..offset + 4];
+          if coverage == 255 && color[3] == 255 {
+            pixel.copy_from_slice(&color);
+          } else {
+            blend(pixel, color, coverage);
+          }
+        }
+      });
+  }
+
+  /// Copy `source` with its top left corner at `(x, y)`, clipped to the frame
+  pub fn blit(&mut self, source: &Frame, x: u32, y: u32) {
+    let dest = self.clip(Rect {
+      x,
+      y,
+      width: source.width,
+      height: source.height,
+    });
+    if dest.width == 0 || dest.height == 0 {
+      return;
+    }
+
+    let stride = self.stride();
+    let source_stride = source.stride();
+    let row_bytes = dest.width as usize * 4;
+    for row in 0..dest.height as usize {
+      let target = (dest.y as usize + row) * stride + dest.x as usize * 4;
+      let from = row * source_stride;
+      self.data[target..target + row_bytes].copy_from_slice(&source.data[from..from + row_bytes]);
+    }
+  }
+
+  /// Blend `color` over the pixels for which `inside` holds, anti-aliased by
+  /// sub-sampling each pixel within `bounds` (`x0, y0, x1, y1`)
+  pub fn fill_shape(
+    &mut self,
+    bounds: (f64, f64, f64, f64),
+    color: Color,
+    inside: impl Fn(f64, f64) -> bool,
+  ) {
+    let (x0, y0, x1, y1) = bounds;
+    let min_x = x0.floor().max(0.0) as u32;
+    let min_y = y0.floor().max(0.0) as u32;
+    let max_x = (x1.ceil().max(0.0) as u32).min(self.width);
+    let max_y = (y1.ceil().max(0.0) as u32).min(self.height);
+    let stride = self.stride();
+    let total = SHAPE_SAMPLES * SHAPE_SAMPLES;
+
+    for y in min_y..max_y {
+      for x in min_x..max_x {
+        let mut hits = 0;
+        for sy in 0..SHAPE_SAMPLES {
+          for sx in 0..SHAPE_SAMPLES {
+            let px = x as f64 + (sx as f64 + 0.5) / SHAPE_SAMPLES as f64;
+            let py = y as f64 + (sy as f64 + 0.5) / SHAPE_SAMPLES as f64;
+            if inside(px, py) {
+              hits += 1;
+            }
+          }
+        }
+
+        if hits > 0 {
+          let offset = y as usize * stride + x as usize * 4;
+          blend(
+            &mut self.data[offset..offset + 4],
+            color,
+            (hits * 255 + total / 2) / total,
+          );
+        }
+      }
+    }
+  }
+
+  /// Replace `rect` with `color`
+  pub fn fill_rect(&mut self, rect: Rect, color: Color) {
+    let rect = self.clip(rect);
+    let stride = self.stride();
+    for row in rect.y..rect.y + rect.height {
+      let start = row as usize * stride + rect.x as usize * 4;
+      for pixel in self.data[start..start + rect.width as usize * 4].chunks_exact_mut(4) {
+        pixel.copy_from_slice(&color);
+      }
+    }
+  }
+
+  /// Replace `rect` with squares of `block_size` filled with their average colour
+  pub fn pixelate(&mut self, rect: Rect, block_size: u32) {
+    let rect = self.clip(rect);
+    let block_size = block_size.max(1);
+    let stride = self.stride();
+
+    for block_y in (rect.y..rect.y + rect.height).step_by(block_size as usize) {
+      let block_height = block_size.min(rect.y + rect.height - block_y);
+      for block_x in (rect.x..rect.x + rect.width).step_by(block_size as usize) {
+        let block = Rect {
+          x: block_x,
+          y: block_y,
+          width: block_size.min(rect.x + rect.width - block_x),
+          height: block_height,
+        };
+
+        let mut sum = [0u64; 4];
+        for row in block.y..block.y + block.height {
+          let start = row as usize * stride + block.x as usize * 4;
+          for pixel in self.data[start..start + block.width as usize * 4].chunks_exact(4) {
+            sum = std::array::from_fn(|channel| sum[channel] + pixel[channel] as u64);
+          }
+        }
+
+        let count = (block.width * block.height) as u64;
+        let color: Color =
+          std::array::from_fn(|channel| ((sum[channel] + count / 2) / count) as u8);
+        self.fill_rect(block, color);
+      }
+    }
+  }
+
+  /// Blur `rect` with a box blur of `radius`, without sampling pixels outside it
+  pub fn blur(&mut self, rect: Rect, radius: u32) {
+    let rect = self.clip(rect);
+    if rect.width == 0 || rect.height == 0 || radius == 0 {
+      return;
+    }
+
+    let stride = self.stride();
+    let row_bytes = rect.width as usize * 4;
+    let mut pixels = Vec::with_capacity(row_bytes * rect.height as usize);
+    for row in rect.y..rect.y + rect.height {
+      let start = row as usize * stride + rect.x as usize * 4;
+      pixels.extend_from_slice(&self.data[start..start + row_bytes]);
+    }
+
+    let (width, height) = (rect.width as usize, rect.height as usize);
+    let radius = (radius as usize).min(width.max(height));
+    for _ in 0..BLUR_PASSES {
+      box_blur(&mut pixels, (height, row_bytes), (width, 4), radius);
+      box_blur(&mut pixels, (width, 4), (height, row_bytes), radius);
+    }
+
+    for (row, source) in pixels.chunks_exact(row_bytes).enumerate() {
+      let start = (rect.y as usize + row) * stride + rect.x as usize * 4;
+      self.data[start..start + row_bytes].copy_from_slice(source);
+    }
+  }
+
+  fn clip(&self, rect: Rect) -> Rect {
+    let x = rect.x.min(self.width);
+    let y = rect.y.min(self.height);
+    Rect {
+      x,
+      y,
+      width: rect.width.min(self.width - x),
+      height: rect.height.min(self.height - y),
+    }
+  }
+}
+
+/// Parse a `#rrggbb` colour
+pub fn parse_hex_color(value: &str) -> Option<Color> {
+  let hex = value.strip_prefix('#')?;
+  if hex.len() != 6 {
+    return None;
+  }
+
+  let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+  Some([channel(0)?, channel(2)?, channel(4)?, 255])
+}
@@ -0,0 +1,6 @@
+mod cursor;
+mod decoder;
+pub mod frame;
+pub mod models;
+pub mod redaction;
+pub mod service;
@@ -0,0 +1,26 @@
+use crate::db::recordings::RecordingRow;
+
+/// Frame rate of composited video, the rate screen recordings are captured at
+pub const COMPOSITOR_FRAME_RATE: u32 = 60;
+
+/// Where the recording was captured, to place mouse events on the screen video
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RecordingGeometry {
+  /// Top left of the recorded area in global logical coordinates
+  pub origin_x: f64,
+  pub origin_y: f64,
+  pub scale_factor: f64,
+  /// The system cursor is part of the screen video, so is not drawn again
+  pub has_system_cursor: bool,
+}
+
+impl From<&RecordingRow> for RecordingGeometry {
+  fn from(row: &RecordingRow) -> Self {
+    Self {
+      origin_x: row.origin_x,
+      origin_y: row.origin_y,
+      scale_factor: row.scale_factor,
+      has_system_cursor: row.has_system_cursor,
+    }
+  }
+}
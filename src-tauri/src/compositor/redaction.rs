@@ -0,0 +1,214 @@
+use crate::{
+  compositor::frame::{parse_hex_color, Frame, Rect},
+  project::models::{Redaction, RedactionArea, RedactionKeyframe, RedactionStyle},
+};
+
+/// Redactions of a project, ready to be applied to screen frames
+pub struct Redactions {
+  /// Ordered by start time, keyframes ordered by time
+  redactions: Vec<Redaction>,
+}
+
+impl Redactions {
+  pub fn new(redactions: &[Redaction]) -> Self {
+    let mut redactions: Vec<Redaction> = redactions
+      .iter()
+      .filter(|redaction| redaction.end_ms > redaction.start_ms)
+      .cloned()
+      .collect();
+
+    redactions.sort_by_key(|redaction| redaction.start_ms);
+    for redaction in &mut redactions {
+      redaction.keyframes.sort_by_key(|keyframe| keyframe.time_ms);
+    }
+
+    Self { redactions }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.redactions.is_empty()
+  }
+
+  /// Hide the areas redacted at `time_ms` of a screen frame.
+  ///
+  /// `scale` is frame pixels per screen video pixel, e.g. below `1.0` for thumbnails.
+  pub fn apply(&self, frame: &mut Frame, time_ms: u64, scale: f64) {
+    let active = self
+      .redactions
+      .iter()
+      .take_while(|redaction| redaction.start_ms <= time_ms)
+      .filter(|redaction| time_ms < redaction.end_ms);
+
+    for redaction in active {
+      let rect = to_rect(area_at(redaction, time_ms), frame);
+      if rect.width == 0 || rect.height == 0 {
+        continue;
+      }
+
+      let scaled = |size: u32| ((size as f64 * scale).round() as u32).max(1);
+      match &redaction.style {
+        RedactionStyle::Blur { radius } => frame.blur(rect, scaled(*radius)),
+        RedactionStyle::Pixelate { block_size } => frame.pixelate(rect, scaled(*block_size)),
+        RedactionStyle::Fill { color } => {
+          frame.fill_rect(rect, parse_hex_color(color).unwrap_or([0, 0, 0, 255]))
+        }
+      }
+    }
+  }
+}
+
+/// Redaction during `[start_ms, end_ms)` following an area sampled while recording,
+/// e.g. a window. `None` if the area is not visible in that time.
+///
+/// `samples` are ordered by time and only taken when the area changed, `None`
+/// while it is not visible. Keyframes hold each area until `sample_interval_ms`
+/// before the next, so a move is not spread over the time the area was still.
+pub fn track_area(
+  samples: &[(u64, Option<RedactionArea>)],
+  (start_ms, end_ms): (u64, u64),
+  sample_interval_ms: u64,
+) -> Option<Redaction> {
+  let start = samples.partition_point(|(time_ms, _)| *time_ms <= start_ms);
+  let during: Vec<(u64, RedactionArea)> = samples[start..]
+    .iter()
+    .take_while(|(time_ms, _)| *time_ms < end_ms)
+    .filter_map(|(time_ms, area)| area.map(|area| (*time_ms, area)))
+    .collect();
+
+  // Area at the start, otherwise where it first appears
+  let area = samples[..start]
+    .iter()
+    .rev()
+    .find_map(|(_, area)| *area)
+    .or_else(|| during.first().map(|(_, area)| *area))?;
+
+  let mut keyframes: Vec<RedactionKeyframe> = Vec::new();
+  let mut previous = area;
+  for (time_ms, area) in during {
+    if area == previous {
+      continue;
+    }
+
+    let hold_ms = time_ms.saturating_sub(sample_interval_ms).max(start_ms);
+    if hold_ms
+      > keyframes
+        .last()
+        .map_or(start_ms, |keyframe| keyframe.time_ms)
+    {
+      keyframes.push(RedactionKeyframe {
+        time_ms: hold_ms,
+        area: previous,
+      });
+    }
+    keyframes.push(RedactionKeyframe { time_ms, area });
+    previous = area;
+  }
+
+  Some(Redaction {
+    start_ms,
+    end_ms,
+    area,
+    keyframes,
+    style: RedactionStyle::default(),
+  })
+}
+
+/// Area at `time_ms`, interpolated between the start area and keyframes
+fn area_at(redaction: &Redaction, time_ms: u64) -> RedactionArea {
+  let keyframes = &redaction.keyframes;
+  let next = keyframes.partition_point(|keyframe| keyframe.time_ms <= time_ms);
+
+  let (start_ms, from) = match next.checked_sub(1) {
+    Some(previous) => (keyframes[previous].time_ms, keyframes[previous].area),
+    None => (redaction.start_ms, redaction.area),
+  };
+  let Some(to) = keyframes.get(next) else {
+    return from;
+  };
+
+  let t = (time_ms.saturating_sub(start_ms)) as f64 / (to.time_ms - start_ms).max(1) as f64;
+  let lerp = |a: f64, b: f64| a + (b - a) * t.clamp(0.0, 1.0);
+  RedactionArea {
+    x: lerp(from.x, to.area.x),
+    y: lerp(from.y, to.area.y),
+    width: lerp(from.width, to.area.width),
+    height: lerp(from.height, to.area.height),
+  }
+}
+
+/// Pixels of `frame` covered by `area`, rounded outwards so nothing peeks through
+fn to_rect(area: RedactionArea, frame: &Frame) -> Rect {
+  let (width, height) = (frame.width as f64, frame.height as f64);
+  let x0 = (area.x.clamp(0.0, 1.0) * width).floor();
+  let y0 = (area.y.clamp(0.0, 1.0) * height).floor();
+  let x1 = ((area.x + area.width).clamp(0.0, 1.0) * width).ceil();
+  let y1 = ((area.y + area.height).clamp(0.0, 1.0) * height).ceil();
+
+  Rect {
+    x: x0 as u32,
+    y: y0 as u32,
+    width: (x1 - x0).max(0.0) as u32,
+    height: (y1 - y0).max(0.0) as u32,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn area(x: f64) -> RedactionArea {
+    RedactionArea {
+      x,
+      y: 0.1,
+      width: 0.2,
+      height: 0.2,
+    }
+  }
+
+  fn keyframe(time_ms: u64, x: f64) -> RedactionKeyframe {
+    RedactionKeyframe {
+      time_ms,
+      area: area(x),
+    }
+  }
+
+  #[test]
+  fn track_area_holds_between_moves() {
+    let samples = [
+      (0, Some(area(0.1))),
+      (2000, Some(area(0.3))),
+      (2100, Some(area(0.4))),
+      (5000, Some(area(0.5))),
+    ];
+    let redaction = track_area(&samples, (1000, 4000), 100).unwrap();
+
+    assert_eq!(redaction.area, area(0.1));
+    assert_eq!(
+      redaction.keyframes,
+      [
+        keyframe(1900, 0.1),
+        keyframe(2000, 0.3),
+        keyframe(2100, 0.4)
+      ]
+    );
+    assert_eq!(area_at(&redaction, 1500), area(0.1));
+    assert_eq!(area_at(&redaction, 3000), area(0.4));
+  }
+
+  #[test]
+  fn track_area_starts_where_it_appears() {
+    let samples = [(0, None), (1500, Some(area(0.2))), (1600, None)];
+    let redaction = track_area(&samples, (1000, 3000), 100).unwrap();
+
+    assert_eq!(redaction.area, area(0.2));
+    assert!(redaction.keyframes.is_empty());
+  }
+
+  #[test]
+  fn track_area_not_visible() {
+    let samples = [(0, None), (1500, Some(area(0.2)))];
+
+    assert_eq!(track_area(&samples, (0, 1000), 100), None);
+    assert_eq!(track_area(&[], (0, 1000), 100), None);
+  }
+}
@@ -0,0 +1,566 @@
+use std::{io::Write, path::Path};
+
+use crate::{
+  compositor::{
+    cursor::CursorTrack,
+    decoder::FrameDecoder,
+    frame::{parse_hex_color, Frame, Rect, Region},
+    models::{RecordingGeometry, COMPOSITOR_FRAME_RATE},
+    redaction::Redactions,
+  },
+  project::models::{CameraLayout, CameraPosition, Project, ZoomRange},
+  recording::{ffmpeg::video_dimensions, models::RecordingFile},
+};
+
+const MAX_ZOOM_SCALE: f64 = 10.0;
+
+/// Time to ease into and out of a zoom
+const ZOOM_TRANSITION_MS: u64 = 400;
+
+/// Smallest screen area left by background padding
+const MIN_SCREEN_SIZE: u32 = 16;
+
+/// Composites the screen, redactions, camera, cursor and background of a recording into
+/// the frames of the exported video.
+///
+/// Output only depends on the decoded frames, the project and the frame time,
+/// the same inputs always produce the same pixels.
+pub struct Compositor {
+  /// Even, as required by yuv420p encoders
+  screen_size: (u32, u32),
+  /// Where the screen is drawn in the output
+  screen_rect: Rect,
+  corner_radius: f64,
+  /// Output size frame with the background drawn, copied for every frame
+  background: Frame,
+  zooms: Vec<ZoomRange>,
+  redactions: Redactions,
+  camera: Option<CameraPlacement>,
+  cursor: Option<CursorTrack>,
+}
+
+/// Size and position of the burned in camera
+struct CameraPlacement {
+  size: (u32, u32),
+  x: u32,
+  y: u32,
+}
+
+fn even(size: u32) -> u32 {
+  (size & !1).max(2)
+}
+
+impl Compositor {
+  pub fn new(
+    source_folder_path: &Path,
+    project: &Project,
+    geometry: Option<&RecordingGeometry>,
+  ) -> Result<Self, String> {
+    let screen_size = video_dimensions(&source_folder_path.join(RecordingFile::Screen.as_ref()))
+      .ok_or("Failed to probe screen size")?;
+
+    let camera_path = source_folder_path.join(RecordingFile::Camera.as_ref());
+    let camera_size =
+      if project.camera.visible && !project.separate_camera_file && camera_path.exists() {
+        video_dimensions(&camera_path)
+      } else {
+        None
+      };
+
+    let cursor = geometry
+      .and_then(|geometry| CursorTrack::load(source_folder_path, geometry, &project.cursor));
+
+    Ok(Self::with_inputs(project, screen_size, camera_size, cursor))
+  }
+
+  /// Compositor for a screen and burned in camera of the given sizes
+  fn with_inputs(
+    project: &Project,
+    (width, height): (u32, u32),
+    camera_size: Option<(u32, u32)>,
+    cursor: Option<CursorTrack>,
+  ) -> Self {
+    let screen_size = (even(width), even(height));
+    let (width, height) = screen_size;
+
+    let mut background = Frame::new(width, height);
+    let (screen_rect, corner_radius) = if project.background.enabled {
+      let settings = &project.background;
+      let top = parse_hex_color(&settings.color_top).unwrap_or([0, 0, 0, 255]);
+      let bottom = parse_hex_color(&settings.color_bottom).unwrap_or(top);
+      background.fill_vertical_gradient(top, bottom);
+
+      let max_padding = (width.min(height).saturating_sub(MIN_SCREEN_SIZE)) / 2;
+      let padding = settings.padding.min(max_padding);
+      (
+        fit_rect(screen_size, padding),
+        settings.corner_radius as f64,
+      )
+    } else {
+      (
+        Rect {
+          x: 0,
+          y: 0,
+          width,
+          height,
+        },
+        0.0,
+      )
+    };
+
+    let camera =
+      camera_size.map(|camera_size| place_camera(&project.camera, camera_size, screen_size));
+
+    let mut zooms = project.zooms.clone();
+    zooms.sort_by_key(|zoom| zoom.start_ms);
+    let mut zoom_end = 0;
+    // Overlapping zooms are resolved in favour of the earlier one
+    zooms.retain_mut(|zoom| {
+      zoom.start_ms = zoom.start_ms.max(zoom_end);
+      if zoom.end_ms <= zoom.start_ms || zoom.scale <= 1.0 {
+        return false;
+      }
+      zoom_end = zoom.end_ms;
+      true
+    });
+
+    Self {
+      screen_size,
+      screen_rect,
+      corner_radius,
+      background,
+      zooms,
+      redactions: Redactions::new(&project.redactions),
+      camera,
+      cursor,
+    }
+  }
+
+  /// Size of the composited frames
+  pub fn output_size(&self) -> (u32, u32) {
+    (self.background.width, self.background.height)
+  }
+
+  /// Composite the frame at `time_ms` of the recording timeline
+  pub fn compose(&self, mut screen: Frame, camera: Option<&Frame>, time_ms: u64) -> Frame {
+    let mut frame = self.background.clone();
+
+    self.redactions.apply(&mut screen, time_ms, 1.0);
+    let region = self.zoom_region(time_ms);
+    frame.draw_scaled(&screen, region, self.screen_rect, self.corner_radius);
+
+    if let Some(cursor) = &self.cursor {
+      let rect = self.screen_rect;
+      let magnification = rect.width as f64 / region.width;
+      cursor.draw(&mut frame, time_ms, magnification, |x, y| {
+        (
+          rect.x as f64 + (x - region.x) * magnification,
+          rect.y as f64 + (y - region.y) * rect.height as f64 / region.height,
+        )
+      });
+    }
+
+    if let (Some(placement), Some(camera)) = (&self.camera, camera) {
+      frame.blit(camera, placement.x, placement.y);
+    }
+
+    frame
+  }
+
+  /// Part of the screen shown at `time_ms`, easing in and out of each zoom
+  fn zoom_region(&self, time_ms: u64) -> Region {
+    let (width, height) = self.screen_size;
+    let index = self.zooms.partition_point(|zoom| zoom.start_ms <= time_ms);
+    let Some(zoom) = index
+      .checked_sub(1)
+      .map(|i| &self.zooms[i])
+      .filter(|zoom| time_ms < zoom.end_ms)
+    else {
+      return Region::full(width, height);
+    };
+
+    let transition = ZOOM_TRANSITION_MS
+      .min((zoom.end_ms - zoom.start_ms) / 2)
+      .max(1);
+    let progress =
+      ((time_ms - zoom.start_ms).min(zoom.end_ms - time_ms) as f64 / transition as f64).min(1.0);
+    let eased = progress * progress * (3.0 - 2.0 * progress);
+    let scale = 1.0 + (zoom.scale.min(MAX_ZOOM_SCALE) - 1.0) * eased;
+
+    let (center_x, center_y) = match &self.cursor {
+      Some(cursor) if zoom.follow_cursor => cursor.position(time_ms),
+      _ => (zoom.center_x * width as f64, zoom.center_y * height as f64),
+    };
+
+    let (width, height) = (width as f64, height as f64);
+    let region_width = width / scale;
+    let region_height = height / scale;
+    Region {
+      x: (center_x - region_width / 2.0).clamp(0.0, width - region_width),
+      y: (center_y - region_height / 2.0).clamp(0.0, height - region_height),
+      width: region_width,
+      height: region_height,
+    }
+  }
+
+  fn screen_decoder(
+    &self,
+    source_folder_path: &Path,
+    seek_ms: u64,
+  ) -> Result<FrameDecoder, String> {
+    let (width, height) = self.screen_size;
+    FrameDecoder::spawn(
+      &source_folder_path.join(RecordingFile::Screen.as_ref()),
+      seek_ms,
+      COMPOSITOR_FRAME_RATE,
+      &format!("crop={width}:{height}:0:0"),
+      self.screen_size,
+    )
+  }
+
+  fn camera_decoder(
+    &self,
+    source_folder_path: &Path,
+    seek_ms: u64,
+  ) -> Result<Option<FrameDecoder>, String> {
+    let Some(placement) = &self.camera else {
+      return Ok(None);
+    };
+
+    let (width, height) = placement.size;
+    FrameDecoder::spawn(
+      &source_folder_path.join(RecordingFile::Camera.as_ref()),
+      seek_ms,
+      COMPOSITOR_FRAME_RATE,
+      &format!("scale={width}:{height}"),
+      placement.size,
+    )
+    .map(Some)
+  }
+
+  /// Decode and composite every frame, writing them to `output` as raw RGBA.
+  ///
+  /// Stops early without error if `output` is closed, e.g. the encoder was
+  /// cancelled. Returns the number of frames written.
+  pub fn write_frames(
+    &self,
+    source_folder_path: &Path,
+    mut output: impl Write,
+  ) -> Result<u64, String> {
+    let mut screen = self.screen_decoder(source_folder_path, 0)?;
+    let mut camera = self.camera_decoder(source_folder_path, 0)?;
+    // Held once the camera ends before the screen
+    let mut camera_frame = None;
+
+    let mut index = 0;
+    while let Some(screen_frame) = screen.next_frame()? {
+      if let Some(decoder) = &mut camera {
+        match decoder.next_frame()? {
+          Some(frame) => camera_frame = Some(frame),
+          None => camera = None,
+        }
+      }
+
+      let frame = self.compose(screen_frame, camera_frame.as_ref(), frame_time_ms(index));
+      if let Err(e) = output.write_all(&frame.data) {
+        log::info!("Stopped compositing, output closed: {e}");
+        return Ok(index);
+      }
+
+      index += 1;
+    }
+
+    if index == 0 {
+      return Err(screen.failure());
+    }
+
+    Ok(index)
+  }
+
+  /// Decode and composite the single frame at `position_ms`
+  pub fn render(&self, source_folder_path: &Path, position_ms: u64) -> Result<Frame, String> {
+    let mut screen = self.screen_decoder(source_folder_path, position_ms)?;
+    let Some(screen_frame) = screen.next_frame()? else {
+      return Err(screen.failure());
+    };
+
+    let camera_frame = match self.camera_decoder(source_folder_path, position_ms)? {
+      Some(mut decoder) => decoder.next_frame()?,
+      None => None,
+    };
+
+    Ok(self.compose(screen_frame, camera_frame.as_ref(), position_ms))
+  }
+}
+
+/// Screen frame at `time_ms` scaled to `width`, with `redactions` applied.
+///
+/// Used for thumbnails, which show the screen without the rest of the composition.
+pub fn render_redacted_screen(
+  source_folder_path: &Path,
+  redactions: &Redactions,
+  time_ms: u64,
+  width: u32,
+) -> Result<Frame, String> {
+  let screen_path = source_folder_path.join(RecordingFile::Screen.as_ref());
+  let (screen_width, screen_height) =
+    video_dimensions(&screen_path).ok_or("Failed to probe screen size")?;
+  let width = even(width);
+  let height =
+    even((width as f64 * screen_height as f64 / screen_width.max(1) as f64).round() as u32);
+
+  let mut decoder = FrameDecoder::spawn(
+    &screen_path,
+    time_ms,
+    COMPOSITOR_FRAME_RATE,
+    &format!("scale={width}:{height}"),
+    (width, height),
+  )?;
+  let Some(mut frame) = decoder.next_frame()? else {
+    return Err(decoder.failure());
+  };
+
+  redactions.apply(
+    &mut frame,
+    time_ms,
+    width as f64 / screen_width.max(1) as f64,
+  );
+  Ok(frame)
+}
+
+/// Time of frame `index` of the composited video
+fn frame_time_ms(index: u64) -> u64 {
+  index * 1000 / COMPOSITOR_FRAME_RATE as u64
+}
+
+/// Largest area with the aspect ratio of `size`, centred inside `padding`
+fn fit_rect((width, height): (u32, u32), padding: u32) -> Rect {
+  let available_width = width - 2 * padding;
+  let available_height = height - 2 * padding;
+  let scale = (available_width as f64 / width as f64).min(available_height as f64 / height as f64);
+  let fitted_width = ((width as f64 * scale).round() as u32).min(available_width);
+  let fitted_height = ((height as f64 * scale).round() as u32).min(available_height);
+
+  Rect {
+    x: (width - fitted_width) / 2,
+    y: (height - fitted_height) / 2,
+    width: fitted_width,
+    height: fitted_height,
+  }
+}
+
+/// Camera scaled to the layout width in the corner chosen by the layout
+fn place_camera(
+  layout: &CameraLayout,
+  (camera_width, camera_height): (u32, u32),
+  (width, height): (u32, u32),
+) -> CameraPlacement {
+  let scaled_width = even(layout.width.min(width));
+  let scaled_height = even(
+    ((scaled_width as f64 * camera_height as f64 / camera_width.max(1) as f64).round() as u32)
+      .min(height),
+  );
+
+  let right = width.saturating_sub(scaled_width + layout.margin);
+  let bottom = height.saturating_sub(scaled_height + layout.margin);
+  let (x, y) = match layout.position {
+    CameraPosition::TopLeft => (layout.margin, layout.margin),
+    CameraPosition::TopRight => (right, layout.margin),
+    CameraPosition::BottomLeft => (layout.margin, bottom),
+    CameraPosition::BottomRight => (right, bottom),
+  };
+
+  CameraPlacement {
+    size: (scaled_width, scaled_height),
+    x,
+    y,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rdev::Button;
+
+  use super::*;
+  use crate::{
+    project::models::{BackgroundSettings, CursorSettings},
+    recording::input_events::MouseEventRecord,
+  };
+
+  const SCREEN_SIZE: (u32, u32) = (64, 36);
+  const CAMERA_SIZE: (u32, u32) = (16, 12);
+
+  /// Screen with a distinct colour per pixel, so any misplaced pixel changes the hash
+  fn screen() -> Frame {
+    let (width, height) = SCREEN_SIZE;
+    let data = (0..height)
+      .flat_map(|y| {
+        (0..width).flat_map(move |x| [(x * 4) as u8, (y * 7) as u8, (x + y) as u8, 255])
+      })
+      .collect();
+    Frame::from_raw(width, height, data)
+  }
+
+  fn camera() -> Frame {
+    let (width, height) = CAMERA_SIZE;
+    let data = (0..width * height)
+      .flat_map(|i| [200, (i * 3) as u8, 40, 255])
+      .collect();
+    Frame::from_raw(width, height, data)
+  }
+
+  /// FNV-1a, stable across platforms and toolchains unlike `DefaultHasher`
+  fn hash(frame: &Frame) -> u64 {
+    frame.data.iter().fold(0xcbf29ce484222325, |hash, byte| {
+      (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+  }
+
+  fn compose(compositor: &Compositor, time_ms: u64) -> Frame {
+    compositor.compose(screen(), Some(&camera()), time_ms)
+  }
+
+  fn project() -> Project {
+    Project {
+      cursor: CursorSettings {
+        visible: false,
+        click_ripples: false,
+        ..Default::default()
+      },
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn unedited_screen_is_unchanged() {
+    let compositor = Compositor::with_inputs(&project(), SCREEN_SIZE, None, None);
+
+    assert_eq!(compositor.output_size(), SCREEN_SIZE);
+    assert_eq!(compose(&compositor, 0), screen());
+  }
+
+  #[test]
+  fn background() {
+    let project = Project {
+      background: BackgroundSettings {
+        enabled: true,
+        color_top: "#ff0000".to_string(),
+        color_bottom: "#0000ff".to_string(),
+        padding: 6,
+        corner_radius: 4,
+      },
+      ..project()
+    };
+    let compositor = Compositor::with_inputs(&project, SCREEN_SIZE, None, None);
+    let frame = compose(&compositor, 0);
+
+    assert_eq!(&frame.data[..4], &[255, 0, 0, 255]);
+    assert_eq!(hash(&frame), BACKGROUND_HASH);
+  }
+
+  #[test]
+  fn camera_placement() {
+    let mut project = project();
+    project.camera.width = 16;
+    project.camera.margin = 4;
+    let compositor = Compositor::with_inputs(&project, SCREEN_SIZE, Some(CAMERA_SIZE), None);
+    let frame = compose(&compositor, 0);
+
+    // Bottom right, inset by the margin
+    let (x, y) = (64 - 16 - 4, 36 - 12 - 4);
+    let camera = camera();
+    for row in 0..CAMERA_SIZE.1 {
+      let start = (((y + row) * frame.width + x) * 4) as usize;
+      let camera_start = (row * CAMERA_SIZE.0 * 4) as usize;
+      assert_eq!(
+        frame.data[start..start + 16 * 4],
+        camera.data[camera_start..camera_start + 16 * 4]
+      );
+    }
+    assert_eq!(hash(&frame), CAMERA_HASH);
+  }
+
+  #[test]
+  fn zoom() {
+    let project = Project {
+      zooms: vec![ZoomRange {
+        start_ms: 1000,
+        end_ms: 3000,
+        scale: 2.0,
+        center_x: 0.25,
+        center_y: 0.5,
+        follow_cursor: false,
+      }],
+      ..project()
+    };
+    let compositor = Compositor::with_inputs(&project, SCREEN_SIZE, None, None);
+
+    assert_eq!(compose(&compositor, 0), screen());
+    assert_eq!(hash(&compose(&compositor, 1200)), ZOOM_EASING_HASH);
+    assert_eq!(hash(&compose(&compositor, 2000)), ZOOM_HASH);
+    assert_eq!(compose(&compositor, 3000), screen());
+  }
+
+  #[test]
+  fn cursor_and_click_ripple() {
+    let geometry = RecordingGeometry {
+      origin_x: 100.0,
+      origin_y: 50.0,
+      scale_factor: 1.0,
+      has_system_cursor: false,
+    };
+    let events = vec![
+      MouseEventRecord::Move {
+        elapsed_ms: 0,
+        x: 110.0,
+        y: 55.0,
+      },
+      MouseEventRecord::Move {
+        elapsed_ms: 200,
+        x: 130.0,
+        y: 65.0,
+      },
+      MouseEventRecord::Down {
+        elapsed_ms: 100,
+        button: Button::Left,
+      },
+    ];
+    let project = Project {
+      cursor: CursorSettings::default(),
+      ..project()
+    };
+    let cursor = CursorTrack::from_events(events, &geometry, &project.cursor);
+    let compositor = Compositor::with_inputs(&project, SCREEN_SIZE, None, cursor);
+
+    // Halfway between the moves the tip is at (20, 10), the outline left of the fill
+    let frame = compose(&compositor, 100);
+    let outline = ((20 * frame.width + 20) * 4) as usize;
+    assert_eq!(&frame.data[outline..outline + 4], &[255, 255, 255, 255]);
+    assert_eq!(hash(&frame), CURSOR_HASH);
+    assert_eq!(hash(&compose(&compositor, 300)), CURSOR_AFTER_RIPPLE_HASH);
+  }
+
+  #[test]
+  fn output_is_deterministic() {
+    let mut project = project();
+    project.background.enabled = true;
+    project.zooms = vec![ZoomRange {
+      start_ms: 0,
+      end_ms: 1000,
+      scale: 1.5,
+      center_x: 0.5,
+      center_y: 0.5,
+      follow_cursor: false,
+    }];
+    let compositor = Compositor::with_inputs(&project, SCREEN_SIZE, Some(CAMERA_SIZE), None);
+
+    assert_eq!(compose(&compositor, 250), compose(&compositor, 250));
+  }
+
+  const BACKGROUND_HASH: u64 = 18164818673778139301;
+  const CAMERA_HASH: u64 = 8158646556820066901;
+  const ZOOM_EASING_HASH: u64 = 1032846808881328853;
+  const ZOOM_HASH: u64 = 16709316510694536613;
+  const CURSOR_HASH: u64 = 9463530670367729885;
+  const CURSOR_AFTER_RIPPLE_HASH: u64 = 7045506680318961490;
+}
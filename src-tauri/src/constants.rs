@@ -3,6 +3,13 @@ use strum_macros::{AsRefStr, Display, EnumString};
 pub mod store {
   pub const STORE_NAME: &str = "orbit-cursor-store.json";
   pub const FIRST_RUN: &str = "firstRun";
+  pub const GENERATE_PROXY_MEDIA: &str = "generateProxyMedia";
+  pub const RETENTION_SETTINGS: &str = "retentionSettings";
+  /// Absolute path, unset for the default inside app data
+  pub const RECORDINGS_ROOT: &str = "recordingsRoot";
+  pub const STORAGE_OPTIMIZATION: &str = "storageOptimization";
+  /// User export presets, built in presets are not stored
+  pub const EXPORT_PRESETS: &str = "exportPresets";
   #[cfg(target_os = "macos")]
   pub const NATIVE_REQUESTABLE_PERMISSIONS: &str = "nativeRequestablePermissions";
 }
@@ -56,6 +63,42 @@ pub enum Events {
 
   #[strum(serialize = "export_complete")]
   ExportComplete,
+
+  #[strum(serialize = "export_outputs_complete")]
+  ExportOutputsComplete,
+
+  #[strum(serialize = "export_failed")]
+  ExportFailed,
+
+  #[strum(serialize = "export_size_report")]
+  ExportSizeReport,
+
+  #[strum(serialize = "transcription_progress")]
+  TranscriptionProgress,
+
+  #[strum(serialize = "transcription_complete")]
+  TranscriptionComplete,
+
+  #[strum(serialize = "transcription_failed")]
+  TranscriptionFailed,
+
+  #[strum(serialize = "marker_added")]
+  MarkerAdded,
+
+  #[strum(serialize = "thumbnails_generated")]
+  ThumbnailsGenerated,
+
+  #[strum(serialize = "proxies_generated")]
+  ProxiesGenerated,
+
+  #[strum(serialize = "retention_applied")]
+  RetentionApplied,
+
+  #[strum(serialize = "library_move_progress")]
+  LibraryMoveProgress,
+
+  #[strum(serialize = "storage_optimized")]
+  StorageOptimized,
 }
 
 #[derive(EnumString, AsRefStr, Display, Debug, Clone, Copy, PartialEq, Eq, Hash)]
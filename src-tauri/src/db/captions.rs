@@ -0,0 +1,57 @@
+use sqlx::{Acquire, Sqlite, SqlitePool};
+
+use crate::captions::models::CaptionSegment;
+
+pub async fn get_caption_segments(
+  pool: &SqlitePool,
+  recording_id: i64,
+) -> sqlx::Result<Vec<CaptionSegment>> {
+  sqlx::query_as!(
+    CaptionSegment,
+    r#"
+    SELECT start_ms, end_ms, text
+    FROM caption_segments
+    WHERE recording_id = ?
+    ORDER BY start_ms ASC
+    "#,
+    recording_id
+  )
+  .fetch_all(pool)
+  .await
+}
+
+/// Replace all caption segments of a recording
+pub async fn replace_caption_segments(
+  conn: impl Acquire<'_, Database = Sqlite>,
+  recording_id: i64,
+  segments: &[CaptionSegment],
+) -> sqlx::Result<()> {
+  let mut tx = conn.begin().await?;
+
+  sqlx::query!(
+    r#"
+    DELETE FROM caption_segments
+    WHERE recording_id = ?
+    "#,
+    recording_id
+  )
+  .execute(&mut *tx)
+  .await?;
+
+  for segment in segments {
+    sqlx::query!(
+      r#"
+      INSERT INTO caption_segments (recording_id, start_ms, end_ms, text)
+      VALUES (?, ?, ?, ?)
+      "#,
+      recording_id,
+      segment.start_ms,
+      segment.end_ms,
+      segment.text
+    )
+    .execute(&mut *tx)
+    .await?;
+  }
+
+  tx.commit().await
+}
@@ -0,0 +1,123 @@
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+
+use crate::library::models::Folder;
+
+pub async fn list_folders(pool: &SqlitePool) -> sqlx::Result<Vec<Folder>> {
+  sqlx::query_as!(
+    Folder,
+    r#"
+    SELECT id, name, parent_id
+    FROM folders
+    ORDER BY name ASC
+    "#
+  )
+  .fetch_all(pool)
+  .await
+}
+
+pub async fn insert_folder(
+  pool: &SqlitePool,
+  name: &str,
+  parent_id: Option<i64>,
+) -> sqlx::Result<Folder> {
+  let result = sqlx::query!(
+    r#"
+    INSERT INTO folders (name, parent_id)
+    VALUES (?, ?)
+    "#,
+    name,
+    parent_id
+  )
+  .execute(pool)
+  .await?;
+
+  Ok(Folder {
+    id: result.last_insert_rowid(),
+    name: name.to_string(),
+    parent_id,
+  })
+}
+
+pub async fn rename_folder(pool: &SqlitePool, folder_id: i64, name: &str) -> sqlx::Result<()> {
+  sqlx::query!(
+    r#"
+    UPDATE folders
+    SET name = ?
+    WHERE id = ?
+    "#,
+    name,
+    folder_id
+  )
+  .execute(pool)
+  .await?;
+
+  Ok(())
+}
+
+/// Delete a folder and its subfolders.
+///
+/// Recordings inside are soft deleted when `soft_delete_contents` is set,
+/// otherwise they are moved out of the folder.
+pub async fn delete_folder(
+  pool: &SqlitePool,
+  folder_id: i64,
+  soft_delete_contents: bool,
+) -> sqlx::Result<()> {
+  let mut tx = pool.begin().await?;
+
+  if soft_delete_contents {
+    sqlx::query!(
+      r#"
+      WITH RECURSIVE subfolders (id) AS (
+        SELECT ?
+        UNION
+        SELECT folders.id
+        FROM folders
+        JOIN subfolders ON folders.parent_id = subfolders.id
+      )
+      UPDATE recordings
+      SET deleted_at = CURRENT_TIMESTAMP
+      WHERE deleted_at IS NULL
+        AND folder_id IN (SELECT id FROM subfolders)
+      "#,
+      folder_id
+    )
+    .execute(&mut *tx)
+    .await?;
+  }
+
+  // Subfolders cascade, recordings are set to no folder
+  sqlx::query!(
+    r#"
+    DELETE FROM folders
+    WHERE id = ?
+    "#,
+    folder_id
+  )
+  .execute(&mut *tx)
+  .await?;
+
+  tx.commit().await
+}
+
+/// Move recordings into a folder, `None` moves them to the top level
+pub async fn move_recordings_to_folder(
+  pool: &SqlitePool,
+  recording_ids: &[i64],
+  folder_id: Option<i64>,
+) -> sqlx::Result<()> {
+  if recording_ids.is_empty() {
+    return Ok(());
+  }
+
+  let mut builder = QueryBuilder::<Sqlite>::new("UPDATE recordings SET folder_id = ");
+  builder.push_bind(folder_id).push(" WHERE id IN (");
+  let mut ids = builder.separated(", ");
+  for recording_id in recording_ids {
+    ids.push_bind(*recording_id);
+  }
+  builder.push(")");
+  builder.build().execute(pool).await?;
+
+  Ok(())
+}
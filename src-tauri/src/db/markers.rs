@@ -0,0 +1,99 @@
+use sqlx::{Acquire, Sqlite, SqlitePool};
+
+use crate::markers::models::{Marker, MarkerUpdate};
+
+pub async fn insert_marker(
+  conn: impl Acquire<'_, Database = Sqlite>,
+  recording_id: i64,
+  position_ms: i64,
+  name: Option<String>,
+) -> sqlx::Result<Marker> {
+  let mut conn = conn.acquire().await?;
+
+  let name = match name {
+    Some(name) => name,
+    None => {
+      let count = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*)
+        FROM markers
+        WHERE recording_id = ?
+        "#,
+        recording_id
+      )
+      .fetch_one(&mut *conn)
+      .await?;
+
+      format!("Marker {}", count + 1)
+    }
+  };
+
+  let result = sqlx::query!(
+    r#"
+    INSERT INTO markers (recording_id, position_ms, name)
+    VALUES (?, ?, ?)
+    "#,
+    recording_id,
+    position_ms,
+    name
+  )
+  .execute(&mut *conn)
+  .await?;
+
+  Ok(Marker {
+    id: result.last_insert_rowid(),
+    recording_id,
+    position_ms,
+    name,
+  })
+}
+
+pub async fn list_markers(pool: &SqlitePool, recording_id: i64) -> sqlx::Result<Vec<Marker>> {
+  sqlx::query_as!(
+    Marker,
+    r#"
+    SELECT id, recording_id, position_ms, name
+    FROM markers
+    WHERE recording_id = ?
+    ORDER BY position_ms ASC
+    "#,
+    recording_id
+  )
+  .fetch_all(pool)
+  .await
+}
+
+pub async fn update_marker(
+  pool: &SqlitePool,
+  marker_id: i64,
+  update: &MarkerUpdate,
+) -> sqlx::Result<()> {
+  sqlx::query!(
+    r#"
+    UPDATE markers
+    SET name = ?, position_ms = ?
+    WHERE id = ?
+    "#,
+    update.name,
+    update.position_ms,
+    marker_id
+  )
+  .execute(pool)
+  .await?;
+
+  Ok(())
+}
+
+pub async fn delete_marker(pool: &SqlitePool, marker_id: i64) -> sqlx::Result<()> {
+  sqlx::query!(
+    r#"
+    DELETE FROM markers
+    WHERE id = ?
+    "#,
+    marker_id
+  )
+  .execute(pool)
+  .await?;
+
+  Ok(())
+}
@@ -1 +1,6 @@
+pub mod captions;
+pub mod folders;
+pub mod markers;
+pub mod projects;
 pub mod recordings;
+pub mod tags;
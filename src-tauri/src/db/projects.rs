@@ -0,0 +1,50 @@
+use sqlx::{Acquire, Sqlite, SqlitePool};
+
+/// Serialized project and history, see `project::service`
+pub struct ProjectRow {
+  pub project: String,
+  pub undo_stack: String,
+  pub redo_stack: String,
+}
+
+pub async fn get_project(pool: &SqlitePool, recording_id: i64) -> sqlx::Result<Option<ProjectRow>> {
+  sqlx::query_as!(
+    ProjectRow,
+    r#"
+    SELECT project, undo_stack, redo_stack
+    FROM recording_projects
+    WHERE recording_id = ?
+    "#,
+    recording_id
+  )
+  .fetch_optional(pool)
+  .await
+}
+
+pub async fn upsert_project(
+  conn: impl Acquire<'_, Database = Sqlite>,
+  recording_id: i64,
+  row: &ProjectRow,
+) -> sqlx::Result<()> {
+  let mut conn = conn.acquire().await?;
+
+  sqlx::query!(
+    r#"
+    INSERT INTO recording_projects (recording_id, project, undo_stack, redo_stack)
+    VALUES (?, ?, ?, ?)
+    ON CONFLICT (recording_id) DO UPDATE
+    SET project = excluded.project,
+      undo_stack = excluded.undo_stack,
+      redo_stack = excluded.redo_stack,
+      updated_at = CURRENT_TIMESTAMP
+    "#,
+    recording_id,
+    row.project,
+    row.undo_stack,
+    row.redo_stack
+  )
+  .execute(&mut *conn)
+  .await?;
+
+  Ok(())
+}
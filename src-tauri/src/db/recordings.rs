@@ -1,11 +1,15 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use chrono::{Duration, Utc};
 use fancy_regex::Regex;
-use serde::Serialize;
-use sqlx::{types::time::OffsetDateTime, Row, SqlitePool};
+use serde::{Deserialize, Serialize};
+use sqlx::{types::time::OffsetDateTime, Acquire, QueryBuilder, Row, Sqlite, SqlitePool};
 
-use crate::recording::models::{RecordingFile, RecordingType};
+use crate::recording::{
+  file::is_offline,
+  models::{RecordingFile, RecordingType},
+};
 
 pub struct NewRecording<'a> {
   pub recording_directory: &'a str,
@@ -20,7 +24,12 @@ pub struct NewRecording<'a> {
   pub r#type: &'a RecordingType,
 }
 
-pub async fn insert_recording(pool: &SqlitePool, new: &NewRecording<'_>) -> sqlx::Result<i64> {
+pub async fn insert_recording(
+  conn: impl Acquire<'_, Database = Sqlite>,
+  new: &NewRecording<'_>,
+) -> sqlx::Result<i64> {
+  let mut conn = conn.acquire().await?;
+
   let has_camera = new.has_camera as i32;
   let has_system_audio = new.has_system_audio as i32;
   let has_microphone = new.has_microphone as i32;
@@ -53,18 +62,20 @@ pub async fn insert_recording(pool: &SqlitePool, new: &NewRecording<'_>) -> sqlx
     new.name,
     r#type
   )
-  .execute(pool)
+  .execute(&mut *conn)
   .await?;
 
   Ok(result.last_insert_rowid())
 }
 
 pub async fn set_recording_metadata(
-  pool: &SqlitePool,
+  conn: impl Acquire<'_, Database = Sqlite>,
   recording_id: i64,
   bytes: u64,
   milliseconds: Option<u64>,
 ) -> sqlx::Result<()> {
+  let mut conn = conn.acquire().await?;
+
   let size = bytes as i64;
   let length = milliseconds.map(|ms| ms as i64);
 
@@ -78,7 +89,7 @@ pub async fn set_recording_metadata(
     length,
     recording_id
   )
-  .execute(pool)
+  .execute(&mut *conn)
   .await?;
 
   Ok(())
@@ -118,15 +129,472 @@ pub struct RecordingMetadata {
   pub has_microphone: bool,
   pub has_camera: bool,
   pub has_system_cursor: bool,
+  pub thumbnail: Option<PathBuf>,
+  pub preview_sprite: Option<PathBuf>,
+  pub folder_id: Option<i64>,
+  pub tag_ids: Vec<i64>,
+  pub pinned: bool,
+  /// Stored on a volume that is not currently mounted
+  pub offline: bool,
 }
 
-pub async fn list_recordings(pool: &SqlitePool) -> sqlx::Result<Vec<RecordingMetadata>> {
-  let records = sqlx::query!(
+/// Return path of the recording file if it exists on disk
+fn existing_file(recording_directory: &str, file: RecordingFile) -> Option<PathBuf> {
+  let path = file.complete_path(recording_directory);
+  path.exists().then_some(path)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RecordingSortField {
+  #[default]
+  CreatedAt,
+  LastOpenedAt,
+  Size,
+  Length,
+}
+
+impl RecordingSortField {
+  /// Expression sorted on, never `NULL` so cursor comparisons hold
+  fn expression(&self) -> &'static str {
+    match self {
+      RecordingSortField::CreatedAt => "created_at",
+      RecordingSortField::LastOpenedAt => "COALESCE(last_opened_at, '')",
+      RecordingSortField::Size => "size",
+      RecordingSortField::Length => "length",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+  Asc,
+  #[default]
+  Desc,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordingStatus {
+  #[default]
+  All,
+  Active,
+  Deleted,
+}
+
+/// Filters, sort and page of `list_recordings`, every field is optional
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RecordingQuery {
+  /// Case-insensitive substring of the name
+  pub name: Option<String>,
+  /// Full text search of the name, every word matched as a prefix
+  pub text: Option<String>,
+  pub types: Vec<RecordingType>,
+  #[serde(with = "time::serde::iso8601::option")]
+  pub created_after: Option<OffsetDateTime>,
+  #[serde(with = "time::serde::iso8601::option")]
+  pub created_before: Option<OffsetDateTime>,
+  pub has_camera: Option<bool>,
+  pub has_microphone: Option<bool>,
+  /// Recordings having every one of these tags
+  pub tag_ids: Vec<i64>,
+  /// Recordings in this folder or any of its subfolders
+  pub folder_id: Option<i64>,
+  pub min_size_bytes: Option<i64>,
+  pub max_size_bytes: Option<i64>,
+  pub min_length_ms: Option<i64>,
+  pub max_length_ms: Option<i64>,
+  pub status: RecordingStatus,
+  pub sort_by: RecordingSortField,
+  pub sort_direction: SortDirection,
+  /// `next_cursor` of the previous page
+  pub cursor: Option<String>,
+  /// Page size, all matching recordings when not set
+  pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingPage {
+  pub recordings: Vec<RecordingMetadata>,
+  /// Pass back as `cursor` for the next page, `None` on the last page
+  pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum SortKey {
+  Integer(i64),
+  Text(String),
+}
+
+/// Position of the last recording in a page, encoded as an opaque string
+#[derive(Debug, Serialize, Deserialize)]
+struct RecordingCursor {
+  key: SortKey,
+  id: i64,
+}
+
+impl RecordingCursor {
+  fn encode(&self) -> String {
+    URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+  }
+
+  fn decode(cursor: &str) -> sqlx::Result<Self> {
+    URL_SAFE_NO_PAD
+      .decode(cursor)
+      .ok()
+      .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+      .ok_or_else(|| sqlx::Error::Decode("Invalid recordings cursor".into()))
+  }
+}
+
+fn push_sort_key(builder: &mut QueryBuilder<'_, Sqlite>, key: &SortKey) {
+  match key {
+    SortKey::Integer(value) => builder.push_bind(*value),
+    SortKey::Text(value) => builder.push_bind(value.clone()),
+  };
+}
+
+/// Quote each word as an FTS5 prefix query, so user input is never parsed as syntax
+fn fts_prefix_query(text: &str) -> Option<String> {
+  let terms: Vec<String> = text
+    .split_whitespace()
+    .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+    .collect();
+
+  (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+pub async fn list_recordings(
+  pool: &SqlitePool,
+  query: &RecordingQuery,
+) -> sqlx::Result<RecordingPage> {
+  let sort_expression = query.sort_by.expression();
+
+  let mut builder = QueryBuilder::<Sqlite>::new(format!(
     r#"
-    SELECT id, name, created_at, size, length, type, has_camera, has_microphone, has_system_audio, has_system_cursor, deleted_at
+    SELECT id, name, recording_directory, created_at, size, length, type, has_camera, has_microphone, has_system_audio, has_system_cursor, deleted_at, folder_id, pinned,
+      (SELECT GROUP_CONCAT(tag_id) FROM recording_tags WHERE recording_id = recordings.id) AS tag_ids,
+      {sort_expression} AS sort_key
     FROM recordings
     WHERE size IS NOT NULL AND length IS NOT NULL
-    ORDER BY created_at DESC
+    "#
+  ));
+
+  if let Some(name) = query.name.as_deref().filter(|n| !n.is_empty()) {
+    let escaped = name
+      .replace('\\', "\\\\")
+      .replace('%', "\\%")
+      .replace('_', "\\_");
+    builder
+      .push(" AND name LIKE ")
+      .push_bind(format!("%{escaped}%"))
+      .push(" ESCAPE '\\'");
+  }
+
+  if let Some(text) = query.text.as_deref().and_then(fts_prefix_query) {
+    builder
+      .push(" AND id IN (SELECT rowid FROM recordings_fts WHERE recordings_fts MATCH ")
+      .push_bind(text)
+      .push(")");
+  }
+
+  if !query.types.is_empty() {
+    builder.push(" AND type IN (");
+    let mut types = builder.separated(", ");
+    for r#type in &query.types {
+      types.push_bind(r#type.to_string());
+    }
+    builder.push(")");
+  }
+
+  if let Some(created_after) = query.created_after {
+    builder
+      .push(" AND created_at >= datetime(")
+      .push_bind(created_after)
+      .push(")");
+  }
+
+  if let Some(created_before) = query.created_before {
+    builder
+      .push(" AND created_at < datetime(")
+      .push_bind(created_before)
+      .push(")");
+  }
+
+  if let Some(has_camera) = query.has_camera {
+    builder
+      .push(" AND has_camera = ")
+      .push_bind(has_camera as i32);
+  }
+
+  if let Some(has_microphone) = query.has_microphone {
+    builder
+      .push(" AND has_microphone = ")
+      .push_bind(has_microphone as i32);
+  }
+
+  if !query.tag_ids.is_empty() {
+    builder.push(" AND id IN (SELECT recording_id FROM recording_tags WHERE tag_id IN (");
+    let mut tag_ids = builder.separated(", ");
+    for tag_id in &query.tag_ids {
+      tag_ids.push_bind(*tag_id);
+    }
+    builder
+      .push(") GROUP BY recording_id HAVING COUNT(*) = ")
+      .push_bind(query.tag_ids.len() as i64)
+      .push(")");
+  }
+
+  if let Some(folder_id) = query.folder_id {
+    builder.push(
+      r#"
+      AND folder_id IN (
+        WITH RECURSIVE subfolders (id) AS (
+          SELECT "#,
+    );
+    builder.push_bind(folder_id).push(
+      r#"
+          UNION
+          SELECT folders.id
+          FROM folders
+          JOIN subfolders ON folders.parent_id = subfolders.id
+        )
+        SELECT id FROM subfolders
+      )
+      "#,
+    );
+  }
+
+  let ranges = [
+    ("size >= ", query.min_size_bytes),
+    ("size <= ", query.max_size_bytes),
+    ("length >= ", query.min_length_ms),
+    ("length <= ", query.max_length_ms),
+  ];
+  for (condition, value) in ranges {
+    if let Some(value) = value {
+      builder.push(" AND ").push(condition).push_bind(value);
+    }
+  }
+
+  match query.status {
+    RecordingStatus::All => {}
+    RecordingStatus::Active => {
+      builder.push(" AND deleted_at IS NULL");
+    }
+    RecordingStatus::Deleted => {
+      builder.push(" AND deleted_at IS NOT NULL");
+    }
+  }
+
+  let (comparison, direction) = match query.sort_direction {
+    SortDirection::Asc => (">", "ASC"),
+    SortDirection::Desc => ("<", "DESC"),
+  };
+
+  // Keyset pagination, `id` breaks ties between equal sort keys
+  if let Some(cursor) = query.cursor.as_deref() {
+    let cursor = RecordingCursor::decode(cursor)?;
+
+    builder.push(format!(" AND ({sort_expression} {comparison} "));
+    push_sort_key(&mut builder, &cursor.key);
+    builder.push(format!(" OR ({sort_expression} = "));
+    push_sort_key(&mut builder, &cursor.key);
+    builder
+      .push(format!(" AND id {comparison} "))
+      .push_bind(cursor.id)
+      .push("))");
+  }
+
+  builder.push(format!(
+    " ORDER BY {sort_expression} {direction}, id {direction}"
+  ));
+
+  // One extra row tells whether there is a next page
+  if let Some(limit) = query.limit {
+    builder.push(" LIMIT ").push_bind(limit as i64 + 1);
+  }
+
+  let mut rows = builder.build().fetch_all(pool).await?;
+
+  let next_cursor = match query.limit {
+    Some(limit) if rows.len() > limit as usize => {
+      rows.truncate(limit as usize);
+      let last = rows.last().expect("Page has at least one row");
+      let key = match query.sort_by {
+        RecordingSortField::Size | RecordingSortField::Length => {
+          SortKey::Integer(last.try_get("sort_key")?)
+        }
+        RecordingSortField::CreatedAt | RecordingSortField::LastOpenedAt => {
+          SortKey::Text(last.try_get_unchecked("sort_key")?)
+        }
+      };
+
+      Some(
+        RecordingCursor {
+          key,
+          id: last.try_get("id")?,
+        }
+        .encode(),
+      )
+    }
+    _ => None,
+  };
+
+  let recordings = rows
+    .iter()
+    .map(|row| {
+      let recording_directory: String = row.try_get("recording_directory")?;
+      let r#type: Option<String> = row.try_get("type")?;
+      let tag_ids: Option<String> = row.try_get_unchecked("tag_ids")?;
+
+      Ok(RecordingMetadata {
+        id: row.try_get("id")?,
+        name: row.try_get("name")?,
+        created_at: row.try_get("created_at")?,
+        deleted_at: row.try_get("deleted_at")?,
+        size_bytes: row.try_get("size")?,
+        length_ms: row.try_get("length")?,
+        r#type: r#type.and_then(|s| s.parse().ok()),
+        has_system_audio: row.try_get::<i64, _>("has_system_audio")? != 0,
+        has_microphone: row.try_get::<i64, _>("has_microphone")? != 0,
+        has_camera: row.try_get::<i64, _>("has_camera")? != 0,
+        has_system_cursor: row.try_get::<i64, _>("has_system_cursor")? != 0,
+        thumbnail: existing_file(&recording_directory, RecordingFile::Thumbnail),
+        preview_sprite: existing_file(&recording_directory, RecordingFile::PreviewSprite),
+        folder_id: row.try_get("folder_id")?,
+        pinned: row.try_get::<i64, _>("pinned")? != 0,
+        offline: is_offline(Path::new(&recording_directory)),
+        tag_ids: tag_ids
+          .map(|ids| ids.split(',').filter_map(|id| id.parse().ok()).collect())
+          .unwrap_or_default(),
+      })
+    })
+    .collect::<sqlx::Result<Vec<_>>>()?;
+
+  Ok(RecordingPage {
+    recordings,
+    next_cursor,
+  })
+}
+
+pub struct ThumbnailSource {
+  pub recording_directory: PathBuf,
+  pub length_ms: Option<u64>,
+  pub poster_time_ms: Option<u64>,
+}
+
+pub async fn get_thumbnail_source(
+  pool: &SqlitePool,
+  recording_id: i64,
+) -> sqlx::Result<ThumbnailSource> {
+  let record = sqlx::query!(
+    r#"
+    SELECT recording_directory, length, poster_time_ms
+    FROM recordings
+    WHERE id = ?
+    "#,
+    recording_id
+  )
+  .fetch_one(pool)
+  .await?;
+
+  Ok(ThumbnailSource {
+    recording_directory: PathBuf::from(record.recording_directory),
+    length_ms: record.length.map(|ms| ms as u64),
+    poster_time_ms: record.poster_time_ms.map(|ms| ms as u64),
+  })
+}
+
+pub async fn set_poster_time(
+  conn: impl Acquire<'_, Database = Sqlite>,
+  recording_id: i64,
+  poster_time_ms: u64,
+) -> sqlx::Result<()> {
+  let mut conn = conn.acquire().await?;
+
+  let poster_time_ms = poster_time_ms as i64;
+
+  sqlx::query!(
+    r#"
+    UPDATE recordings
+    SET poster_time_ms = ?
+    WHERE id = ?
+    "#,
+    poster_time_ms,
+    recording_id
+  )
+  .execute(&mut *conn)
+  .await?;
+
+  Ok(())
+}
+
+/// Every stored column of a recording, used to copy it elsewhere
+pub struct RecordingRow {
+  pub name: String,
+  pub created_at: OffsetDateTime,
+  pub recording_directory: PathBuf,
+  pub r#type: Option<RecordingType>,
+  pub origin_x: f64,
+  pub origin_y: f64,
+  pub scale_factor: f64,
+  pub has_camera: bool,
+  pub has_system_audio: bool,
+  pub has_microphone: bool,
+  pub has_system_cursor: bool,
+  pub length_ms: Option<u64>,
+  pub poster_time_ms: Option<u64>,
+}
+
+pub async fn get_recording_row(pool: &SqlitePool, recording_id: i64) -> sqlx::Result<RecordingRow> {
+  let record = sqlx::query!(
+    r#"
+    SELECT name, created_at as "created_at: OffsetDateTime", recording_directory, type, origin_x, origin_y, scale_factor, has_camera, has_system_audio, has_microphone, has_system_cursor, length, poster_time_ms
+    FROM recordings
+    WHERE id = ?
+    "#,
+    recording_id
+  )
+  .fetch_one(pool)
+  .await?;
+
+  Ok(RecordingRow {
+    name: record.name,
+    created_at: record.created_at,
+    recording_directory: PathBuf::from(record.recording_directory),
+    r#type: record.r#type.and_then(|s| s.parse().ok()),
+    origin_x: record.origin_x,
+    origin_y: record.origin_y,
+    scale_factor: record.scale_factor,
+    has_camera: record.has_camera != 0,
+    has_system_audio: record.has_system_audio != 0,
+    has_microphone: record.has_microphone != 0,
+    has_system_cursor: record.has_system_cursor != 0,
+    length_ms: record.length.map(|ms| ms as u64),
+    poster_time_ms: record.poster_time_ms.map(|ms| ms as u64),
+  })
+}
+
+pub struct RecordingFiles {
+  pub id: i64,
+  pub recording_directory: PathBuf,
+  pub has_camera: bool,
+  pub has_system_audio: bool,
+  pub has_microphone: bool,
+  pub size_bytes: Option<i64>,
+}
+
+/// Directory, file flags and size of every recording, for integrity checks
+pub async fn list_recording_files(pool: &SqlitePool) -> sqlx::Result<Vec<RecordingFiles>> {
+  let records = sqlx::query!(
+    r#"
+    SELECT id, recording_directory, has_camera, has_system_audio, has_microphone, size
+    FROM recordings
     "#
   )
   .fetch_all(pool)
@@ -135,23 +603,66 @@ pub async fn list_recordings(pool: &SqlitePool) -> sqlx::Result<Vec<RecordingMet
   Ok(
     records
       .into_iter()
-      .map(|record| RecordingMetadata {
+      .map(|record| RecordingFiles {
         id: record.id,
-        name: record.name,
-        created_at: record.created_at,
-        deleted_at: record.deleted_at,
-        size_bytes: record.size,
-        length_ms: record.length,
-        r#type: record.r#type.and_then(|s| s.parse().ok()),
+        recording_directory: PathBuf::from(record.recording_directory),
+        has_camera: record.has_camera != 0,
         has_system_audio: record.has_system_audio != 0,
         has_microphone: record.has_microphone != 0,
-        has_camera: record.has_camera != 0,
-        has_system_cursor: record.has_system_cursor != 0,
+        size_bytes: record.size,
       })
       .collect(),
   )
 }
 
+pub async fn set_recording_directory(
+  pool: &SqlitePool,
+  recording_id: i64,
+  recording_directory: &str,
+) -> sqlx::Result<()> {
+  sqlx::query!(
+    r#"
+    UPDATE recordings
+    SET recording_directory = ?
+    WHERE id = ?
+    "#,
+    recording_directory,
+    recording_id
+  )
+  .execute(pool)
+  .await?;
+
+  Ok(())
+}
+
+pub async fn set_recording_flags(
+  pool: &SqlitePool,
+  recording_id: i64,
+  has_camera: bool,
+  has_system_audio: bool,
+  has_microphone: bool,
+) -> sqlx::Result<()> {
+  let has_camera = has_camera as i32;
+  let has_system_audio = has_system_audio as i32;
+  let has_microphone = has_microphone as i32;
+
+  sqlx::query!(
+    r#"
+    UPDATE recordings
+    SET has_camera = ?, has_system_audio = ?, has_microphone = ?
+    WHERE id = ?
+    "#,
+    has_camera,
+    has_system_audio,
+    has_microphone,
+    recording_id
+  )
+  .execute(pool)
+  .await?;
+
+  Ok(())
+}
+
 #[derive(Debug, Serialize)]
 pub struct RecordingDetails {
   pub id: i64,
@@ -160,6 +671,11 @@ pub struct RecordingDetails {
   pub camera: Option<PathBuf>,
   pub system_audio: Option<PathBuf>,
   pub microphone: Option<PathBuf>,
+  /// Low resolution preview versions, only once generated
+  pub screen_proxy: Option<PathBuf>,
+  pub camera_proxy: Option<PathBuf>,
+  /// Stored on a volume that is not currently mounted, paths can't be opened
+  pub offline: bool,
 }
 
 pub async fn get_recording_details(
@@ -187,15 +703,18 @@ pub async fn get_recording_details(
       None
     },
     system_audio: if record.has_system_audio != 0 {
-      Some(RecordingFile::SystemAudio.complete_path(&record.recording_directory))
+      Some(RecordingFile::SystemAudio.resolve(Path::new(&record.recording_directory)))
     } else {
       None
     },
     microphone: if record.has_microphone != 0 {
-      Some(RecordingFile::Microphone.complete_path(&record.recording_directory))
+      Some(RecordingFile::Microphone.resolve(Path::new(&record.recording_directory)))
     } else {
       None
     },
+    screen_proxy: existing_file(&record.recording_directory, RecordingFile::ScreenProxy),
+    camera_proxy: existing_file(&record.recording_directory, RecordingFile::CameraProxy),
+    offline: is_offline(Path::new(&record.recording_directory)),
   })
 }
 
@@ -308,9 +827,12 @@ pub async fn hard_delete_recordings(
   Ok(dirs)
 }
 
-pub async fn get_recordings_to_hard_delete(pool: &SqlitePool) -> sqlx::Result<Vec<i64>> {
-  let cutoff = Utc::now() - Duration::days(30); // Retention period of 30 days
-  let cutoff_str = cutoff.to_rfc3339();
+pub async fn get_recordings_to_hard_delete(
+  pool: &SqlitePool,
+  retention_days: u32,
+) -> sqlx::Result<Vec<i64>> {
+  let cutoff = Utc::now() - Duration::days(retention_days as i64);
+  let cutoff_str = cutoff.format("%Y-%m-%d %H:%M:%S").to_string();
 
   let ids: Vec<i64> = sqlx::query_scalar!(
     r#"
@@ -327,6 +849,120 @@ pub async fn get_recordings_to_hard_delete(pool: &SqlitePool) -> sqlx::Result<Ve
   Ok(ids)
 }
 
+/// Unpinned recordings, not in the trash, created more than `days` ago
+pub async fn get_recordings_to_auto_trash(pool: &SqlitePool, days: u32) -> sqlx::Result<Vec<i64>> {
+  let cutoff = Utc::now() - Duration::days(days as i64);
+  let cutoff_str = cutoff.format("%Y-%m-%d %H:%M:%S").to_string();
+
+  sqlx::query_scalar!(
+    r#"
+    SELECT id
+    FROM recordings
+    WHERE deleted_at IS NULL
+      AND pinned = 0
+      AND created_at < ?
+    "#,
+    cutoff_str
+  )
+  .fetch_all(pool)
+  .await
+}
+
+/// Finished recordings, not yet optimized, unopened for more than `days`
+pub async fn get_recordings_to_optimize(pool: &SqlitePool, days: u32) -> sqlx::Result<Vec<i64>> {
+  let cutoff = Utc::now() - Duration::days(days as i64);
+  let cutoff_str = cutoff.format("%Y-%m-%d %H:%M:%S").to_string();
+
+  sqlx::query_scalar!(
+    r#"
+    SELECT id
+    FROM recordings
+    WHERE deleted_at IS NULL
+      AND optimized_at IS NULL
+      AND size IS NOT NULL
+      AND COALESCE(last_opened_at, created_at) < ?
+    ORDER BY created_at ASC
+    "#,
+    cutoff_str
+  )
+  .fetch_all(pool)
+  .await
+}
+
+pub async fn set_recording_optimized(
+  pool: &SqlitePool,
+  recording_id: i64,
+  bytes: u64,
+) -> sqlx::Result<()> {
+  let size = bytes as i64;
+
+  sqlx::query!(
+    r#"
+    UPDATE recordings
+    SET size = ?, optimized_at = CURRENT_TIMESTAMP
+    WHERE id = ?
+    "#,
+    size,
+    recording_id
+  )
+  .execute(pool)
+  .await?;
+
+  Ok(())
+}
+
+pub struct StorageEntry {
+  pub id: i64,
+  pub size_bytes: u64,
+  pub pinned: bool,
+}
+
+/// Size of every recording not in the trash, oldest first
+pub async fn list_storage_usage(pool: &SqlitePool) -> sqlx::Result<Vec<StorageEntry>> {
+  let records = sqlx::query!(
+    r#"
+    SELECT id, size, pinned
+    FROM recordings
+    WHERE deleted_at IS NULL
+    ORDER BY created_at ASC, id ASC
+    "#
+  )
+  .fetch_all(pool)
+  .await?;
+
+  Ok(
+    records
+      .into_iter()
+      .map(|record| StorageEntry {
+        id: record.id,
+        size_bytes: record.size.unwrap_or(0).max(0) as u64,
+        pinned: record.pinned != 0,
+      })
+      .collect(),
+  )
+}
+
+pub async fn set_recordings_pinned(
+  pool: &SqlitePool,
+  recording_ids: &[i64],
+  pinned: bool,
+) -> sqlx::Result<()> {
+  if recording_ids.is_empty() {
+    return Ok(());
+  }
+
+  let mut builder = QueryBuilder::<Sqlite>::new("UPDATE recordings SET pinned = ");
+  builder.push_bind(pinned as i32).push(" WHERE id IN (");
+  let mut ids = builder.separated(", ");
+  for recording_id in recording_ids {
+    ids.push_bind(*recording_id);
+  }
+  builder.push(")");
+  builder.build().execute(pool).await?;
+
+  Ok(())
+}
+
 pub async fn restore_recordings(pool: &SqlitePool, recording_ids: Vec<i64>) -> sqlx::Result<()> {
   if recording_ids.is_empty() {
     return Err(sqlx::Error::RowNotFound);
@@ -0,0 +1,114 @@
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+
+use crate::library::models::Tag;
+
+pub async fn list_tags(pool: &SqlitePool) -> sqlx::Result<Vec<Tag>> {
+  sqlx::query_as!(
+    Tag,
+    r#"
+    SELECT id, name
+    FROM tags
+    ORDER BY name ASC
+    "#
+  )
+  .fetch_all(pool)
+  .await
+}
+
+pub async fn insert_tag(pool: &SqlitePool, name: &str) -> sqlx::Result<Tag> {
+  let result = sqlx::query!(
+    r#"
+    INSERT INTO tags (name)
+    VALUES (?)
+    "#,
+    name
+  )
+  .execute(pool)
+  .await?;
+
+  Ok(Tag {
+    id: result.last_insert_rowid(),
+    name: name.to_string(),
+  })
+}
+
+pub async fn rename_tag(pool: &SqlitePool, tag_id: i64, name: &str) -> sqlx::Result<()> {
+  sqlx::query!(
+    r#"
+    UPDATE tags
+    SET name = ?
+    WHERE id = ?
+    "#,
+    name,
+    tag_id
+  )
+  .execute(pool)
+  .await?;
+
+  Ok(())
+}
+
+/// Delete a tag, assignments are removed by cascade
+pub async fn delete_tag(pool: &SqlitePool, tag_id: i64) -> sqlx::Result<()> {
+  sqlx::query!(
+    r#"
+    DELETE FROM tags
+    WHERE id = ?
+    "#,
+    tag_id
+  )
+  .execute(pool)
+  .await?;
+
+  Ok(())
+}
+
+/// Assign every tag to every recording, existing assignments are kept
+pub async fn assign_tags(
+  pool: &SqlitePool,
+  recording_ids: &[i64],
+  tag_ids: &[i64],
+) -> sqlx::Result<()> {
+  if recording_ids.is_empty() || tag_ids.is_empty() {
+    return Ok(());
+  }
+
+  let pairs = recording_ids
+    .iter()
+    .flat_map(|recording_id| tag_ids.iter().map(move |tag_id| (*recording_id, *tag_id)));
+
+  let mut builder =
+    QueryBuilder::<Sqlite>::new("INSERT OR IGNORE INTO recording_tags (recording_id, tag_id) ");
+  builder.push_values(pairs, |mut row, (recording_id, tag_id)| {
+    row.push_bind(recording_id).push_bind(tag_id);
+  });
+  builder.build().execute(pool).await?;
+
+  Ok(())
+}
+
+pub async fn unassign_tags(
+  pool: &SqlitePool,
+  recording_ids: &[i64],
+  tag_ids: &[i64],
+) -> sqlx::Result<()> {
+  if recording_ids.is_empty() || tag_ids.is_empty() {
+    return Ok(());
+  }
+
+  let mut builder =
+    QueryBuilder::<Sqlite>::new("DELETE FROM recording_tags WHERE recording_id IN (");
+  let mut ids = builder.separated(", ");
+  for recording_id in recording_ids {
+    ids.push_bind(*recording_id);
+  }
+  builder.push(") AND tag_id IN (");
+  let mut ids = builder.separated(", ");
+  for tag_id in tag_ids {
+    ids.push_bind(*tag_id);
+  }
+  builder.push(")");
+  builder.build().execute(pool).await?;
+
+  Ok(())
+}
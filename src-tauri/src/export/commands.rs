@@ -1,12 +1,29 @@
-use std::path::PathBuf;
+use std::{collections::BTreeMap, path::PathBuf};
 
 use parking_lot::Mutex;
 use serde::Deserialize;
-use tauri::{AppHandle, Manager, State};
+use serde_json::Value;
+use sqlx::{Pool, Sqlite};
+use tauri::{
+  ipc::{Channel, InvokeResponseBody},
+  AppHandle, Emitter, Manager, State,
+};
 
 use crate::{
-  export::service::{self, encode_recording},
+  captions::models::CaptionExportMode,
+  compositor::models::RecordingGeometry,
+  constants::Events,
+  db::{self, recordings::RecordingRow},
+  export::{
+    models::{
+      CaptionExport, ExportFailed, ExportFailureReason, ExportMetadata, ExportPreset,
+      PreviewFrameFormat,
+    },
+    presets,
+    service::{self, encode_recording},
+  },
   models::EditingState,
+  project::{self, models::Project},
 };
 
 #[tauri::command]
@@ -25,26 +42,228 @@ pub struct ExportOptions {
   pub source_folder_path: PathBuf,
   pub destination_file_path: PathBuf,
   pub open_folder_after_export: bool,
-  pub separate_audio_tracks: bool,
-  pub separate_camera_file: bool,
+  #[serde(default)]
+  pub recording_id: Option<i64>,
+  /// Changes merged over the saved project of `recording_id` as a JSON merge
+  /// patch, e.g. settings picked in the export dialog
+  #[serde(default)]
+  pub project: Option<Value>,
+  /// Output format and settings, the default MP4 preset if omitted
+  #[serde(default)]
+  pub preset: Option<ExportPreset>,
+  /// Encode in two passes to stay within this size, lowering resolution or
+  /// frame rate if needed
+  #[serde(default)]
+  pub max_size_bytes: Option<u64>,
+  /// Extra container metadata, e.g. `{ "artist": "Acme" }`
+  #[serde(default)]
+  pub metadata_tags: BTreeMap<String, String>,
+  /// More files written from the same decode and composite pass, e.g. a GIF
+  /// next to the MP4. `max_size_bytes` only applies to the main output
+  #[serde(default)]
+  pub additional_outputs: Vec<ExportDestination>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportDestination {
+  pub destination_file_path: PathBuf,
+  pub preset: ExportPreset,
 }
+
 #[tauri::command]
 pub async fn export_recording(app_handle: AppHandle, options: ExportOptions) {
+  let saved_project = match options.recording_id {
+    Some(recording_id) => {
+      let pool: State<'_, Pool<Sqlite>> = app_handle.state();
+      project::service::load_project(&pool, recording_id)
+        .await
+        .unwrap_or_else(|e| {
+          log::warn!("Failed to load project for export: {e}");
+          Project::default()
+        })
+    }
+    None => Project::default(),
+  };
+
+  let project = match &options.project {
+    Some(patch) => match project::service::patch_project(&saved_project, patch) {
+      Ok(project) => project,
+      Err(e) => {
+        let _ = app_handle.emit(
+          Events::ExportFailed.as_ref(),
+          ExportFailed {
+            reason: ExportFailureReason::Unknown,
+            exit_code: None,
+            stderr_tail: vec![format!("Invalid project changes: {e}")],
+          },
+        );
+        return;
+      }
+    },
+    None => saved_project,
+  };
+
+  let captions = match (options.recording_id, project.captions.clone()) {
+    (Some(recording_id), Some(mode)) => {
+      load_caption_export(&app_handle.state::<Pool<Sqlite>>(), recording_id, mode).await
+    }
+    _ => None,
+  };
+
+  let markers = match options.recording_id {
+    Some(recording_id) if project.chapters || project.chapters_text_file => {
+      let pool: State<'_, Pool<Sqlite>> = app_handle.state();
+      db::markers::list_markers(&pool, recording_id)
+        .await
+        .unwrap_or_else(|e| {
+          log::warn!("Failed to load markers for export: {e}");
+          vec![]
+        })
+    }
+    _ => vec![],
+  };
+
+  let recording = match options.recording_id {
+    Some(recording_id) => {
+      load_recording_row(&app_handle.state::<Pool<Sqlite>>(), recording_id).await
+    }
+    None => None,
+  };
+  let geometry = recording.as_ref().map(RecordingGeometry::from);
+
+  let package_info = app_handle.package_info();
+  let metadata = ExportMetadata {
+    title: recording.as_ref().map(|row| row.name.clone()),
+    creation_time: recording.as_ref().map(|row| row.created_at),
+    encoder: format!("{} {}", package_info.name, package_info.version),
+    tags: options.metadata_tags.clone(),
+  };
+
   encode_recording(
-    app_handle,
-    options.source_folder_path,
-    options.destination_file_path.clone(),
-    options.separate_audio_tracks,
-    options.separate_camera_file,
-    options.open_folder_after_export,
+    app_handle, options, project, geometry, captions, markers, metadata,
   );
 }
 
+async fn load_recording_row(pool: &Pool<Sqlite>, recording_id: i64) -> Option<RecordingRow> {
+  match db::recordings::get_recording_row(pool, recording_id).await {
+    Ok(row) => Some(row),
+    Err(e) => {
+      log::warn!("Failed to load recording for export: {e}");
+      None
+    }
+  }
+}
+
+/// Captions of a recording to export in `mode`, `None` if there are none
+async fn load_caption_export(
+  pool: &Pool<Sqlite>,
+  recording_id: i64,
+  mode: CaptionExportMode,
+) -> Option<CaptionExport> {
+  match db::captions::get_caption_segments(pool, recording_id).await {
+    Ok(segments) if !segments.is_empty() => Some(CaptionExport { segments, mode }),
+    Ok(_) => None,
+    Err(e) => {
+      log::warn!("Failed to load captions for export: {e}");
+      None
+    }
+  }
+}
+
+/// Render the frame at `position_ms` as it will look when exported.
+///
+/// Uses `project` if given, otherwise the saved project, and is scaled and
+/// watermarked for `preset`, the default MP4 preset if omitted. The frame is sent
+/// over `channel` as PNG bytes, or RGBA prefixed with little-endian `u32` width
+/// and height.
+#[tauri::command]
+pub async fn render_preview_frame(
+  pool: State<'_, Pool<Sqlite>>,
+  recording_id: i64,
+  position_ms: u64,
+  project: Option<Project>,
+  preset: Option<ExportPreset>,
+  format: PreviewFrameFormat,
+  channel: Channel,
+) -> Result<(), String> {
+  let source_folder_path = db::recordings::get_recording_directory(&pool, recording_id)
+    .await
+    .map_err(|e| e.to_string())?;
+  let project = match project {
+    Some(project) => project,
+    None => project::service::load_project(&pool, recording_id).await?,
+  };
+
+  let captions = match project.captions.clone() {
+    Some(mode @ CaptionExportMode::BurnIn { .. }) => {
+      load_caption_export(&pool, recording_id, mode).await
+    }
+    _ => None,
+  };
+
+  let geometry = load_recording_row(&pool, recording_id)
+    .await
+    .as_ref()
+    .map(RecordingGeometry::from);
+
+  let preset = preset.unwrap_or_else(presets::default_preset);
+
+  let frame = tauri::async_runtime::spawn_blocking(move || {
+    service::render_frame(
+      &source_folder_path,
+      &project,
+      geometry.as_ref(),
+      captions.as_ref(),
+      &preset,
+      position_ms,
+      format,
+    )
+  })
+  .await
+  .map_err(|e| e.to_string())??;
+
+  let body = match format {
+    PreviewFrameFormat::Png => frame.data,
+    PreviewFrameFormat::Rgba => {
+      let mut body = Vec::with_capacity(8 + frame.data.len());
+      body.extend(&frame.width.to_le_bytes());
+      body.extend(&frame.height.to_le_bytes());
+      body.extend(frame.data);
+      body
+    }
+  };
+
+  channel
+    .send(InvokeResponseBody::Raw(body))
+    .map_err(|e| e.to_string())
+}
+
+/// Built in presets followed by the user's saved presets
+#[tauri::command]
+pub fn list_export_presets(app_handle: AppHandle) -> Vec<ExportPreset> {
+  presets::list_presets(&app_handle)
+}
+
+/// Save a user preset, returned with its assigned id
+#[tauri::command]
+pub fn save_export_preset(
+  app_handle: AppHandle,
+  preset: ExportPreset,
+) -> Result<ExportPreset, String> {
+  presets::save_preset(&app_handle, preset)
+}
+
+#[tauri::command]
+pub fn delete_export_preset(app_handle: AppHandle, id: String) -> Result<(), String> {
+  presets::delete_preset(&app_handle, &id)
+}
+
 #[tauri::command]
 pub async fn cancel_export(app_handle: AppHandle) {
   let editing_state: State<'_, Mutex<EditingState>> = app_handle.state();
 
-  if let Some(export_process) = editing_state.lock().take_export_process() {
+  if let Some(export_process) = editing_state.lock().cancel_export() {
     tauri::async_runtime::spawn_blocking(move || {
       let mut export_process_guard = export_process.lock();
       let _ = export_process_guard.kill();
@@ -0,0 +1,412 @@
+use std::{
+  collections::{HashMap, HashSet},
+  fmt,
+};
+
+use ffmpeg_sidecar::command::FfmpegCommand;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StreamType {
+  Video,
+  Audio,
+  Subtitle,
+}
+
+impl StreamType {
+  fn specifier(&self) -> &'static str {
+    match self {
+      StreamType::Video => "v",
+      StreamType::Audio => "a",
+      StreamType::Subtitle => "s",
+    }
+  }
+}
+
+/// Input file of the graph, its ffmpeg index is assigned in order of adding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InputId(usize);
+
+impl InputId {
+  pub fn index(&self) -> usize {
+    self.0
+  }
+
+  pub fn video(&self) -> Pad {
+    Pad::Stream(*self, StreamType::Video)
+  }
+
+  pub fn audio(&self) -> Pad {
+    Pad::Stream(*self, StreamType::Audio)
+  }
+
+  pub fn subtitle(&self) -> Pad {
+    Pad::Stream(*self, StreamType::Subtitle)
+  }
+}
+
+/// Stream flowing through the graph, either from an input or a filter output
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Pad {
+  Stream(InputId, StreamType),
+  Label(String),
+}
+
+impl Pad {
+  /// Stream specifier for `-map`, e.g. `0:v` or `[outv]`
+  pub fn map_specifier(&self) -> String {
+    match self {
+      Pad::Stream(input, stream_type) => format!("{}:{}", input.0, stream_type.specifier()),
+      Pad::Label(label) => format!("[{label}]"),
+    }
+  }
+}
+
+impl fmt::Display for Pad {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Pad::Stream(input, stream_type) => write!(f, "[{}:{}]", input.0, stream_type.specifier()),
+      Pad::Label(label) => write!(f, "[{label}]"),
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+struct Input {
+  /// Options applying to this input, e.g. `-ss`
+  options: Vec<String>,
+  path: String,
+}
+
+/// Filter, or comma separated filter chain, with its input and output pads
+#[derive(Debug, Clone)]
+struct FilterNode {
+  inputs: Vec<Pad>,
+  filter: String,
+  outputs: Vec<Pad>,
+}
+
+/// Inputs, filters and output maps of an ffmpeg command.
+///
+/// Input indices and labels are assigned by the graph, so streams are
+/// referenced by `Pad` rather than by hand computed index.
+#[derive(Debug, Clone, Default)]
+pub struct FilterGraph {
+  inputs: Vec<Input>,
+  filters: Vec<FilterNode>,
+  /// Pads mapped to each output file, in order
+  outputs: Vec<Vec<Pad>>,
+  labels: HashSet<String>,
+}
+
+impl FilterGraph {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn input(&mut self, path: impl Into<String>) -> InputId {
+    self.input_with_options(path, Vec::new())
+  }
+
+  pub fn input_with_options(&mut self, path: impl Into<String>, options: Vec<String>) -> InputId {
+    self.inputs.push(Input {
+      options,
+      path: path.into(),
+    });
+
+    InputId(self.inputs.len() - 1)
+  }
+
+  /// Unique label based on `name`
+  fn label(&mut self, name: &str) -> Pad {
+    let mut label = name.to_string();
+    let mut suffix = 1;
+    while self.labels.contains(&label) {
+      suffix += 1;
+      label = format!("{name}_{suffix}");
+    }
+
+    self.labels.insert(label.clone());
+    Pad::Label(label)
+  }
+
+  /// Add a filter with an output pad for each of `outputs`, returned in order
+  pub fn filter(
+    &mut self,
+    inputs: impl IntoIterator<Item = Pad>,
+    filter: impl Into<String>,
+    outputs: &[&str],
+  ) -> Vec<Pad> {
+    let outputs: Vec<Pad> = outputs.iter().map(|name| self.label(name)).collect();
+    self.filters.push(FilterNode {
+      inputs: inputs.into_iter().collect(),
+      filter: filter.into(),
+      outputs: outputs.clone(),
+    });
+
+    outputs
+  }
+
+  /// Add a filter with a single input and output
+  pub fn chain(&mut self, input: Pad, filter: impl Into<String>, output: &str) -> Pad {
+    self.filter([input], filter, &[output]).remove(0)
+  }
+
+  /// Add a filter splitting `input` into `count` copies, `split` or `asplit`
+  pub fn split(&mut self, input: Pad, split_filter: &str, output: &str, count: usize) -> Vec<Pad> {
+    let outputs: Vec<String> = (0..count).map(|i| format!("{output}{i}")).collect();
+    let outputs: Vec<&str> = outputs.iter().map(String::as_str).collect();
+    self.filter([input], format!("{split_filter}={count}"), &outputs)
+  }
+
+  /// Include `pad` in the current output
+  pub fn map(&mut self, pad: Pad) {
+    match self.outputs.last_mut() {
+      Some(maps) => maps.push(pad),
+      None => self.outputs.push(vec![pad]),
+    }
+  }
+
+  /// Map following pads to another output file
+  pub fn next_output(&mut self) {
+    self.outputs.push(Vec::new());
+  }
+
+  /// Check every pad refers to an existing input or is used exactly once
+  pub fn validate(&self) -> Result<(), String> {
+    let mut consumers: HashMap<&Pad, usize> = HashMap::new();
+    for pad in self
+      .filters
+      .iter()
+      .flat_map(|node| &node.inputs)
+      .chain(self.outputs.iter().flatten())
+    {
+      *consumers.entry(pad).or_default() += 1;
+    }
+
+    let produced: HashSet<&Pad> = self.filters.iter().flat_map(|node| &node.outputs).collect();
+
+    for pad in consumers.keys() {
+      match pad {
+        Pad::Stream(input, _) if input.0 >= self.inputs.len() => {
+          return Err(format!("{pad} refers to a missing input"));
+        }
+        Pad::Label(_) if !produced.contains(pad) => {
+          return Err(format!("{pad} is not produced by any filter"));
+        }
+        _ => {}
+      }
+    }
+
+    for pad in produced {
+      match consumers.get(pad) {
+        None => return Err(format!("{pad} is not used")),
+        Some(1) => {}
+        Some(_) => return Err(format!("{pad} is used more than once")),
+      }
+    }
+
+    if self.outputs.is_empty() {
+      return Err("Graph has no outputs".to_string());
+    }
+    if let Some(index) = self.outputs.iter().position(Vec::is_empty) {
+      return Err(format!("Output {index} has no streams"));
+    }
+
+    Ok(())
+  }
+
+  /// `filter_complex` value, `None` if the graph has no filters
+  pub fn filter_complex(&self) -> Option<String> {
+    if self.filters.is_empty() {
+      return None;
+    }
+
+    let nodes: Vec<String> = self
+      .filters
+      .iter()
+      .map(|node| {
+        let inputs: String = node.inputs.iter().map(Pad::to_string).collect();
+        let outputs: String = node.outputs.iter().map(Pad::to_string).collect();
+        format!("{inputs}{}{outputs}", node.filter)
+      })
+      .collect();
+
+    Some(nodes.join(";"))
+  }
+
+  /// ffmpeg arguments for the inputs and filters
+  pub fn input_args(&self) -> Result<Vec<String>, String> {
+    self.validate()?;
+
+    let mut args = Vec::new();
+    for input in &self.inputs {
+      args.extend(input.options.iter().cloned());
+      args.extend(["-i".to_string(), input.path.clone()]);
+    }
+
+    if let Some(filter_complex) = self.filter_complex() {
+      args.extend(["-filter_complex".to_string(), filter_complex]);
+    }
+
+    Ok(args)
+  }
+
+  /// ffmpeg `-map` arguments of output `index`
+  pub fn output_args(&self, index: usize) -> Vec<String> {
+    self
+      .outputs
+      .get(index)
+      .into_iter()
+      .flatten()
+      .flat_map(|pad| ["-map".to_string(), pad.map_specifier()])
+      .collect()
+  }
+
+  /// ffmpeg arguments for the inputs, filters and maps of a single output graph
+  pub fn to_args(&self) -> Result<Vec<String>, String> {
+    let mut args = self.input_args()?;
+    args.extend(self.output_args(0));
+    Ok(args)
+  }
+
+  /// Validate and add the graph to `command`, for a single output
+  pub fn apply(&self, command: &mut FfmpegCommand) -> Result<(), String> {
+    command.args(self.to_args()?);
+    Ok(())
+  }
+
+  /// Validate and add the inputs and filters to `command`, outputs are added
+  /// with `apply_output`
+  pub fn apply_inputs(&self, command: &mut FfmpegCommand) -> Result<(), String> {
+    command.args(self.input_args()?);
+    Ok(())
+  }
+
+  /// Add the maps of output `index` to `command`, before that output's options
+  pub fn apply_output(&self, command: &mut FfmpegCommand, index: usize) {
+    command.args(self.output_args(index));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn input_options_precede_their_input() {
+    let mut graph = FilterGraph::new();
+    let screen = graph.input_with_options("screen.mp4", vec!["-ss".to_string(), "5".to_string()]);
+    graph.map(screen.video());
+
+    assert_eq!(
+      graph.to_args(),
+      Ok(
+        ["-ss", "5", "-i", "screen.mp4", "-map", "0:v"]
+          .map(String::from)
+          .to_vec()
+      )
+    );
+  }
+
+  #[test]
+  fn labels_are_unique() {
+    let mut graph = FilterGraph::new();
+    let screen = graph.input("screen.mp4");
+    let first = graph.chain(screen.video(), "hflip", "flipped");
+    let second = graph.chain(first.clone(), "vflip", "flipped");
+
+    assert_eq!(first, Pad::Label("flipped".to_string()));
+    assert_eq!(second, Pad::Label("flipped_2".to_string()));
+  }
+
+  #[test]
+  fn split_labels_each_copy() {
+    let mut graph = FilterGraph::new();
+    let screen = graph.input("screen.mp4");
+    let copies = graph.split(screen.video(), "split", "copy", 2);
+    for pad in copies {
+      graph.map(pad);
+    }
+
+    assert_eq!(
+      graph.filter_complex(),
+      Some("[0:v]split=2[copy0][copy1]".to_string())
+    );
+    assert_eq!(graph.validate(), Ok(()));
+  }
+
+  #[test]
+  fn outputs_map_their_own_pads() {
+    let mut graph = FilterGraph::new();
+    let screen = graph.input("screen.mp4");
+    let microphone = graph.input("microphone.wav");
+    graph.map(screen.video());
+    graph.next_output();
+    graph.map(microphone.audio());
+
+    assert_eq!(graph.validate(), Ok(()));
+    assert_eq!(graph.output_args(0), ["-map", "0:v"]);
+    assert_eq!(graph.output_args(1), ["-map", "1:a"]);
+    assert!(graph.output_args(2).is_empty());
+  }
+
+  #[test]
+  fn rejects_duplicate_consumer() {
+    let mut graph = FilterGraph::new();
+    let screen = graph.input("screen.mp4");
+    let flipped = graph.chain(screen.video(), "hflip", "flipped");
+    graph.map(flipped.clone());
+    graph.map(flipped);
+
+    assert_eq!(
+      graph.validate(),
+      Err("[flipped] is used more than once".to_string())
+    );
+    assert!(graph.to_args().is_err());
+  }
+
+  #[test]
+  fn rejects_unused_label() {
+    let mut graph = FilterGraph::new();
+    let screen = graph.input("screen.mp4");
+    graph.filter([screen.video()], "split=2", &["used", "unused"]);
+    graph.map(Pad::Label("used".to_string()));
+
+    assert_eq!(graph.validate(), Err("[unused] is not used".to_string()));
+  }
+
+  #[test]
+  fn rejects_label_without_filter() {
+    let mut graph = FilterGraph::new();
+    graph.input("screen.mp4");
+    graph.map(Pad::Label("missing".to_string()));
+
+    assert_eq!(
+      graph.validate(),
+      Err("[missing] is not produced by any filter".to_string())
+    );
+  }
+
+  #[test]
+  fn rejects_missing_input() {
+    let mut graph = FilterGraph::new();
+    graph.input("screen.mp4");
+    graph.map(InputId(1).audio());
+
+    assert_eq!(
+      graph.validate(),
+      Err("[1:a] refers to a missing input".to_string())
+    );
+  }
+
+  #[test]
+  fn rejects_empty_output() {
+    let mut graph = FilterGraph::new();
+    let screen = graph.input("screen.mp4");
+    assert_eq!(graph.validate(), Err("Graph has no outputs".to_string()));
+
+    graph.map(screen.video());
+    graph.next_output();
+    assert_eq!(graph.validate(), Err("Output 1 has no streams".to_string()));
+  }
+}
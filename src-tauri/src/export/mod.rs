@@ -1,2 +1,5 @@
 pub mod commands;
+mod filter_graph;
+pub mod models;
+mod presets;
 mod service;
@@ -0,0 +1,379 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+use strum_macros::Display;
+use time::OffsetDateTime;
+
+use crate::captions::models::{CaptionExportMode, CaptionSegment};
+
+/// Video encoder used for an export attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoEncoder {
+  /// Platform hardware encoder (`h264_videotoolbox`, NVENC, QSV, AMF)
+  Hardware,
+  /// `libx264`, always available in the bundled ffmpeg
+  Software,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Display)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
+pub enum ExportFailureReason {
+  HardwareEncoderUnavailable,
+  DiskFull,
+  PermissionDenied,
+  MissingInput,
+  /// The target file size leaves too little bitrate for the video
+  TargetSizeUnreachable,
+  Unknown,
+}
+
+impl ExportFailureReason {
+  /// Classify a failed export from the tail of ffmpeg stderr.
+  pub fn from_stderr(lines: &[String]) -> Self {
+    let stderr = lines.join("\n").to_lowercase();
+
+    let matches_any = |needles: &[&str]| needles.iter().any(|n| stderr.contains(n));
+
+    if matches_any(&["no space left on device", "disk full"]) {
+      ExportFailureReason::DiskFull
+    } else if matches_any(&["permission denied", "operation not permitted"]) {
+      ExportFailureReason::PermissionDenied
+    } else if matches_any(&[
+      "error while opening encoder",
+      "cannot load nvcuda",
+      "cannot load cuda",
+      "no nvenc capable devices found",
+      "openencodesessionex failed",
+      "no device available for encoder",
+      "failed to create hardware device",
+      "error initializing an internal mfx session",
+      "cannot create compression session",
+      "amf failed",
+    ]) {
+      ExportFailureReason::HardwareEncoderUnavailable
+    } else if matches_any(&["no such file or directory", "invalid data found"]) {
+      ExportFailureReason::MissingInput
+    } else {
+      ExportFailureReason::Unknown
+    }
+  }
+}
+
+/// Payload of `Events::ExportFailed`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportFailed {
+  pub reason: ExportFailureReason,
+  pub exit_code: Option<i32>,
+  pub stderr_tail: Vec<String>,
+}
+
+/// Captions to include in an export.
+#[derive(Debug, Clone)]
+pub struct CaptionExport {
+  pub segments: Vec<CaptionSegment>,
+  pub mode: CaptionExportMode,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(
+  rename_all = "camelCase",
+  rename_all_fields = "camelCase",
+  tag = "type"
+)]
+pub enum EditAction {
+  Cut,
+  SpeedUp { factor: f64 },
+}
+
+/// Edit applied to `[start_ms, end_ms)` of the recording timeline.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditRange {
+  pub start_ms: u64,
+  pub end_ms: u64,
+  pub action: EditAction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreviewFrameFormat {
+  Png,
+  Rgba,
+}
+
+/// Single composited frame, see `export::service::render_frame`
+#[derive(Debug, Clone)]
+pub struct RenderedFrame {
+  pub width: u32,
+  pub height: u32,
+  pub data: Vec<u8>,
+}
+
+/// Container and codecs of an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFormat {
+  Mp4H264,
+  Mp4Hevc,
+  WebmVp9,
+  WebmAv1,
+  /// ProRes in a MOV, large files intended for further editing
+  ProresMov,
+  Gif,
+  M4a,
+  Mp3,
+  Wav,
+}
+
+impl ExportFormat {
+  pub fn extension(&self) -> &'static str {
+    match self {
+      ExportFormat::Mp4H264 | ExportFormat::Mp4Hevc => "mp4",
+      ExportFormat::WebmVp9 | ExportFormat::WebmAv1 => "webm",
+      ExportFormat::ProresMov => "mov",
+      ExportFormat::Gif => "gif",
+      ExportFormat::M4a => "m4a",
+      ExportFormat::Mp3 => "mp3",
+      ExportFormat::Wav => "wav",
+    }
+  }
+
+  pub fn has_video(&self) -> bool {
+    !matches!(
+      self,
+      ExportFormat::M4a | ExportFormat::Mp3 | ExportFormat::Wav
+    )
+  }
+
+  pub fn has_audio(&self) -> bool {
+    *self != ExportFormat::Gif
+  }
+
+  /// Whether the first attempt uses the platform hardware encoder
+  pub fn has_hardware_encoder(&self) -> bool {
+    match self {
+      ExportFormat::Mp4H264 => true,
+      ExportFormat::Mp4Hevc => cfg!(target_os = "macos"),
+      _ => false,
+    }
+  }
+
+  /// Whether soft subtitles can be muxed into the container
+  pub fn supports_subtitles(&self) -> bool {
+    matches!(
+      self,
+      ExportFormat::Mp4H264
+        | ExportFormat::Mp4Hevc
+        | ExportFormat::WebmVp9
+        | ExportFormat::WebmAv1
+        | ExportFormat::ProresMov
+    )
+  }
+
+  /// Whether the format can be encoded in two passes to a target file size
+  pub fn supports_target_size(&self) -> bool {
+    matches!(
+      self,
+      ExportFormat::Mp4H264 | ExportFormat::Mp4Hevc | ExportFormat::WebmVp9
+    )
+  }
+
+  pub fn supports_chapters(&self) -> bool {
+    !matches!(self, ExportFormat::Gif | ExportFormat::Wav)
+  }
+
+  pub fn supports_metadata(&self) -> bool {
+    *self != ExportFormat::Gif
+  }
+
+  /// MP4 family containers, which only keep custom tags with `use_metadata_tags`
+  pub fn is_mov_family(&self) -> bool {
+    matches!(
+      self,
+      ExportFormat::Mp4H264 | ExportFormat::Mp4Hevc | ExportFormat::ProresMov | ExportFormat::M4a
+    )
+  }
+}
+
+/// Quality of an export, mapped to the rate control of each codec
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportQuality {
+  Low,
+  Medium,
+  #[default]
+  High,
+}
+
+impl ExportQuality {
+  /// Value of a codec setting for this quality
+  pub fn select<T>(self, low: T, medium: T, high: T) -> T {
+    match self {
+      ExportQuality::Low => low,
+      ExportQuality::Medium => medium,
+      ExportQuality::High => high,
+    }
+  }
+}
+
+/// Named export settings, either built in or saved by the user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportPreset {
+  /// Empty for a new user preset, assigned when saved
+  #[serde(default)]
+  pub id: String,
+  pub name: String,
+  pub format: ExportFormat,
+  /// Output height, width keeps the aspect ratio. Never upscales, `None` keeps
+  /// the recording size
+  #[serde(default)]
+  pub height: Option<u32>,
+  /// `None` keeps the recording frame rate
+  #[serde(default)]
+  pub frame_rate: Option<u32>,
+  #[serde(default)]
+  pub quality: ExportQuality,
+  /// Drawn over the recording, not over the intro and outro
+  #[serde(default)]
+  pub watermark: Option<Watermark>,
+  /// Clip joined before the recording, scaled to the output
+  #[serde(default)]
+  pub intro_path: Option<PathBuf>,
+  /// Clip joined after the recording, scaled to the output
+  #[serde(default)]
+  pub outro_path: Option<PathBuf>,
+  #[serde(default)]
+  pub built_in: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WatermarkPosition {
+  TopLeft,
+  TopRight,
+  BottomLeft,
+  #[default]
+  BottomRight,
+  Center,
+}
+
+/// Image, e.g. a logo, drawn over the exported video
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Watermark {
+  pub image_path: PathBuf,
+  #[serde(default)]
+  pub position: WatermarkPosition,
+  /// Width as a fraction of the output width, height keeps the aspect ratio
+  #[serde(default = "default_watermark_scale")]
+  pub scale: f64,
+  /// From `0.0`, invisible, to `1.0`, opaque
+  #[serde(default = "default_watermark_opacity")]
+  pub opacity: f64,
+  /// Distance from the output edges in pixels
+  #[serde(default = "default_watermark_margin")]
+  pub margin: u32,
+}
+
+fn default_watermark_scale() -> f64 {
+  0.15
+}
+
+fn default_watermark_opacity() -> f64 {
+  0.8
+}
+
+fn default_watermark_margin() -> u32 {
+  24
+}
+
+/// Container metadata written to exported files
+#[derive(Debug, Clone, Default)]
+pub struct ExportMetadata {
+  pub title: Option<String>,
+  /// When the recording was made
+  pub creation_time: Option<OffsetDateTime>,
+  /// Name and version of the app, written as the comment
+  pub encoder: String,
+  /// Written last, so they take precedence over the fields above
+  pub tags: BTreeMap<String, String>,
+}
+
+/// Payload of `Events::ExportSizeReport`, sent before `ExportComplete` when
+/// exporting to a target file size
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSizeReport {
+  pub max_size_bytes: u64,
+  pub size_bytes: u64,
+  /// Output height, lowered from the preset if needed to fit the size
+  pub height: Option<u32>,
+  /// Output frame rate, lowered from the preset if needed to fit the size
+  pub frame_rate: Option<u32>,
+  /// Bits per second
+  pub video_bitrate: u64,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn classify(stderr: &[&str]) -> ExportFailureReason {
+    let lines: Vec<String> = stderr.iter().map(|line| line.to_string()).collect();
+    ExportFailureReason::from_stderr(&lines)
+  }
+
+  #[test]
+  fn from_stderr_classifies_each_reason() {
+    let cases = [
+      (
+        vec![
+          "[mp4 @ 0x7f8b1c004a00] Error writing trailer: No space left on device",
+          "Error closing file out.mp4: No space left on device",
+        ],
+        ExportFailureReason::DiskFull,
+      ),
+      (
+        vec!["/Volumes/Share/out.mp4: Permission denied"],
+        ExportFailureReason::PermissionDenied,
+      ),
+      (
+        vec![
+          "[h264_nvenc @ 0000020a] OpenEncodeSessionEx failed: unsupported device (2): (no details)",
+          "[vost#0:0/h264_nvenc @ 0000020a] Error while opening encoder - maybe incorrect parameters such as bit_rate, rate, width or height.",
+        ],
+        ExportFailureReason::HardwareEncoderUnavailable,
+      ),
+      (
+        vec!["[in#1 @ 0x600001] Error opening input: No such file or directory"],
+        ExportFailureReason::MissingInput,
+      ),
+      (
+        vec!["screen.mp4: Invalid data found when processing input"],
+        ExportFailureReason::MissingInput,
+      ),
+      (
+        vec!["Conversion failed!"],
+        ExportFailureReason::Unknown,
+      ),
+      (vec![], ExportFailureReason::Unknown),
+    ];
+
+    for (stderr, reason) in cases {
+      assert_eq!(classify(&stderr), reason, "{stderr:?}");
+    }
+  }
+
+  #[test]
+  fn from_stderr_prefers_disk_full_over_hardware_encoder() {
+    let stderr = [
+      "[h264_videotoolbox @ 0x7f9] Error while opening encoder",
+      "av_interleaved_write_frame(): No space left on device",
+    ];
+
+    assert_eq!(classify(&stderr), ExportFailureReason::DiskFull);
+  }
+}
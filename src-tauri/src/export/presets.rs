@@ -0,0 +1,179 @@
+use serde_json::json;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+use uuid::Uuid;
+
+use crate::{
+  constants::store::{EXPORT_PRESETS, STORE_NAME},
+  export::models::{ExportFormat, ExportPreset, ExportQuality},
+};
+
+/// Id of the preset used when an export does not name one
+pub const DEFAULT_PRESET_ID: &str = "mp4-h264";
+
+fn built_in(
+  id: &str,
+  name: &str,
+  format: ExportFormat,
+  height: Option<u32>,
+  frame_rate: Option<u32>,
+  quality: ExportQuality,
+) -> ExportPreset {
+  ExportPreset {
+    id: id.to_string(),
+    name: name.to_string(),
+    format,
+    height,
+    frame_rate,
+    quality,
+    watermark: None,
+    intro_path: None,
+    outro_path: None,
+    built_in: true,
+  }
+}
+
+pub fn built_in_presets() -> Vec<ExportPreset> {
+  vec![
+    built_in(
+      DEFAULT_PRESET_ID,
+      "MP4 (H.264)",
+      ExportFormat::Mp4H264,
+      None,
+      None,
+      ExportQuality::High,
+    ),
+    built_in(
+      "mp4-h264-720p",
+      "MP4 (H.264) 720p",
+      ExportFormat::Mp4H264,
+      Some(720),
+      Some(30),
+      ExportQuality::Medium,
+    ),
+    built_in(
+      "mp4-hevc",
+      "MP4 (HEVC)",
+      ExportFormat::Mp4Hevc,
+      None,
+      None,
+      ExportQuality::High,
+    ),
+    built_in(
+      "webm-vp9",
+      "WebM (VP9)",
+      ExportFormat::WebmVp9,
+      None,
+      None,
+      ExportQuality::High,
+    ),
+    built_in(
+      "webm-av1",
+      "WebM (AV1)",
+      ExportFormat::WebmAv1,
+      None,
+      None,
+      ExportQuality::High,
+    ),
+    built_in(
+      "prores-mov",
+      "ProRes (MOV)",
+      ExportFormat::ProresMov,
+      None,
+      None,
+      ExportQuality::High,
+    ),
+    built_in(
+      "gif",
+      "GIF",
+      ExportFormat::Gif,
+      Some(480),
+      Some(15),
+      ExportQuality::High,
+    ),
+    built_in(
+      "m4a",
+      "Audio (M4A)",
+      ExportFormat::M4a,
+      None,
+      None,
+      ExportQuality::High,
+    ),
+    built_in(
+      "mp3",
+      "Audio (MP3)",
+      ExportFormat::Mp3,
+      None,
+      None,
+      ExportQuality::High,
+    ),
+    built_in(
+      "wav",
+      "Audio (WAV)",
+      ExportFormat::Wav,
+      None,
+      None,
+      ExportQuality::High,
+    ),
+  ]
+}
+
+pub fn default_preset() -> ExportPreset {
+  built_in_presets().remove(0)
+}
+
+fn user_presets(app_handle: &AppHandle) -> Vec<ExportPreset> {
+  app_handle
+    .store(STORE_NAME)
+    .ok()
+    .and_then(|store| store.get(EXPORT_PRESETS))
+    .and_then(|value| serde_json::from_value(value).ok())
+    .unwrap_or_default()
+}
+
+fn set_user_presets(app_handle: &AppHandle, presets: &[ExportPreset]) -> Result<(), String> {
+  app_handle
+    .store(STORE_NAME)
+    .map_err(|e| e.to_string())?
+    .set(EXPORT_PRESETS, json!(presets));
+
+  Ok(())
+}
+
+/// Built in presets followed by user presets
+pub fn list_presets(app_handle: &AppHandle) -> Vec<ExportPreset> {
+  let mut presets = built_in_presets();
+  presets.extend(user_presets(app_handle));
+  presets
+}
+
+/// Add or replace a user preset, built in presets are saved as a copy
+pub fn save_preset(
+  app_handle: &AppHandle,
+  mut preset: ExportPreset,
+) -> Result<ExportPreset, String> {
+  if preset.name.trim().is_empty() {
+    return Err("Preset name cannot be empty".to_string());
+  }
+
+  if preset.id.is_empty() || preset.built_in || built_in_presets().iter().any(|p| p.id == preset.id)
+  {
+    preset.id = Uuid::new_v4().to_string();
+  }
+  preset.built_in = false;
+
+  let mut presets = user_presets(app_handle);
+  match presets.iter_mut().find(|p| p.id == preset.id) {
+    Some(existing) => *existing = preset.clone(),
+    None => presets.push(preset.clone()),
+  }
+  set_user_presets(app_handle, &presets)?;
+
+  Ok(preset)
+}
+
+pub fn delete_preset(app_handle: &AppHandle, id: &str) -> Result<(), String> {
+  let mut presets = user_presets(app_handle);
+  presets.retain(|p| p.id != id);
+  set_user_presets(app_handle, &presets)
+}
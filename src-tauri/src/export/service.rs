@@ -1,6 +1,6 @@
 use std::sync::Arc;
 use std::{
-  io::{BufRead, BufReader},
+  io::{BufRead, BufReader, Cursor, Read, Write},
   path::{Path, PathBuf},
   process::ChildStdout,
 };
@@ -8,6 +8,8 @@ use std::{
 use ffmpeg_sidecar::command::FfmpegCommand;
 use parking_lot::Mutex;
 use tauri::{AppHandle, Emitter, Manager, State};
+use time::{format_description::well_known::Rfc3339, UtcOffset};
+use tokio::sync::RwLockReadGuard;
 use uuid::Uuid;
 
 use std::process::Command;
@@ -15,25 +17,255 @@ use std::process::Command;
 use crate::models::EditingState;
 #[cfg(target_os = "windows")]
 use crate::recording::ffmpeg::get_hardware_encoder;
-#[cfg(debug_assertions)]
-use crate::recording::ffmpeg::log_ffmpeg_output;
-use crate::{constants::Events, recording::models::RecordingFile};
+use crate::{
+  captions::{
+    models::{CaptionExportMode, CaptionSegment, SubtitleFormat},
+    service::{burn_in_filter, soft_subtitle_codec, write_subtitles},
+  },
+  compositor::{
+    frame::Frame,
+    models::{RecordingGeometry, COMPOSITOR_FRAME_RATE},
+    service::Compositor,
+  },
+  constants::Events,
+  export::{
+    commands::ExportOptions,
+    filter_graph::{FilterGraph, InputId, Pad},
+    models::{
+      CaptionExport, EditAction, EditRange, ExportFailed, ExportFailureReason, ExportFormat,
+      ExportMetadata, ExportPreset, ExportQuality, ExportSizeReport, PreviewFrameFormat,
+      RenderedFrame, VideoEncoder, Watermark, WatermarkPosition,
+    },
+    presets::default_preset,
+  },
+  markers::{
+    models::Marker,
+    service::{chapters_to_text, markers_to_chapters, write_ffmetadata_chapters, Chapter},
+  },
+  project::models::{AudioSettings, Project},
+  recording::{
+    ffmpeg::{capture_ffmpeg_stderr, has_audio_stream, shortest_video_length, video_dimensions},
+    file::LIBRARY_LOCK,
+    models::RecordingFile,
+  },
+};
 
-pub fn encode_recording(
-  app_handle: AppHandle,
+/// Maximum number of ffmpeg stderr lines kept for `ExportFailed`
+const STDERR_TAIL_LINES: usize = 20;
+
+const MIN_SPEED_FACTOR: f64 = 0.5;
+const MAX_SPEED_FACTOR: f64 = 100.0;
+
+/// Frame rate of GIF exports without a preset frame rate, and the highest allowed
+const GIF_FRAME_RATE: (u32, u32) = (15, 30);
+/// Height of GIF exports without a preset height, and the highest allowed
+const GIF_HEIGHT: (u32, u32) = (480, 720);
+
+/// Audio format the intro, outro and recording are converted to before joining
+const JOINED_AUDIO_FORMAT: &str = "aresample=48000,aformat=sample_fmts=fltp:channel_layouts=stereo";
+
+/// Share of a target file size left for container overhead
+const TARGET_SIZE_OVERHEAD: f64 = 0.03;
+/// Lowest video bitrate, in bits per second, worth encoding to a target size
+const MIN_TARGET_VIDEO_BITRATE: u64 = 100_000;
+/// Heights tried, in order, when a target size leaves too few bits per pixel
+const TARGET_SIZE_HEIGHTS: [u32; 4] = [1080, 720, 540, 360];
+
+/// Everything needed to (re)run an export, independent of encoder choice.
+#[derive(Debug, Clone)]
+struct ExportJob {
   source_folder_path: PathBuf,
-  destination_file_path: PathBuf,
-  separate_audio_tracks: bool,
-  separate_camera_file: bool,
+  camera_path: Option<PathBuf>,
   open_folder_after_export: bool,
+  project: Project,
+  // Needed to draw the cursor, `None` if the recording is unknown
+  geometry: Option<RecordingGeometry>,
+  captions: Option<CaptionExport>,
+  // Temporary SRT used as input for burned in captions, on the recording timeline
+  subtitles_file: Option<PathBuf>,
+  /// Written from a single decode and composite pass, the first is the main output
+  outputs: Vec<ExportOutput>,
+  /// Applies to the main output
+  size_target: Option<SizeTarget>,
+  metadata: ExportMetadata,
+  /// Keeps the library from moving until every pass and retry finished
+  _library_guard: Arc<RwLockReadGuard<'static, ()>>,
+}
+
+impl ExportJob {
+  fn main_output(&self) -> &ExportOutput {
+    &self.outputs[0]
+  }
+}
+
+/// File written by an export job
+#[derive(Debug, Clone)]
+struct ExportOutput {
+  output_path: PathBuf,
+  preset: ExportPreset,
+  intro: Option<Clip>,
+  outro: Option<Clip>,
+  chapters: Vec<Chapter>,
+  // Temporary ffmetadata used as input for embedded chapters
+  chapters_file: Option<PathBuf>,
+  // Temporary SRT used as input for soft captions, on the output timeline
+  subtitles_file: Option<PathBuf>,
+}
+
+impl ExportOutput {
+  /// Where the recording starts in the output
+  fn intro_ms(&self) -> u64 {
+    self.intro.as_ref().map_or(0, |clip| clip.duration_ms)
+  }
+}
+
+/// Intro or outro of the preset, probed before exporting
+#[derive(Debug, Clone)]
+struct Clip {
+  path: PathBuf,
+  duration_ms: u64,
+  has_audio: bool,
+}
+
+/// Bitrates fitting an export into `max_size_bytes`, see `plan_size_target`
+#[derive(Debug, Clone)]
+struct SizeTarget {
+  max_size_bytes: u64,
+  /// Duration of the edited output
+  duration_ms: u64,
+  /// Bits per second
+  video_bitrate: u64,
+  /// Bits per second, per track
+  audio_bitrate: u64,
+  /// Prefix of the first pass statistics files
+  passlog_file: PathBuf,
+  /// Whether the second pass was re-run for overshooting the size
+  retried: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EncodePass {
+  Single,
+  /// Analysis only, output is discarded
+  First,
+  Second,
+}
+
+/// Export the recording in `options.source_folder_path` as edited in `project`,
+/// to the destination and any additional outputs
+pub fn encode_recording(
+  app_handle: AppHandle,
+  options: ExportOptions,
+  project: Project,
+  geometry: Option<RecordingGeometry>,
+  captions: Option<CaptionExport>,
+  markers: Vec<Marker>,
+  metadata: ExportMetadata,
 ) {
-  log::info!("Starting export to {destination_file_path:?}");
-  let mut child = FfmpegCommand::new();
+  let ExportOptions {
+    source_folder_path,
+    destination_file_path,
+    open_folder_after_export,
+    preset,
+    max_size_bytes,
+    additional_outputs,
+    ..
+  } = options;
 
-  let available_streams = check_recording_files(source_folder_path.as_path());
+  let Ok(library_guard) = LIBRARY_LOCK.try_read() else {
+    let _ = app_handle.emit(
+      Events::ExportFailed.as_ref(),
+      ExportFailed {
+        reason: ExportFailureReason::Unknown,
+        exit_code: None,
+        stderr_tail: vec!["Cannot export while the library is being moved".to_string()],
+      },
+    );
+    return;
+  };
 
-  let (output_path, camera_path) =
-    prepare_output_path(&destination_file_path, separate_camera_file);
+  let destinations: Vec<(PathBuf, ExportPreset)> =
+    std::iter::once((destination_file_path, preset.unwrap_or_else(default_preset)))
+      .chain(
+        additional_outputs
+          .into_iter()
+          .map(|output| (output.destination_file_path, output.preset)),
+      )
+      .collect();
+
+  let mut clips = Vec::with_capacity(destinations.len());
+  for (_, preset) in &destinations {
+    match probe_preset_inputs(preset) {
+      Ok(preset_clips) => clips.push(preset_clips),
+      Err(failure) => {
+        log::warn!(
+          "Export preset inputs unavailable: {:?}",
+          failure.stderr_tail
+        );
+        let _ = app_handle.emit(Events::ExportFailed.as_ref(), failure);
+        return;
+      }
+    }
+  }
+
+  let with_chapters = (project.chapters || project.chapters_text_file) && !markers.is_empty();
+  let mut camera_path = None;
+  let mut outputs: Vec<ExportOutput> = Vec::with_capacity(destinations.len());
+  for ((destination_file_path, preset), (intro, outro)) in destinations.into_iter().zip(clips) {
+    // The extension always matches the container
+    let destination_file_path = destination_file_path.with_extension(preset.format.extension());
+
+    let output_path = if outputs.is_empty() {
+      let (output_path, main_camera_path) =
+        prepare_output_path(&destination_file_path, project.separate_camera_file);
+      camera_path = main_camera_path;
+      output_path
+    } else {
+      additional_output_path(&destination_file_path, &outputs)
+    };
+
+    log::info!("Starting export to {output_path:?}");
+
+    let intro_ms = intro.as_ref().map_or(0, |clip| clip.duration_ms);
+    let outro_ms = outro.as_ref().map_or(0, |clip| clip.duration_ms);
+
+    let chapters = if with_chapters {
+      export_chapters(
+        &source_folder_path,
+        &markers,
+        &project.edits,
+        (intro_ms, outro_ms),
+      )
+    } else {
+      vec![]
+    };
+
+    let chapters_file = if project.chapters && preset.format.supports_chapters() {
+      write_chapters_file(&chapters)
+    } else {
+      None
+    };
+
+    let subtitles_file = match &captions {
+      Some(CaptionExport {
+        segments,
+        mode: CaptionExportMode::Soft,
+      }) if preset.format.supports_subtitles() => {
+        write_temporary_subtitles(&output_captions(segments, &project.edits, intro_ms))
+      }
+      _ => None,
+    };
+
+    outputs.push(ExportOutput {
+      output_path,
+      preset,
+      intro,
+      outro,
+      chapters,
+      chapters_file,
+      subtitles_file,
+    });
+  }
 
   // Start with copying across camera if relevant, better UX for progress
   // Instead of percentage 100% -> indeterminate, it goes the opposite
@@ -45,45 +277,312 @@ pub fn encode_recording(
     );
   }
 
-  configure_input_streams(
-    &mut child,
-    &source_folder_path,
-    &available_streams,
-    separate_camera_file,
-  );
+  // Burned in before the edit list is applied
+  let subtitles_file = match &captions {
+    Some(CaptionExport {
+      segments,
+      mode: CaptionExportMode::BurnIn { .. },
+    }) => write_temporary_subtitles(segments),
+    _ => None,
+  };
 
-  configure_progress_options(&mut child);
+  let mut job = ExportJob {
+    source_folder_path,
+    camera_path,
+    open_folder_after_export,
+    project,
+    geometry,
+    captions,
+    subtitles_file,
+    outputs,
+    size_target: None,
+    metadata,
+    _library_guard: Arc::new(library_guard),
+  };
 
-  let video_filter = configure_video_tracks(
-    &mut child,
-    available_streams.has_camera,
-    separate_camera_file,
-  );
+  if let Some(max_size_bytes) = max_size_bytes {
+    match plan_size_target(&job, max_size_bytes) {
+      Ok((size_target, preset)) => {
+        log::info!(
+          "Exporting to {max_size_bytes} bytes at {} bps video, {:?}p {:?} fps",
+          size_target.video_bitrate,
+          preset.height,
+          preset.frame_rate
+        );
+        job.size_target = Some(size_target);
+        job.outputs[0].preset = preset;
+      }
+      Err(failure) => {
+        emit_export_failed(&app_handle, &job, failure);
+        return;
+      }
+    }
+
+    // Two pass encoding needs the software encoders
+    run_export(app_handle, job, VideoEncoder::Software, EncodePass::First);
+    return;
+  }
+
+  let encoder = if job
+    .outputs
+    .iter()
+    .any(|output| output.preset.format.has_hardware_encoder())
+  {
+    VideoEncoder::Hardware
+  } else {
+    VideoEncoder::Software
+  };
+
+  run_export(app_handle, job, encoder, EncodePass::Single);
+}
+
+/// Spawn ffmpeg for the export job and monitor it in a separate thread.
+///
+/// Every output is encoded by the same ffmpeg process from one composited
+/// video. A hardware encoder failure is retried once with the software encoder.
+/// The first of two passes starts the second once finished.
+fn run_export(app_handle: AppHandle, job: ExportJob, encoder: VideoEncoder, pass: EncodePass) {
+  let mut child = FfmpegCommand::new();
+
+  // The first pass only analyses the main output
+  let outputs = match pass {
+    EncodePass::First => &job.outputs[..1],
+    EncodePass::Single | EncodePass::Second => &job.outputs[..],
+  };
+  let output_count = |has_stream: fn(&ExportFormat) -> bool| {
+    outputs
+      .iter()
+      .filter(|output| has_stream(&output.preset.format))
+      .count()
+  };
+  let has_video = output_count(ExportFormat::has_video) > 0;
+  let has_audio = output_count(ExportFormat::has_audio) > 0;
+
+  let compositor = match has_video
+    .then(|| Compositor::new(&job.source_folder_path, &job.project, job.geometry.as_ref()))
+    .transpose()
+  {
+    Ok(compositor) => compositor,
+    Err(e) => {
+      log::error!("Failed to prepare export compositor: {e}");
+      emit_export_failed(
+        &app_handle,
+        &job,
+        ExportFailed {
+          reason: ExportFailureReason::Unknown,
+          exit_code: None,
+          stderr_tail: vec![e],
+        },
+      );
+      return;
+    }
+  };
+
+  let available_streams = if has_audio {
+    check_recording_files(job.source_folder_path.as_path(), &job.project.audio)
+  } else {
+    RecordingFilePresence::default()
+  };
+
+  let has_audio_only_output = outputs
+    .iter()
+    .any(|output| !output.preset.format.has_video());
+  if has_audio_only_output && !available_streams.has_audio() {
+    emit_export_failed(
+      &app_handle,
+      &job,
+      ExportFailed {
+        reason: ExportFailureReason::MissingInput,
+        exit_code: None,
+        stderr_tail: vec!["The recording has no audible audio to export".to_string()],
+      },
+    );
+    return;
+  }
+
+  let mut graph = FilterGraph::new();
+  let composited = compositor
+    .as_ref()
+    .map(|compositor| add_composited_input(&mut graph, compositor.output_size()));
+  let inputs = add_recording_inputs(&mut graph, &job.source_folder_path, &available_streams);
+
+  let burn_in = match (&job.subtitles_file, job.captions.as_ref().map(|c| &c.mode)) {
+    (Some(subtitles_file), Some(CaptionExportMode::BurnIn { style })) => {
+      Some(burn_in_filter(subtitles_file, style))
+    }
+    _ => None,
+  };
+
+  let video = composited.map(|composited| match burn_in {
+    Some(captions) => graph.chain(composited.video(), captions, "captioned"),
+    None => composited.video(),
+  });
 
-  let audio_filter = configure_audio_tracks(
-    &mut child,
-    &available_streams,
+  // Audio only containers hold a single track, mixed per output below
+  let separate_audio_tracks = job.project.separate_audio_tracks && has_video;
+  let audio = configure_audio_tracks(
+    &mut graph,
+    &inputs,
+    &job.project.audio,
     separate_audio_tracks,
-    separate_camera_file,
   );
 
-  let mut filters = Vec::new();
-  if let Some(f) = video_filter {
-    filters.push(f);
+  let (video, audio) = configure_edit_list(&mut graph, &job.project.edits, video, audio);
+
+  // Each output filters its own copy of the edited streams
+  let mut videos = video
+    .map(|video| {
+      copies(
+        &mut graph,
+        video,
+        "split",
+        "output_v",
+        output_count(ExportFormat::has_video),
+      )
+    })
+    .unwrap_or_default()
+    .into_iter();
+  let mut tracks: Vec<_> = audio
+    .into_iter()
+    .enumerate()
+    .map(|(track, pad)| {
+      copies(
+        &mut graph,
+        pad,
+        "asplit",
+        &format!("output_a{track}_"),
+        output_count(ExportFormat::has_audio),
+      )
+      .into_iter()
+    })
+    .collect();
+
+  // Chapters input and whether soft subtitles are mapped, for each output
+  let mut output_streams = Vec::with_capacity(outputs.len());
+  for (index, output) in outputs.iter().enumerate() {
+    if index > 0 {
+      graph.next_output();
+    }
+    let format = output.preset.format;
+
+    let audio: Vec<Pad> = if format.has_audio() {
+      tracks.iter_mut().filter_map(Iterator::next).collect()
+    } else {
+      vec![]
+    };
+    let audio = if audio.len() == 2 && !(separate_audio_tracks && format.has_video()) {
+      graph.filter(audio, "amix=inputs=2", &["output_mix"])
+    } else {
+      audio
+    };
+
+    let video = if format.has_video() {
+      videos.next()
+    } else {
+      None
+    };
+    let audio = match (video, &compositor) {
+      (Some(video), Some(compositor)) => {
+        let (video, audio) =
+          configure_output_video(&mut graph, video, audio, output, compositor.output_size());
+        graph.map(video);
+        audio
+      }
+      _ => audio,
+    };
+    for pad in audio {
+      graph.map(pad);
+    }
+
+    // The first pass output is discarded
+    let soft_subtitles_input = output
+      .subtitles_file
+      .as_ref()
+      .filter(|_| pass != EncodePass::First)
+      .map(|subtitles_file| graph.input(subtitles_file.to_string_lossy()));
+
+    if let Some(subtitles) = soft_subtitles_input {
+      graph.map(subtitles.subtitle());
+    }
+
+    let chapters_input = output
+      .chapters_file
+      .as_ref()
+      .filter(|_| pass != EncodePass::First)
+      .map(|chapters_file| graph.input(chapters_file.to_string_lossy()));
+
+    output_streams.push((chapters_input, soft_subtitles_input.is_some()));
   }
-  if let Some(f) = audio_filter {
-    filters.push(f);
+
+  if let Err(e) = graph.apply_inputs(&mut child) {
+    log::error!("Invalid export filter graph: {e}");
+    emit_export_failed(
+      &app_handle,
+      &job,
+      ExportFailed {
+        reason: ExportFailureReason::Unknown,
+        exit_code: None,
+        stderr_tail: vec![e],
+      },
+    );
+    return;
   }
-  if !filters.is_empty() {
-    child.filter_complex(filters.join(";"));
+
+  configure_progress_options(&mut child);
+
+  for (index, (output, (chapters_input, has_soft_subtitles))) in
+    outputs.iter().zip(output_streams).enumerate()
+  {
+    graph.apply_output(&mut child, index);
+
+    if let Some(chapters) = chapters_input {
+      child.args(["-map_chapters", &chapters.index().to_string()]);
+    }
+
+    if has_soft_subtitles {
+      child.args(["-c:s", soft_subtitle_codec(&output.output_path)]);
+    }
+
+    match &job.size_target {
+      Some(size_target) if index == 0 => {
+        configure_target_size_options(&mut child, &job, output, size_target, pass)
+      }
+      _ => configure_output_options(&mut child, &job, output, encoder),
+    }
   }
 
-  configure_output_options(&mut child, &output_path);
+  let mut ffmpeg_child = match child.spawn() {
+    Ok(ffmpeg_child) => ffmpeg_child,
+    Err(e) => {
+      log::error!("Failed to spawn export ffmpeg: {e}");
+      emit_export_failed(
+        &app_handle,
+        &job,
+        ExportFailed {
+          reason: ExportFailureReason::Unknown,
+          exit_code: None,
+          stderr_tail: vec![e.to_string()],
+        },
+      );
+      return;
+    }
+  };
 
-  let mut ffmpeg_child = child.spawn().unwrap();
+  let stderr_handle = capture_ffmpeg_stderr(
+    ffmpeg_child.take_stderr().unwrap(),
+    "[export]".to_string(),
+    STDERR_TAIL_LINES,
+  );
 
-  #[cfg(debug_assertions)]
-  log_ffmpeg_output(ffmpeg_child.take_stderr().unwrap(), "[export]".to_string());
+  // Killing the encoder on cancel closes stdin, which stops the compositor
+  let frames_input = ffmpeg_child.take_stdin().unwrap();
+  let source_folder_path = job.source_folder_path.clone();
+  let compositor_handle = std::thread::spawn(move || match compositor {
+    Some(compositor) => compositor
+      .write_frames(&source_folder_path, frames_input)
+      .map(|_| ()),
+    None => Ok(()),
+  });
 
   let ffmpeg_arc = Arc::new(Mutex::new(ffmpeg_child));
 
@@ -95,184 +594,1392 @@ pub fn encode_recording(
 
   let stdout = ffmpeg_arc.clone().lock().take_stdout().unwrap();
   let app_handle_for_reader = app_handle.clone();
+  // Each pass of a two pass encode is half of the progress
+  let duration_ms = job
+    .size_target
+    .as_ref()
+    .map_or(0, |target| target.duration_ms);
+  let to_progress = move |milliseconds: u64| match pass {
+    EncodePass::Single => milliseconds,
+    EncodePass::First => milliseconds / 2,
+    EncodePass::Second => (duration_ms + milliseconds) / 2,
+  };
+
   std::thread::spawn(move || {
-    ffmpeg_progress_reader(app_handle_for_reader, stdout);
+    ffmpeg_progress_reader(app_handle_for_reader, stdout, to_progress);
 
     let status = ffmpeg_arc.lock().wait(); // Clean up resources
+    let stderr_tail = stderr_handle.join().unwrap_or_default();
+    let composited = compositor_handle
+      .join()
+      .unwrap_or_else(|_| Err("Compositor panicked".to_string()));
+
+    let cancelled = {
+      let editing_state: State<'_, Mutex<EditingState>> = app_handle.state();
+      let mut editing_state = editing_state.lock();
+      editing_state.take_export_process();
+      editing_state.is_export_cancelled()
+    };
 
     match status {
-      Ok(exit_status) => {
-        if exit_status.success() {
-          log::info!("Successful export");
-          let _ = app_handle.emit(Events::ExportComplete.as_ref(), output_path.clone());
+      Ok(exit_status) if exit_status.success() => {
+        // The encoder also finishes when frames stop early
+        if let Err(e) = composited {
+          log::warn!("Export failed to composite frames: {e}");
+          emit_export_failed(
+            &app_handle,
+            &job,
+            ExportFailed {
+              reason: ExportFailureReason::Unknown,
+              exit_code: None,
+              stderr_tail: vec![e],
+            },
+          );
+          return;
+        }
 
-          if open_folder_after_export {
-            open_path_in_file_browser(output_path);
+        if pass == EncodePass::First {
+          if cancelled {
+            log::info!("Export cancelled");
+            remove_temporary_files(&job);
+            handle_cancellation(&job);
+          } else {
+            run_export(app_handle, job, encoder, EncodePass::Second);
           }
-        } else {
-          log::info!("Export cancelled {:?}", exit_status.code());
-          handle_cancellation(output_path, camera_path);
+          return;
+        }
+
+        if let Some(size_target) = &job.size_target {
+          let main_output = job.main_output();
+          let size_bytes = std::fs::metadata(&main_output.output_path)
+            .map(|metadata| metadata.len())
+            .unwrap_or_default();
+
+          if size_bytes > size_target.max_size_bytes && !size_target.retried {
+            let mut size_target = size_target.clone();
+            // Scale the video bitrate by the overshoot, with the usual overhead margin
+            size_target.video_bitrate = (size_target.video_bitrate as f64
+              * size_target.max_size_bytes as f64
+              / size_bytes as f64
+              * (1.0 - TARGET_SIZE_OVERHEAD)) as u64;
+            size_target.retried = true;
+            log::info!(
+              "Export of {size_bytes} bytes exceeds the target, re-encoding at {} bps",
+              size_target.video_bitrate
+            );
+
+            let mut job = job;
+            job.size_target = Some(size_target);
+            remove_output_files(&job);
+            run_export(app_handle, job, encoder, EncodePass::Second);
+            return;
+          }
+
+          let _ = app_handle.emit(
+            Events::ExportSizeReport.as_ref(),
+            ExportSizeReport {
+              max_size_bytes: size_target.max_size_bytes,
+              size_bytes,
+              height: main_output.preset.height,
+              frame_rate: main_output.preset.frame_rate,
+              video_bitrate: size_target.video_bitrate,
+            },
+          );
+        }
+
+        log::info!("Successful export");
+        write_sidecar_captions(&job);
+        write_chapters_text_file(&job);
+        remove_temporary_files(&job);
+
+        let output_paths: Vec<&PathBuf> = job
+          .outputs
+          .iter()
+          .map(|output| &output.output_path)
+          .collect();
+        let _ = app_handle.emit(Events::ExportOutputsComplete.as_ref(), output_paths);
+        let main_path = job.main_output().output_path.clone();
+        let _ = app_handle.emit(Events::ExportComplete.as_ref(), main_path.clone());
+
+        if job.open_folder_after_export {
+          open_path_in_file_browser(main_path);
+        }
+      }
+      Ok(_) if cancelled => {
+        log::info!("Export cancelled");
+        remove_temporary_files(&job);
+        handle_cancellation(&job);
+      }
+      Ok(exit_status) => {
+        let reason = ExportFailureReason::from_stderr(&stderr_tail);
+        log::warn!(
+          "Export failed ({reason}) with code {:?}: {}",
+          exit_status.code(),
+          stderr_tail.last().map(String::as_str).unwrap_or_default()
+        );
+
+        if reason == ExportFailureReason::HardwareEncoderUnavailable
+          && encoder == VideoEncoder::Hardware
+        {
+          log::info!("Retrying export with software encoder");
+          remove_output_files(&job);
+          run_export(app_handle, job, VideoEncoder::Software, pass);
+          return;
         }
+
+        emit_export_failed(
+          &app_handle,
+          &job,
+          ExportFailed {
+            reason,
+            exit_code: exit_status.code(),
+            stderr_tail,
+          },
+        );
+      }
+      Err(e) => {
+        log::warn!("Failed to export {e}");
+        emit_export_failed(
+          &app_handle,
+          &job,
+          ExportFailed {
+            reason: ExportFailureReason::Unknown,
+            exit_code: None,
+            stderr_tail,
+          },
+        );
       }
-      Err(e) => log::warn!("Failed to export {e}"),
     }
   });
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Render the composited frame at `position_ms` of the recording timeline.
+///
+/// Uses the export compositor, then scales and watermarks the frame like
+/// `configure_output_video` does for `preset`. Edits are not applied, they
+/// only drop or retime frames.
+pub fn render_frame(
+  source_folder_path: &Path,
+  project: &Project,
+  geometry: Option<&RecordingGeometry>,
+  captions: Option<&CaptionExport>,
+  preset: &ExportPreset,
+  position_ms: u64,
+  format: PreviewFrameFormat,
+) -> Result<RenderedFrame, String> {
+  let compositor = Compositor::new(source_folder_path, project, geometry)?;
+  let frame = compositor.render(source_folder_path, position_ms)?;
+  let (width, height) = (frame.width, frame.height);
+
+  let output_size = output_dimensions(preset, (width, height));
+  let watermark = preset
+    .watermark
+    .as_ref()
+    .filter(|_| preset.format.has_video());
+  if let Some(watermark) = watermark {
+    if !watermark.image_path.is_file() {
+      return Err(format!("Missing or unreadable {:?}", watermark.image_path));
+    }
+  }
+
+  let burn_in = match captions {
+    Some(CaptionExport {
+      segments,
+      mode: CaptionExportMode::BurnIn { style },
+    }) => {
+      // The frame is the start of the caption timeline
+      let position_ms = position_ms as i64;
+      let segments: Vec<CaptionSegment> = segments
+        .iter()
+        .filter(|segment| segment.end_ms > position_ms)
+        .map(|segment| CaptionSegment {
+          start_ms: (segment.start_ms - position_ms).max(0),
+          end_ms: segment.end_ms - position_ms,
+          text: segment.text.clone(),
+        })
+        .collect();
+
+      let path = std::env::temp_dir().join(format!("orbit-cursor-captions_{}.srt", Uuid::new_v4()));
+      write_subtitles(&segments, SubtitleFormat::Srt, &path).map_err(|e| e.to_string())?;
+      Some((path, style))
+    }
+    _ => None,
+  };
+
+  let unchanged = burn_in.is_none() && watermark.is_none() && output_size == (width, height);
+  let data = match (unchanged, format) {
+    (true, PreviewFrameFormat::Rgba) => Ok(frame.data),
+    (true, PreviewFrameFormat::Png) => encode_png(frame),
+    (false, format) => {
+      let mut graph = FilterGraph::new();
+      let composited = add_composited_input(&mut graph, (width, height));
+      let mut video = composited.video();
+      if let Some((path, style)) = &burn_in {
+        video = graph.chain(video, burn_in_filter(path, style), "captioned");
+      }
+      if output_size != (width, height) {
+        let (output_width, output_height) = output_size;
+        video = graph.chain(
+          video,
+          format!("scale={output_width}:{output_height}:flags=lanczos"),
+          "output_v",
+        );
+      }
+      if let Some(watermark) = watermark {
+        video = configure_watermark(&mut graph, video, watermark, output_size.0);
+      }
+      graph.map(video);
+
+      let mut child = FfmpegCommand::new();
+      graph.apply(&mut child).and_then(|_| {
+        child.args(["-frames:v", "1"]);
+        match format {
+          PreviewFrameFormat::Png => child.codec_video("png").format("image2pipe"),
+          PreviewFrameFormat::Rgba => child.pix_fmt("rgba").format("rawvideo"),
+        };
+        child.output("-");
+
+        read_frame(child, frame.data)
+      })
+    }
+  };
+
+  if let Some((path, _)) = &burn_in {
+    remove_file_if_exists(path);
+  }
+
+  Ok(RenderedFrame {
+    width: output_size.0,
+    height: output_size.1,
+    data: data?,
+  })
+}
+
+fn encode_png(frame: Frame) -> Result<Vec<u8>, String> {
+  let image = image::RgbaImage::from_raw(frame.width, frame.height, frame.data)
+    .ok_or("Frame size does not match its dimensions")?;
+
+  let mut data = Vec::new();
+  image
+    .write_to(&mut Cursor::new(&mut data), image::ImageFormat::Png)
+    .map_err(|e| e.to_string())?;
+
+  Ok(data)
+}
+
+/// Run `command` with `input` written to its stdin, returning its stdout
+fn read_frame(mut command: FfmpegCommand, input: Vec<u8>) -> Result<Vec<u8>, String> {
+  let mut ffmpeg = command.spawn().map_err(|e| e.to_string())?;
+  let stderr_handle =
+    capture_ffmpeg_stderr(ffmpeg.take_stderr().unwrap(), "[preview]".to_string(), 5);
+
+  let mut stdin = ffmpeg.take_stdin().unwrap();
+  let writer = std::thread::spawn(move || stdin.write_all(&input));
+
+  let mut data = Vec::new();
+  ffmpeg
+    .take_stdout()
+    .unwrap()
+    .read_to_end(&mut data)
+    .map_err(|e| e.to_string())?;
+
+  let status = ffmpeg.wait().map_err(|e| e.to_string())?;
+  let _ = writer.join();
+  let stderr_tail = stderr_handle.join().unwrap_or_default();
+  if !status.success() || data.is_empty() {
+    return Err(format!(
+      "Failed to render frame: {}",
+      stderr_tail.join("\n")
+    ));
+  }
+
+  Ok(data)
+}
+
+/// Remove partial output and notify the frontend of the failure
+fn emit_export_failed(app_handle: &AppHandle, job: &ExportJob, failure: ExportFailed) {
+  remove_temporary_files(job);
+  handle_cancellation(job);
+  let _ = app_handle.emit(Events::ExportFailed.as_ref(), failure);
+}
+
+/// Write captions next to each exported file, if requested, on its output timeline
+fn write_sidecar_captions(job: &ExportJob) {
+  let Some(CaptionExport {
+    segments,
+    mode: CaptionExportMode::Sidecar { format },
+  }) = &job.captions
+  else {
+    return;
+  };
+
+  for output in &job.outputs {
+    let segments = output_captions(segments, &job.project.edits, output.intro_ms());
+    let path = output.output_path.with_extension(format.extension());
+    if let Err(e) = write_subtitles(&segments, *format, &path) {
+      log::warn!("Failed to write captions {path:?}: {e}");
+    }
+  }
+}
+
+/// Write chapters as `[output]_chapters.txt` next to each exported file, if requested
+fn write_chapters_text_file(job: &ExportJob) {
+  if !job.project.chapters_text_file {
+    return;
+  }
+
+  for output in job
+    .outputs
+    .iter()
+    .filter(|output| !output.chapters.is_empty())
+  {
+    let stem = output
+      .output_path
+      .file_stem()
+      .unwrap_or_default()
+      .to_string_lossy();
+    let path = output
+      .output_path
+      .with_file_name(format!("{stem}_chapters.txt"));
+    if let Err(e) = std::fs::write(&path, chapters_to_text(&output.chapters)) {
+      log::warn!("Failed to write chapters {path:?}: {e}");
+    }
+  }
+}
+
+/// Captions on the output timeline, after an intro of `intro_ms`.
+///
+/// Cues are mapped through the edit list like chapters, cues inside a cut are
+/// dropped and cues overlapping one keep only their remaining part.
+fn output_captions(
+  segments: &[CaptionSegment],
+  edits: &[EditRange],
+  intro_ms: u64,
+) -> Vec<CaptionSegment> {
+  let timeline = timeline_segments(edits);
+  let to_output =
+    |position_ms: i64| (intro_ms + output_position_ms(&timeline, position_ms.max(0) as u64)) as i64;
+
+  segments
+    .iter()
+    .filter_map(|segment| {
+      let start_ms = to_output(segment.start_ms);
+      let end_ms = to_output(segment.end_ms);
+      (end_ms > start_ms).then(|| CaptionSegment {
+        start_ms,
+        end_ms,
+        text: segment.text.clone(),
+      })
+    })
+    .collect()
+}
+
+/// Write captions to a temporary SRT file, `None` if there are none
+fn write_temporary_subtitles(segments: &[CaptionSegment]) -> Option<PathBuf> {
+  if segments.is_empty() {
+    return None;
+  }
+
+  let path = std::env::temp_dir().join(format!("orbit-cursor-captions_{}.srt", Uuid::new_v4()));
+  match write_subtitles(segments, SubtitleFormat::Srt, &path) {
+    Ok(()) => Some(path),
+    Err(e) => {
+      log::warn!("Failed to write captions for export: {e}");
+      None
+    }
+  }
+}
+
+/// Write chapters to a temporary ffmetadata file, `None` if there are none
+fn write_chapters_file(chapters: &[Chapter]) -> Option<PathBuf> {
+  if chapters.is_empty() {
+    return None;
+  }
+
+  let path = std::env::temp_dir().join(format!("orbit-cursor-chapters_{}.txt", Uuid::new_v4()));
+  match write_ffmetadata_chapters(chapters, &path) {
+    Ok(()) => Some(path),
+    Err(e) => {
+      log::warn!("Failed to write chapters for export: {e}");
+      None
+    }
+  }
+}
+
+fn remove_temporary_files(job: &ExportJob) {
+  let output_files = job
+    .outputs
+    .iter()
+    .flat_map(|output| [&output.chapters_file, &output.subtitles_file]);
+  for file in std::iter::once(&job.subtitles_file)
+    .chain(output_files)
+    .flatten()
+  {
+    remove_file_if_exists(file);
+  }
+
+  if let Some(size_target) = &job.size_target {
+    remove_pass_logs(&size_target.passlog_file);
+  }
+}
+
+/// Remove the statistics files written by the first pass, named after `prefix`.
+///
+/// Covers every encoder's files, e.g. `.log` and `.log.cutree` of x265 and
+/// `-0.log` and `-0.log.mbtree` of x264.
+fn remove_pass_logs(prefix: &Path) {
+  let (Some(dir), Some(prefix)) = (prefix.parent(), prefix.file_name()) else {
+    return;
+  };
+  let prefix = prefix.to_string_lossy();
+
+  if let Ok(entries) = std::fs::read_dir(dir) {
+    for entry in entries.flatten() {
+      if entry
+        .file_name()
+        .to_string_lossy()
+        .starts_with(prefix.as_ref())
+      {
+        remove_file_if_exists(&entry.path());
+      }
+    }
+  }
+}
+
+/// Bitrates, and the preset lowered in height or frame rate if needed, for an
+/// export of at most `max_size_bytes`
+fn plan_size_target(
+  job: &ExportJob,
+  max_size_bytes: u64,
+) -> Result<(SizeTarget, ExportPreset), ExportFailed> {
+  let failure = |reason, message: String| ExportFailed {
+    reason,
+    exit_code: None,
+    stderr_tail: vec![message],
+  };
+
+  let main_output = job.main_output();
+  let format = main_output.preset.format;
+  if !format.supports_target_size() {
+    return Err(failure(
+      ExportFailureReason::Unknown,
+      format!("Exporting {format:?} to a target size is not supported"),
+    ));
+  }
+
+  let screen_path = job.source_folder_path.join(RecordingFile::Screen.as_ref());
+  let length_ms = shortest_video_length(vec![screen_path.clone()]).unwrap_or_default();
+  let duration_ms = output_position_ms(&timeline_segments(&job.project.edits), length_ms)
+    + [&main_output.intro, &main_output.outro]
+      .into_iter()
+      .flatten()
+      .map(|clip| clip.duration_ms)
+      .sum::<u64>();
+  let (width, height) = video_dimensions(&screen_path).unwrap_or_default();
+  if duration_ms == 0 || height == 0 {
+    return Err(failure(
+      ExportFailureReason::MissingInput,
+      "Failed to probe the recording duration and size".to_string(),
+    ));
+  }
+
+  let streams = check_recording_files(&job.source_folder_path, &job.project.audio);
+  let audio_tracks = match (streams.has_microphone, streams.has_system_audio) {
+    (true, true) if job.project.separate_audio_tracks => 2,
+    (false, false) => 0,
+    _ => 1,
+  };
+
+  let total_bitrate = (max_size_bytes as f64 * 8.0 * (1.0 - TARGET_SIZE_OVERHEAD)
+    / (duration_ms as f64 / 1000.0)) as u64;
+  let audio_bitrate = if total_bitrate < 1_000_000 {
+    64_000
+  } else {
+    128_000
+  };
+  let video_bitrate = total_bitrate.saturating_sub(audio_bitrate * audio_tracks);
+  if video_bitrate < MIN_TARGET_VIDEO_BITRATE {
+    return Err(failure(
+      ExportFailureReason::TargetSizeUnreachable,
+      format!("{max_size_bytes} bytes is too small for {duration_ms} ms of video"),
+    ));
+  }
+
+  // Below this the picture breaks up, lower the frame rate then the resolution instead
+  let min_bits_per_pixel = match format {
+    ExportFormat::Mp4H264 => 0.02,
+    _ => 0.014,
+  };
+
+  let preset_height = main_output.preset.height.unwrap_or(height).min(height);
+  let preset_rate = main_output
+    .preset
+    .frame_rate
+    .unwrap_or(COMPOSITOR_FRAME_RATE)
+    .min(COMPOSITOR_FRAME_RATE);
+
+  let mut candidates = vec![
+    (preset_height, preset_rate),
+    (preset_height, preset_rate.min(30)),
+  ];
+  candidates.extend(
+    TARGET_SIZE_HEIGHTS
+      .iter()
+      .filter(|h| **h < preset_height)
+      .map(|h| (*h, preset_rate.min(30))),
+  );
+  let lowest_height = candidates.last().map_or(preset_height, |(h, _)| *h);
+  candidates.push((lowest_height, preset_rate.min(15)));
+  candidates.dedup();
+
+  let fits = |(candidate_height, frame_rate): (u32, u32)| {
+    let candidate_width = width as f64 * candidate_height as f64 / height as f64;
+    video_bitrate as f64 / (candidate_width * candidate_height as f64 * frame_rate as f64)
+      >= min_bits_per_pixel
+  };
+  let (target_height, frame_rate) = candidates
+    .iter()
+    .copied()
+    .find(|candidate| fits(*candidate))
+    .unwrap_or(candidates[candidates.len() - 1]);
+
+  let mut preset = main_output.preset.clone();
+  preset.height = (target_height < height).then_some(target_height);
+  preset.frame_rate = (frame_rate < COMPOSITOR_FRAME_RATE).then_some(frame_rate);
+
+  Ok((
+    SizeTarget {
+      max_size_bytes,
+      duration_ms,
+      video_bitrate,
+      audio_bitrate,
+      passlog_file: std::env::temp_dir().join(format!("orbit-cursor-passlog_{}", Uuid::new_v4())),
+      retried: false,
+    },
+    preset,
+  ))
+}
+
+/// Convert recording markers into chapters on the edited output timeline,
+/// after an intro and before an outro of `(intro_ms, outro_ms)`
+fn export_chapters(
+  source_folder_path: &Path,
+  markers: &[Marker],
+  edits: &[EditRange],
+  (intro_ms, outro_ms): (u64, u64),
+) -> Vec<Chapter> {
+  let segments = timeline_segments(edits);
+  let length_ms =
+    shortest_video_length(vec![source_folder_path.join(RecordingFile::Screen.as_ref())])
+      .unwrap_or_default();
+
+  let markers: Vec<(u64, String)> = markers
+    .iter()
+    .map(|marker| {
+      (
+        intro_ms + output_position_ms(&segments, marker.position_ms.max(0) as u64),
+        marker.name.clone(),
+      )
+    })
+    .collect();
+
+  markers_to_chapters(
+    &markers,
+    intro_ms + output_position_ms(&segments, length_ms) + outro_ms,
+  )
+}
+
+/// Map a position on the recording timeline to the edited output timeline.
+///
+/// Positions inside a cut map to the start of the following kept segment.
+fn output_position_ms(segments: &[TimelineSegment], position_ms: u64) -> u64 {
+  let mut output_ms = 0.0;
+
+  for segment in segments {
+    if position_ms < segment.start_ms {
+      break;
+    }
+
+    let end_ms = segment
+      .end_ms
+      .map_or(position_ms, |end| end.min(position_ms));
+    output_ms += (end_ms - segment.start_ms) as f64 / segment.speed;
+  }
+
+  output_ms.round() as u64
+}
+
+#[derive(Debug, Clone, Copy, Default)]
 struct RecordingFilePresence {
   pub has_system_audio: bool,
   pub has_microphone: bool,
-  pub has_camera: bool,
 }
-fn check_recording_files(folder: &Path) -> RecordingFilePresence {
+
+impl RecordingFilePresence {
+  fn has_audio(&self) -> bool {
+    self.has_system_audio || self.has_microphone
+  }
+}
+
+/// Recorded audio to export, muted tracks are left out
+fn check_recording_files(folder: &Path, audio: &AudioSettings) -> RecordingFilePresence {
   RecordingFilePresence {
-    has_system_audio: folder.join(RecordingFile::SystemAudio.to_string()).exists(),
-    has_microphone: folder.join(RecordingFile::Microphone.to_string()).exists(),
-    has_camera: folder.join(RecordingFile::Camera.to_string()).exists(),
+    has_system_audio: audio.system_audio.is_audible()
+      && RecordingFile::SystemAudio.resolve(folder).exists(),
+    has_microphone: audio.microphone.is_audible()
+      && RecordingFile::Microphone.resolve(folder).exists(),
   }
 }
 
-fn configure_input_streams(
-  child: &mut FfmpegCommand,
+/// Check the watermark exists and probe the intro and outro of a video preset
+fn probe_preset_inputs(
+  preset: &ExportPreset,
+) -> Result<(Option<Clip>, Option<Clip>), ExportFailed> {
+  if !preset.format.has_video() {
+    return Ok((None, None));
+  }
+
+  let unavailable = |path: &Path| ExportFailed {
+    reason: ExportFailureReason::MissingInput,
+    exit_code: None,
+    stderr_tail: vec![format!("Missing or unreadable {path:?}")],
+  };
+
+  if let Some(watermark) = &preset.watermark {
+    if !watermark.image_path.is_file() {
+      return Err(unavailable(&watermark.image_path));
+    }
+  }
+
+  let probe = |path: &Option<PathBuf>| -> Result<Option<Clip>, ExportFailed> {
+    let Some(path) = path else {
+      return Ok(None);
+    };
+
+    let duration_ms = shortest_video_length(vec![path.clone()]).filter(|ms| *ms > 0);
+    match (duration_ms, video_dimensions(path)) {
+      (Some(duration_ms), Some(_)) => Ok(Some(Clip {
+        path: path.clone(),
+        duration_ms,
+        has_audio: has_audio_stream(path),
+      })),
+      _ => Err(unavailable(path)),
+    }
+  };
+
+  Ok((probe(&preset.intro_path)?, probe(&preset.outro_path)?))
+}
+
+/// Audio files added as graph inputs by `add_recording_inputs`
+struct RecordingInputs {
+  microphone: Option<InputId>,
+  system_audio: Option<InputId>,
+}
+
+fn add_recording_inputs(
+  graph: &mut FilterGraph,
   source_folder_path: &Path,
   available_streams: &RecordingFilePresence,
-  separate_camera_file: bool,
-) {
-  child.input(
-    source_folder_path
-      .join(RecordingFile::Screen.as_ref())
-      .to_string_lossy(),
+) -> RecordingInputs {
+  let mut input = |path: PathBuf| graph.input(path.to_string_lossy());
+
+  RecordingInputs {
+    microphone: available_streams
+      .has_microphone
+      .then(|| input(RecordingFile::Microphone.resolve(source_folder_path))),
+    system_audio: available_streams
+      .has_system_audio
+      .then(|| input(RecordingFile::SystemAudio.resolve(source_folder_path))),
+  }
+}
+
+/// Add raw RGBA frames from the compositor, read from stdin
+fn add_composited_input(graph: &mut FilterGraph, (width, height): (u32, u32)) -> InputId {
+  graph.input_with_options(
+    "-",
+    [
+      "-f",
+      "rawvideo",
+      "-pix_fmt",
+      "rgba",
+      "-s",
+      &format!("{width}x{height}"),
+      "-r",
+      &COMPOSITOR_FRAME_RATE.to_string(),
+    ]
+    .map(String::from)
+    .to_vec(),
+  )
+}
+
+fn configure_progress_options(child: &mut FfmpegCommand) {
+  child.args(["-progress", "pipe:1"]);
+  child.arg("-nostats");
+}
+
+/// `count` copies of `pad`, split with `split_filter` if more than one
+fn copies(
+  graph: &mut FilterGraph,
+  pad: Pad,
+  split_filter: &str,
+  name: &str,
+  count: usize,
+) -> Vec<Pad> {
+  if count == 1 {
+    vec![pad]
+  } else {
+    graph.split(pad, split_filter, name, count)
+  }
+}
+
+/// Return the audio tracks to map, with volume applied.
+///
+/// Microphone and system audio are mixed into one track unless kept separate.
+fn configure_audio_tracks(
+  graph: &mut FilterGraph,
+  inputs: &RecordingInputs,
+  audio: &AudioSettings,
+  separate_audio_tracks: bool,
+) -> Vec<Pad> {
+  let mut tracks = Vec::new();
+
+  for (input, settings, label) in [
+    (inputs.microphone, &audio.microphone, "mic_volume"),
+    (inputs.system_audio, &audio.system_audio, "sys_volume"),
+  ] {
+    let Some(input) = input else {
+      continue;
+    };
+
+    tracks.push(if settings.volume == 1.0 {
+      input.audio()
+    } else {
+      graph.chain(input.audio(), format!("volume={}", settings.volume), label)
+    });
+  }
+
+  if tracks.len() == 2 && !separate_audio_tracks {
+    graph.filter(tracks, "amix=inputs=2", &["aout"])
+  } else {
+    tracks
+  }
+}
+
+/// Apply the edit list, returning the edited video and audio pads.
+///
+/// Every kept segment is trimmed out of each stream, retimed if sped up, and
+/// concatenated back together.
+fn configure_edit_list(
+  graph: &mut FilterGraph,
+  edits: &[EditRange],
+  video: Option<Pad>,
+  audio: Vec<Pad>,
+) -> (Option<Pad>, Vec<Pad>) {
+  if edits.is_empty() {
+    return (video, audio);
+  }
+
+  let segments = timeline_segments(edits);
+  let segment_count = segments.len();
+  let track_count = audio.len();
+
+  // Concat expects the streams of each segment together, video first
+  let mut concat_inputs: Vec<Vec<Pad>> = vec![Vec::new(); segment_count];
+
+  let has_video = video.is_some();
+  if let Some(video) = video {
+    let parts = graph.split(video, "split", "edit_v", segment_count);
+    for (i, (part, segment)) in parts.into_iter().zip(&segments).enumerate() {
+      concat_inputs[i].push(graph.chain(
+        part,
+        format!("trim={},{}", segment.trim_args(), segment.setpts()),
+        &format!("edit_v{i}_out"),
+      ));
+    }
+  }
+
+  for (track, pad) in audio.into_iter().enumerate() {
+    let parts = graph.split(pad, "asplit", &format!("edit_a{track}_"), segment_count);
+    for (i, (part, segment)) in parts.into_iter().zip(&segments).enumerate() {
+      concat_inputs[i].push(graph.chain(
+        part,
+        format!("atrim={},{}", segment.trim_args(), segment.asetpts()),
+        &format!("edit_a{track}_{i}_out"),
+      ));
+    }
+  }
+
+  let outputs: Vec<String> = has_video
+    .then(|| "edited_v".to_string())
+    .into_iter()
+    .chain((0..track_count).map(|track| format!("edited_a{track}")))
+    .collect();
+  let outputs: Vec<&str> = outputs.iter().map(String::as_str).collect();
+
+  let mut edited = graph.filter(
+    concat_inputs.into_iter().flatten(),
+    format!(
+      "concat=n={segment_count}:v={}:a={track_count}",
+      u8::from(has_video)
+    ),
+    &outputs,
   );
+  let video = has_video.then(|| edited.remove(0));
 
-  // Only if burned in do we need it as an input stream
-  if available_streams.has_camera && !separate_camera_file {
-    child.input(
-      source_folder_path
-        .join(RecordingFile::Camera.as_ref())
-        .to_string_lossy(),
-    );
+  (video, edited)
+}
+
+/// Kept part of the recording timeline after applying the edit list
+#[derive(Debug, Clone, Copy)]
+struct TimelineSegment {
+  start_ms: u64,
+  // `None` runs until the end of the recording
+  end_ms: Option<u64>,
+  speed: f64,
+}
+
+impl TimelineSegment {
+  fn trim_args(&self) -> String {
+    trim_args(self.start_ms, self.end_ms)
   }
 
-  if available_streams.has_microphone {
-    child.input(
-      source_folder_path
-        .join(RecordingFile::Microphone.as_ref())
-        .to_string_lossy(),
-    );
+  fn setpts(&self) -> String {
+    if self.speed == 1.0 {
+      "setpts=PTS-STARTPTS".to_string()
+    } else {
+      format!("setpts=(PTS-STARTPTS)/{}", self.speed)
+    }
   }
 
-  if available_streams.has_system_audio {
-    child.input(
-      source_folder_path
-        .join(RecordingFile::SystemAudio.as_ref())
-        .to_string_lossy(),
-    );
+  fn asetpts(&self) -> String {
+    if self.speed == 1.0 {
+      "asetpts=PTS-STARTPTS".to_string()
+    } else {
+      format!("asetpts=PTS-STARTPTS,atempo={}", self.speed)
+    }
   }
 }
 
-fn configure_progress_options(child: &mut FfmpegCommand) {
-  child.args(["-progress", "pipe:1"]);
-  child.arg("-nostats");
+/// `trim`/`atrim` options selecting `[start_ms, end_ms)`
+fn trim_args(start_ms: u64, end_ms: Option<u64>) -> String {
+  let start = start_ms as f64 / 1000.0;
+  match end_ms {
+    Some(end_ms) => format!("start={start:.3}:end={:.3}", end_ms as f64 / 1000.0),
+    None => format!("start={start:.3}"),
+  }
 }
 
-/// Return filter_complex string for video tracks
-fn configure_video_tracks(
-  ffmpeg: &mut FfmpegCommand,
-  has_camera: bool,
-  separate_camera_file: bool,
-) -> Option<String> {
-  // Burned in Camera
-  if !separate_camera_file && has_camera {
-    ffmpeg.map("[outv]");
+/// Convert the edit list into the ordered segments of the timeline to keep.
+///
+/// Overlapping edits are resolved in favour of the earlier one.
+fn timeline_segments(edits: &[EditRange]) -> Vec<TimelineSegment> {
+  let mut edits = edits.to_vec();
+  edits.sort_by_key(|e| e.start_ms);
+
+  let mut segments = Vec::new();
+  let mut cursor = 0;
+
+  for edit in edits {
+    let start_ms = edit.start_ms.max(cursor);
+    if edit.end_ms <= start_ms {
+      continue;
+    }
+
+    if start_ms > cursor {
+      segments.push(TimelineSegment {
+        start_ms: cursor,
+        end_ms: Some(start_ms),
+        speed: 1.0,
+      });
+    }
+
+    if let EditAction::SpeedUp { factor } = edit.action {
+      segments.push(TimelineSegment {
+        start_ms,
+        end_ms: Some(edit.end_ms),
+        // Limits of `atempo`
+        speed: factor.clamp(MIN_SPEED_FACTOR, MAX_SPEED_FACTOR),
+      });
+    }
+
+    cursor = edit.end_ms;
+  }
+
+  segments.push(TimelineSegment {
+    start_ms: cursor,
+    end_ms: None,
+    speed: 1.0,
+  });
+
+  segments
+}
+
+/// Scale and retime the video for the preset, add its watermark, intro and
+/// outro, and generate a palette for GIF. Returns the video and audio tracks.
+fn configure_output_video(
+  graph: &mut FilterGraph,
+  video: Pad,
+  audio: Vec<Pad>,
+  output: &ExportOutput,
+  source_size: (u32, u32),
+) -> (Pad, Vec<Pad>) {
+  let preset = &output.preset;
+  let is_gif = preset.format == ExportFormat::Gif;
+  let (width, height) = output_dimensions(preset, source_size);
+  let frame_rate = if is_gif {
     Some(
-      "[1:v]scale=320:-1[camera_scaled];[0:v][camera_scaled]overlay=W-w-10:H-h-10[outv]"
-        .to_string(),
+      preset
+        .frame_rate
+        .unwrap_or(GIF_FRAME_RATE.0)
+        .min(GIF_FRAME_RATE.1),
     )
   } else {
-    ffmpeg.map("0:v");
-    None
+    preset.frame_rate
+  };
+
+  let frame_rate = frame_rate.filter(|rate| *rate > 0 && *rate < COMPOSITOR_FRAME_RATE);
+
+  let mut filters = Vec::new();
+  if let Some(frame_rate) = frame_rate {
+    filters.push(format!("fps={frame_rate}"));
   }
+  if (width, height) != source_size {
+    filters.push(format!("scale={width}:{height}:flags=lanczos"));
+  }
+
+  let video = if filters.is_empty() {
+    video
+  } else {
+    graph.chain(video, filters.join(","), "output_v")
+  };
+
+  let video = match &preset.watermark {
+    Some(watermark) => configure_watermark(graph, video, watermark, width),
+    None => video,
+  };
+
+  let (video, audio) = configure_clips(
+    graph,
+    video,
+    audio,
+    (output.intro.as_ref(), output.outro.as_ref()),
+    (width, height),
+    frame_rate.unwrap_or(COMPOSITOR_FRAME_RATE),
+  );
+
+  if !is_gif {
+    return (video, audio);
+  }
+
+  let (max_colors, dither) = match preset.quality {
+    ExportQuality::Low => (64, "bayer:bayer_scale=3"),
+    ExportQuality::Medium => (128, "bayer:bayer_scale=2"),
+    ExportQuality::High => (256, "sierra2_4a"),
+  };
+
+  let mut parts = graph.split(video, "split", "gif_v", 2);
+  let palette = graph.chain(
+    parts.remove(0),
+    format!("palettegen=max_colors={max_colors}:stats_mode=diff"),
+    "palette",
+  );
+  let video = graph
+    .filter(
+      [parts.remove(0), palette],
+      format!("paletteuse=dither={dither}:diff_mode=rectangle"),
+      &["gif"],
+    )
+    .remove(0);
+
+  (video, audio)
 }
 
-/// Return filter_complex string for audio tracks
-fn configure_audio_tracks(
-  ffmpeg: &mut FfmpegCommand,
-  available_streams: &RecordingFilePresence,
-  separate_audio_tracks: bool,
-  separate_camera_file: bool,
-) -> Option<String> {
-  let &RecordingFilePresence {
-    has_camera,
-    has_microphone,
-    has_system_audio,
-  } = available_streams;
-
-  let camera_input_offset = if has_camera && !separate_camera_file {
-    1
+/// Overlay the watermark image, sized relative to the output `width`
+fn configure_watermark(
+  graph: &mut FilterGraph,
+  video: Pad,
+  watermark: &Watermark,
+  width: u32,
+) -> Pad {
+  let image_width = ((width as f64 * watermark.scale.clamp(0.01, 1.0)).round() as u32).max(1);
+  // A single image, `overlay` holds it once it ends
+  let image = graph.input(watermark.image_path.to_string_lossy());
+  let image = graph.chain(
+    image.video(),
+    format!(
+      "scale={image_width}:-1,format=rgba,colorchannelmixer=aa={:.3}",
+      watermark.opacity.clamp(0.0, 1.0)
+    ),
+    "watermark",
+  );
+
+  let margin = watermark.margin;
+  let (x, y) = match watermark.position {
+    WatermarkPosition::TopLeft => (format!("{margin}"), format!("{margin}")),
+    WatermarkPosition::TopRight => (format!("W-w-{margin}"), format!("{margin}")),
+    WatermarkPosition::BottomLeft => (format!("{margin}"), format!("H-h-{margin}")),
+    WatermarkPosition::BottomRight => (format!("W-w-{margin}"), format!("H-h-{margin}")),
+    WatermarkPosition::Center => ("(W-w)/2".to_string(), "(H-h)/2".to_string()),
+  };
+
+  graph
+    .filter(
+      [video, image],
+      format!("overlay=x={x}:y={y}"),
+      &["watermarked"],
+    )
+    .remove(0)
+}
+
+/// Join the intro and outro around the video, converted to its size, frame
+/// rate and audio tracks
+fn configure_clips(
+  graph: &mut FilterGraph,
+  video: Pad,
+  audio: Vec<Pad>,
+  (intro, outro): (Option<&Clip>, Option<&Clip>),
+  (width, height): (u32, u32),
+  frame_rate: u32,
+) -> (Pad, Vec<Pad>) {
+  if intro.is_none() && outro.is_none() {
+    return (video, audio);
+  }
+
+  let track_count = audio.len();
+  let video_filter = format!(
+    "scale={width}:{height}:force_original_aspect_ratio=decrease,\
+     pad={width}:{height}:(ow-iw)/2:(oh-ih)/2,setsar=1,fps={frame_rate}"
+  );
+
+  // Concat expects the streams of each segment together, video first
+  let mut concat_inputs = Vec::new();
+  if let Some(intro) = intro {
+    concat_inputs.extend(clip_streams(
+      graph,
+      intro,
+      "intro",
+      &video_filter,
+      track_count,
+    ));
+  }
+
+  concat_inputs.push(graph.chain(video, "setsar=1", "recording_v"));
+  for (track, pad) in audio.into_iter().enumerate() {
+    concat_inputs.push(graph.chain(pad, JOINED_AUDIO_FORMAT, &format!("recording_a{track}")));
+  }
+
+  if let Some(outro) = outro {
+    concat_inputs.extend(clip_streams(
+      graph,
+      outro,
+      "outro",
+      &video_filter,
+      track_count,
+    ));
+  }
+
+  let segment_count = 1 + usize::from(intro.is_some()) + usize::from(outro.is_some());
+  let outputs: Vec<String> = std::iter::once("joined_v".to_string())
+    .chain((0..track_count).map(|track| format!("joined_a{track}")))
+    .collect();
+  let outputs: Vec<&str> = outputs.iter().map(String::as_str).collect();
+
+  let mut joined = graph.filter(
+    concat_inputs,
+    format!("concat=n={segment_count}:v=1:a={track_count}"),
+    &outputs,
+  );
+  let video = joined.remove(0);
+
+  (video, joined)
+}
+
+/// Video and `track_count` audio streams of an intro or outro, ready to concat
+fn clip_streams(
+  graph: &mut FilterGraph,
+  clip: &Clip,
+  name: &str,
+  video_filter: &str,
+  track_count: usize,
+) -> Vec<Pad> {
+  let input = graph.input(clip.path.to_string_lossy());
+  let mut streams = vec![graph.chain(input.video(), video_filter, &format!("{name}_v"))];
+  if track_count == 0 {
+    return streams;
+  }
+
+  // Audio as long as the video keeps the following segment in sync, silence if there is none
+  let duration = format!("{:.3}", clip.duration_ms as f64 / 1000.0);
+  let audio = if clip.has_audio {
+    graph.chain(
+      input.audio(),
+      format!("{JOINED_AUDIO_FORMAT},apad=whole_dur={duration},atrim=duration={duration}"),
+      &format!("{name}_a"),
+    )
   } else {
-    0
+    graph
+      .filter(
+        std::iter::empty(),
+        format!("anullsrc=r=48000:cl=stereo,atrim=duration={duration},{JOINED_AUDIO_FORMAT}"),
+        &[&format!("{name}_a")],
+      )
+      .remove(0)
   };
 
-  let mic_input_index = camera_input_offset + 1;
-  let sys_input_index = if has_microphone {
-    mic_input_index + 1
+  if track_count == 1 {
+    streams.push(audio);
+  } else {
+    streams.extend(graph.split(audio, "asplit", &format!("{name}_tracks"), track_count));
+  }
+
+  streams
+}
+
+/// Size of the exported video, scaled down to the preset height keeping the aspect ratio
+fn output_dimensions(preset: &ExportPreset, (width, height): (u32, u32)) -> (u32, u32) {
+  let target_height = if preset.format == ExportFormat::Gif {
+    preset.height.unwrap_or(GIF_HEIGHT.0).min(GIF_HEIGHT.1)
   } else {
-    mic_input_index
+    preset.height.unwrap_or(height)
   };
 
-  if separate_audio_tracks {
-    let mut audio_index = mic_input_index;
+  if target_height == 0 || target_height >= height {
+    return (width, height);
+  }
+
+  // Even dimensions for yuv420p
+  let even = |size: f64| ((size / 2.0).round() as u32 * 2).max(2);
+  (
+    even(width as f64 * target_height as f64 / height as f64),
+    even(target_height as f64),
+  )
+}
+
+/// Codec, rate control and metadata options for the preset, then the output path
+fn configure_output_options(
+  ffmpeg: &mut FfmpegCommand,
+  job: &ExportJob,
+  output: &ExportOutput,
+  encoder: VideoEncoder,
+) {
+  let preset = &output.preset;
+  ffmpeg.arg("-shortest");
 
-    if has_microphone {
-      ffmpeg.map(format!("{audio_index}:a"));
-      audio_index += 1;
+  let quality = preset.quality;
+  let bitrate = quality.select("6000k", "9000k", "12000k");
+
+  match preset.format {
+    ExportFormat::Mp4H264 => {
+      match encoder {
+        VideoEncoder::Hardware => {
+          #[cfg(target_os = "macos")]
+          {
+            ffmpeg.codec_video("h264_videotoolbox");
+            ffmpeg.args(["-b:v", bitrate, "-profile:v", "high"]);
+          }
+
+          #[cfg(target_os = "windows")]
+          ffmpeg
+            .codec_video(get_hardware_encoder())
+            .crf(quality.select(28, 23, 20))
+            .args(["-b:v", bitrate]);
+        }
+        VideoEncoder::Software => {
+          ffmpeg
+            .codec_video("libx264")
+            .crf(quality.select(28, 23, 20))
+            .args(["-maxrate", bitrate, "-bufsize", "24000k"]);
+        }
+      }
+
+      ffmpeg.pix_fmt("yuv420p");
+      ffmpeg.codec_audio("aac");
+      ffmpeg.args(["-b:a", "192k"]);
     }
+    ExportFormat::Mp4Hevc => {
+      match encoder {
+        #[cfg(target_os = "macos")]
+        VideoEncoder::Hardware => {
+          ffmpeg.codec_video("hevc_videotoolbox");
+          ffmpeg.args(["-b:v", bitrate]);
+        }
+        _ => {
+          ffmpeg
+            .codec_video("libx265")
+            .crf(quality.select(30, 26, 22));
+        }
+      }
 
-    if has_system_audio {
-      ffmpeg.map(format!("{audio_index}:a"));
+      // Plays in QuickTime and Safari
+      ffmpeg.args(["-tag:v", "hvc1"]);
+      ffmpeg.pix_fmt("yuv420p");
+      ffmpeg.codec_audio("aac");
+      ffmpeg.args(["-b:a", "192k"]);
+    }
+    ExportFormat::WebmVp9 => {
+      ffmpeg
+        .codec_video("libvpx-vp9")
+        .crf(quality.select(40, 33, 26))
+        .args(["-b:v", "0", "-row-mt", "1"]);
+      ffmpeg.pix_fmt("yuv420p");
+      ffmpeg.codec_audio("libopus");
+      ffmpeg.args(["-b:a", "128k"]);
+    }
+    ExportFormat::WebmAv1 => {
+      ffmpeg
+        .codec_video("libsvtav1")
+        .crf(quality.select(40, 34, 28))
+        .args(["-preset", "8"]);
+      ffmpeg.pix_fmt("yuv420p");
+      ffmpeg.codec_audio("libopus");
+      ffmpeg.args(["-b:a", "128k"]);
+    }
+    ExportFormat::ProresMov => {
+      // LT, standard and HQ profiles
+      ffmpeg
+        .codec_video("prores_ks")
+        .args(["-profile:v", quality.select("1", "2", "3")]);
+      ffmpeg.pix_fmt("yuv422p10le");
+      ffmpeg.codec_audio("pcm_s16le");
+    }
+    ExportFormat::Gif => {
+      ffmpeg.args(["-loop", "0"]);
+    }
+    ExportFormat::M4a => {
+      ffmpeg.codec_audio("aac");
+      ffmpeg.args(["-b:a", quality.select("128k", "192k", "256k")]);
     }
+    ExportFormat::Mp3 => {
+      ffmpeg.codec_audio("libmp3lame");
+      ffmpeg.args(["-q:a", quality.select("5", "2", "0")]);
+    }
+    ExportFormat::Wav => {
+      ffmpeg.codec_audio("pcm_s16le");
+    }
+  }
+
+  configure_metadata(ffmpeg, &job.metadata, preset.format);
+  ffmpeg.output(output.output_path.to_string_lossy());
+}
 
-    None
-  } else if has_microphone && has_system_audio {
-    ffmpeg.map("[aout]");
+/// Title, creation time, encoder and custom tags of the output container
+fn configure_metadata(ffmpeg: &mut FfmpegCommand, metadata: &ExportMetadata, format: ExportFormat) {
+  if !format.supports_metadata() {
+    return;
+  }
 
-    Some(format!("[{mic_input_index}:a][{sys_input_index}:a]amix=inputs=2[aout]").to_string())
-  } else if has_microphone || has_system_audio {
-    let audio_index = if has_microphone {
-      mic_input_index
-    } else {
-      sys_input_index
-    };
+  let creation_time = metadata.creation_time.and_then(|created_at| {
+    created_at
+      .to_offset(UtcOffset::UTC)
+      .format(&Rfc3339)
+      .map_err(|e| log::warn!("Failed to format creation time {created_at}: {e}"))
+      .ok()
+  });
 
-    ffmpeg.map(format!("{audio_index}:a"));
+  let entries = [
+    ("title", metadata.title.clone()),
+    ("creation_time", creation_time),
+    // libavformat replaces any `encoder` tag with its own version
+    (
+      "comment",
+      Some(format!("Exported with {}", metadata.encoder)),
+    ),
+  ]
+  .into_iter()
+  .filter_map(|(key, value)| value.map(|value| (key.to_string(), value)))
+  .chain(metadata.tags.clone())
+  // ffmpeg splits the entry at the first `=`
+  .filter(|(key, value)| !key.trim().is_empty() && !key.contains('=') && !value.is_empty());
 
-    None
-  } else {
-    None
+  for (key, value) in entries {
+    ffmpeg.args(["-metadata", &format!("{}={value}", key.trim())]);
+  }
+
+  // Otherwise the MP4 muxer drops keys it has no atom for
+  if format.is_mov_family() && !metadata.tags.is_empty() {
+    ffmpeg.args(["-movflags", "use_metadata_tags"]);
   }
 }
 
-fn configure_output_options(ffmpeg: &mut FfmpegCommand, destination_file_path: &Path) {
+/// Output options encoding `pass` of a two pass export to `size_target`
+fn configure_target_size_options(
+  ffmpeg: &mut FfmpegCommand,
+  job: &ExportJob,
+  output: &ExportOutput,
+  size_target: &SizeTarget,
+  pass: EncodePass,
+) {
+  let preset = &output.preset;
   ffmpeg.arg("-shortest");
 
-  #[cfg(target_os = "macos")]
-  {
-    ffmpeg.codec_video("h264_videotoolbox");
-    ffmpeg.args(["-b:v", "12000k", "-profile:v", "high"]);
-  }
+  let video_bitrate = size_target.video_bitrate.to_string();
+  let audio_bitrate = size_target.audio_bitrate.to_string();
+  let pass_number = if pass == EncodePass::First { "1" } else { "2" };
+  let passlog_file = size_target.passlog_file.to_string_lossy();
 
-  #[cfg(target_os = "windows")]
-  ffmpeg
-    .codec_video(get_hardware_encoder())
-    .crf(20)
-    .args(["-b:v", "12000k"]);
+  match preset.format {
+    ExportFormat::Mp4Hevc => {
+      // x265-params splits on `:`, which breaks on a Windows drive letter, so
+      // ffmpeg runs in the statistics directory and gets a bare file name
+      let passlog_path = &size_target.passlog_file;
+      if let Some(passlog_dir) = passlog_path.parent() {
+        ffmpeg.as_inner_mut().current_dir(passlog_dir);
+      }
+      let passlog_name = passlog_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+
+      ffmpeg.codec_video("libx265").args([
+        "-b:v",
+        &video_bitrate,
+        "-x265-params",
+        &format!("pass={pass_number}:stats={passlog_name}.log"),
+        "-tag:v",
+        "hvc1",
+      ]);
+      ffmpeg.codec_audio("aac");
+    }
+    ExportFormat::WebmVp9 => {
+      ffmpeg.codec_video("libvpx-vp9").args([
+        "-b:v",
+        &video_bitrate,
+        "-row-mt",
+        "1",
+        "-pass",
+        pass_number,
+        "-passlogfile",
+        &passlog_file,
+      ]);
+      ffmpeg.codec_audio("libopus");
+    }
+    _ => {
+      ffmpeg.codec_video("libx264").args([
+        "-b:v",
+        &video_bitrate,
+        "-pass",
+        pass_number,
+        "-passlogfile",
+        &passlog_file,
+      ]);
+      ffmpeg.codec_audio("aac");
+    }
+  }
 
   ffmpeg.pix_fmt("yuv420p");
-  ffmpeg.codec_audio("aac");
-  ffmpeg.args(["-b:a", "192k"]);
+  ffmpeg.args(["-b:a", &audio_bitrate]);
 
-  ffmpeg.output(destination_file_path.to_string_lossy());
+  if pass == EncodePass::First {
+    let null_device = if cfg!(target_os = "windows") {
+      "NUL"
+    } else {
+      "/dev/null"
+    };
+    ffmpeg.format("null").output(null_device);
+  } else {
+    configure_metadata(ffmpeg, &job.metadata, preset.format);
+    ffmpeg.output(output.output_path.to_string_lossy());
+  }
 }
 
 /// Return unique non existing path for base.
@@ -315,12 +2022,10 @@ fn prepare_output_path(
 
     let file_name = destination_file_path.file_name().unwrap();
     let stem = destination_file_path.file_stem().unwrap().to_string_lossy();
-    let ext = destination_file_path
-      .extension()
-      .map_or("".into(), |e| format!("{}", e.to_string_lossy()));
 
     let recording_file = unique_dir.join(file_name);
-    let camera_file = unique_dir.join(format!("{stem}_camera.{ext}"));
+    // Copied as recorded, whatever the export format
+    let camera_file = unique_dir.join(format!("{stem}_camera.mp4"));
 
     (recording_file, Some(camera_file))
   } else {
@@ -328,15 +2033,40 @@ fn prepare_output_path(
   }
 }
 
+/// Unique path for an additional output, distinct from the job's other outputs
+fn additional_output_path(destination_file_path: &Path, outputs: &[ExportOutput]) -> PathBuf {
+  let stem = destination_file_path
+    .file_stem()
+    .unwrap_or_default()
+    .to_string_lossy();
+  let extension = destination_file_path
+    .extension()
+    .unwrap_or_default()
+    .to_string_lossy();
+
+  let mut path = unique_path(destination_file_path.to_path_buf());
+  let mut index = 1;
+  while outputs.iter().any(|output| output.output_path == path) {
+    index += 1;
+    path = unique_path(destination_file_path.with_file_name(format!("{stem}_{index}.{extension}")));
+  }
+
+  path
+}
+
 /// Spawn a FFmpeg progress thread.
 ///
 /// Emits `ExportProgress` with milliseconds processed.
-fn ffmpeg_progress_reader(app_handle: AppHandle, stdout: ChildStdout) {
+fn ffmpeg_progress_reader(
+  app_handle: AppHandle,
+  stdout: ChildStdout,
+  to_progress: impl Fn(u64) -> u64,
+) {
   let reader = BufReader::new(stdout);
   for line in reader.lines().map_while(Result::ok) {
     if let Some(timestamp_str) = line.strip_prefix("out_time=") {
       if let Ok(milliseconds) = parse_timestamp_to_milliseconds(timestamp_str) {
-        let _ = app_handle.emit(Events::ExportProgress.as_ref(), milliseconds);
+        let _ = app_handle.emit(Events::ExportProgress.as_ref(), to_progress(milliseconds));
       }
     }
   }
@@ -371,24 +2101,33 @@ fn parse_timestamp_to_milliseconds(ts: &str) -> Result<u64, ()> {
 }
 
 /// Delete generated files (including new folder, if relevant)
-fn handle_cancellation(output_path: PathBuf, camera_path: Option<PathBuf>) {
-  if let Some(camera_path) = camera_path {
-    if let Ok(exists) = camera_path.try_exists() {
-      if exists {
-        if let Some(folder) = camera_path.parent() {
-          if let Err(e) = std::fs::remove_dir_all(folder) {
-            eprintln!("Failed to remove folder {folder:?}: {e}");
-          }
-          return;
-        }
-      }
+fn handle_cancellation(job: &ExportJob) {
+  remove_output_files(job);
+
+  if let Some(folder) = job
+    .camera_path
+    .as_ref()
+    .filter(|camera_path| camera_path.try_exists().unwrap_or(false))
+    .and_then(|camera_path| camera_path.parent())
+  {
+    if let Err(e) = std::fs::remove_dir_all(folder) {
+      eprintln!("Failed to remove folder {folder:?}: {e}");
     }
   }
+}
+
+/// Remove the files written by each output, e.g. before encoding them again
+fn remove_output_files(job: &ExportJob) {
+  for output in &job.outputs {
+    remove_file_if_exists(&output.output_path);
+  }
+}
 
-  if let Ok(exists) = output_path.try_exists() {
+fn remove_file_if_exists(path: &Path) {
+  if let Ok(exists) = path.try_exists() {
     if exists {
-      if let Err(e) = std::fs::remove_file(&output_path) {
-        eprintln!("Failed to remove output file {output_path:?}: {e}");
+      if let Err(e) = std::fs::remove_file(path) {
+        eprintln!("Failed to remove output file {path:?}: {e}");
       }
     }
   }
@@ -410,3 +2149,95 @@ pub fn open_path_in_file_browser(path: PathBuf) {
   #[cfg(target_os = "windows")]
   let _ = Command::new("explorer").arg(target).status();
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::project::models::AudioTrackSettings;
+
+  #[test]
+  fn recording_graph_combinations() {
+    // The camera is drawn by the compositor, so it reaches the graph as part of
+    // the composited video, absent for audio only formats
+    let audio = AudioSettings {
+      microphone: AudioTrackSettings {
+        muted: false,
+        volume: 0.5,
+      },
+      system_audio: AudioTrackSettings::default(),
+    };
+    let edit_lists = [
+      vec![],
+      vec![
+        EditRange {
+          start_ms: 1000,
+          end_ms: 2000,
+          action: EditAction::Cut,
+        },
+        EditRange {
+          start_ms: 3000,
+          end_ms: 5000,
+          action: EditAction::SpeedUp { factor: 2.0 },
+        },
+      ],
+    ];
+
+    for video in [true, false] {
+      for has_microphone in [false, true] {
+        for has_system_audio in [false, true] {
+          if !video && !has_microphone && !has_system_audio {
+            // Refused by `run_export` before building the graph
+            continue;
+          }
+
+          for separate_audio_tracks in [false, true] {
+            for edits in &edit_lists {
+              let case = format!(
+                "video: {video}, microphone: {has_microphone}, system audio: {has_system_audio}, \
+                 separate tracks: {separate_audio_tracks}, edits: {}",
+                edits.len()
+              );
+              let available_streams = RecordingFilePresence {
+                has_system_audio,
+                has_microphone,
+              };
+
+              let mut graph = FilterGraph::new();
+              let composited = video.then(|| add_composited_input(&mut graph, (1920, 1080)));
+              let inputs =
+                add_recording_inputs(&mut graph, Path::new("recording"), &available_streams);
+              let tracks =
+                configure_audio_tracks(&mut graph, &inputs, &audio, separate_audio_tracks);
+              let (video_pad, tracks) = configure_edit_list(
+                &mut graph,
+                edits,
+                composited.map(|composited| composited.video()),
+                tracks,
+              );
+
+              let audio_inputs = usize::from(has_microphone) + usize::from(has_system_audio);
+              let expected_tracks = if separate_audio_tracks {
+                audio_inputs
+              } else {
+                audio_inputs.min(1)
+              };
+              assert_eq!(video_pad.is_some(), video, "{case}");
+              assert_eq!(tracks.len(), expected_tracks, "{case}");
+
+              for pad in video_pad.into_iter().chain(tracks) {
+                graph.map(pad);
+              }
+              assert_eq!(graph.validate(), Ok(()), "{case}");
+              assert_eq!(
+                graph.output_args(0).len(),
+                2 * (usize::from(video) + expected_tracks),
+                "{case}"
+              );
+            }
+          }
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,110 @@
+use sqlx::{Pool, Sqlite};
+use tauri::{AppHandle, State};
+
+use crate::{
+  db::{self, recordings::NewRecording},
+  import::{models::ImportOptions, service},
+  recording::{
+    commands::spawn_post_processing,
+    ffmpeg::shortest_video_length,
+    file::{create_recording_directory, folder_size_bytes, recordings_root},
+    models::{RecordingFile, RecordingType},
+  },
+};
+
+/// Import external video, and optional camera and audio, as a new recording.
+///
+/// Returns the id of the new recording.
+#[tauri::command]
+pub async fn import_recording(
+  app_handle: AppHandle,
+  pool: State<'_, Pool<Sqlite>>,
+  options: ImportOptions,
+) -> Result<i64, String> {
+  let recording_dir = create_recording_directory(&recordings_root(&app_handle));
+
+  let import_dir = recording_dir.clone();
+  let import_options = options.clone();
+  let imported = tauri::async_runtime::spawn_blocking(move || {
+    let screen = service::import_video(
+      &import_options.video_path,
+      &import_dir,
+      RecordingFile::Screen,
+      import_options.mode,
+    )?;
+
+    if screen.has_audio {
+      service::import_audio(
+        &import_options.video_path,
+        &import_dir,
+        RecordingFile::SystemAudio,
+      )?;
+    }
+
+    if let Some(camera_path) = &import_options.camera_path {
+      service::import_video(
+        camera_path,
+        &import_dir,
+        RecordingFile::Camera,
+        import_options.mode,
+      )?;
+    }
+
+    if let Some(audio_path) = &import_options.audio_path {
+      service::import_audio(audio_path, &import_dir, RecordingFile::Microphone)?;
+    }
+
+    Ok::<_, String>(screen.has_audio)
+  })
+  .await
+  .map_err(|e| e.to_string())?;
+
+  let has_system_audio = match imported {
+    Ok(has_system_audio) => has_system_audio,
+    Err(e) => {
+      let _ = tokio::fs::remove_dir_all(&recording_dir).await;
+      return Err(e);
+    }
+  };
+
+  let name = options.name.unwrap_or_else(|| {
+    options
+      .video_path
+      .file_stem()
+      .map(|stem| stem.to_string_lossy().to_string())
+      .unwrap_or_else(|| "Imported Recording".to_string())
+  });
+
+  let recording_id = db::recordings::insert_recording(
+    &*pool,
+    &NewRecording {
+      recording_directory: &recording_dir.to_string_lossy(),
+      origin_x: 0.0,
+      origin_y: 0.0,
+      scale_factor: 1.0,
+      name,
+      has_camera: options.camera_path.is_some(),
+      has_system_audio,
+      has_microphone: options.audio_path.is_some(),
+      // Cursor is part of the imported video, there are no mouse events to render
+      has_system_cursor: true,
+      r#type: &RecordingType::Imported,
+    },
+  )
+  .await
+  .map_err(|e| e.to_string())?;
+
+  let bytes = folder_size_bytes(&recording_dir);
+  let milliseconds = shortest_video_length(vec![
+    recording_dir.join(RecordingFile::Screen.as_ref()),
+    recording_dir.join(RecordingFile::Camera.as_ref()),
+  ]);
+
+  db::recordings::set_recording_metadata(&*pool, recording_id, bytes, milliseconds)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  spawn_post_processing(app_handle, recording_id, recording_dir);
+
+  Ok(recording_id)
+}
@@ -0,0 +1,27 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+  /// Copy into the recording directory, the source can be removed afterwards
+  #[default]
+  Copy,
+  /// Hard link when the file is usable as is, falls back to copying
+  Link,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportOptions {
+  /// Screen video, any audio inside is imported as system audio
+  pub video_path: PathBuf,
+  pub camera_path: Option<PathBuf>,
+  /// Separate audio, imported as the microphone track
+  pub audio_path: Option<PathBuf>,
+  /// Defaults to the video file name
+  pub name: Option<String>,
+  #[serde(default)]
+  pub mode: ImportMode,
+}
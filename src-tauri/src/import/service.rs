@@ -0,0 +1,142 @@
+use std::{path::Path, process::Command};
+
+use ffmpeg_sidecar::{command::FfmpegCommand, ffprobe::ffprobe_path};
+
+use crate::{
+  import::models::ImportMode,
+  recording::{ffmpeg::run_to_completion, models::RecordingFile},
+};
+
+/// Video codecs the export pipeline reads without re-encoding
+const SUPPORTED_VIDEO_CODECS: [&str; 2] = ["h264", "hevc"];
+
+#[derive(Debug, Default)]
+pub struct ProbedMedia {
+  pub video_codec: Option<String>,
+  pub has_audio: bool,
+}
+
+/// Probe codecs of the streams in a media file
+pub fn probe_media(path: &Path) -> Result<ProbedMedia, String> {
+  let output = Command::new(ffprobe_path())
+    .args([
+      "-v",
+      "error",
+      "-show_entries",
+      "stream=codec_type,codec_name",
+      "-of",
+      "csv=p=0",
+      path.to_string_lossy().as_ref(),
+    ])
+    .output()
+    .map_err(|e| e.to_string())?;
+
+  if !output.status.success() {
+    return Err(format!(
+      "Failed to probe {}: {}",
+      path.display(),
+      String::from_utf8_lossy(&output.stderr).trim()
+    ));
+  }
+
+  let mut media = ProbedMedia::default();
+  for line in String::from_utf8_lossy(&output.stdout).lines() {
+    match line.trim().split_once(',') {
+      Some((codec, "video")) if media.video_codec.is_none() => {
+        media.video_codec = Some(codec.to_string())
+      }
+      Some((_, "audio")) => media.has_audio = true,
+      _ => {}
+    }
+  }
+
+  Ok(media)
+}
+
+fn is_mp4(path: &Path) -> bool {
+  path
+    .extension()
+    .map(|e| e.to_string_lossy().to_lowercase())
+    .is_some_and(|e| e == "mp4" || e == "m4v")
+}
+
+/// Import a video as `file`, keeping only its video stream.
+///
+/// Supported codecs are remuxed, anything else is transcoded to H.264. Files
+/// already in the expected format are copied or linked as is.
+pub fn import_video(
+  source: &Path,
+  recording_dir: &Path,
+  file: RecordingFile,
+  mode: ImportMode,
+) -> Result<ProbedMedia, String> {
+  let media = probe_media(source)?;
+  let Some(video_codec) = media.video_codec.as_deref() else {
+    return Err(format!("{} has no video stream", source.display()));
+  };
+
+  let destination = recording_dir.join(file.as_ref());
+  let supported = SUPPORTED_VIDEO_CODECS.contains(&video_codec);
+
+  if supported && !media.has_audio && is_mp4(source) {
+    transfer_file(source, &destination, mode)?;
+    return Ok(media);
+  }
+
+  let mut command = FfmpegCommand::new();
+  command
+    .overwrite()
+    .input(source.to_string_lossy())
+    .map("0:v:0")
+    .arg("-an");
+
+  if supported {
+    command.codec_video("copy");
+    if video_codec == "hevc" {
+      // Same tag as recorded screen video, for QuickTime compatibility
+      command.args(["-tag:v", "hvc1"]);
+    }
+  } else {
+    command
+      .codec_video("libx264")
+      .args(["-preset", "medium"])
+      .crf(20)
+      .pix_fmt("yuv420p");
+  }
+
+  command
+    .args(["-movflags", "+faststart"])
+    .output(destination.to_string_lossy());
+
+  run_to_completion(command, "[import video]")?;
+
+  Ok(media)
+}
+
+/// Import audio as `file`, converted to the PCM WAV used by recorded tracks
+pub fn import_audio(
+  source: &Path,
+  recording_dir: &Path,
+  file: RecordingFile,
+) -> Result<(), String> {
+  let mut command = FfmpegCommand::new();
+  command
+    .overwrite()
+    .input(source.to_string_lossy())
+    .map("0:a:0")
+    .arg("-vn")
+    .codec_audio("pcm_s16le")
+    .output(recording_dir.join(file.as_ref()).to_string_lossy());
+
+  run_to_completion(command, "[import audio]")
+}
+
+fn transfer_file(source: &Path, destination: &Path, mode: ImportMode) -> Result<(), String> {
+  if mode == ImportMode::Link && std::fs::hard_link(source, destination).is_ok() {
+    return Ok(());
+  }
+
+  std::fs::copy(source, destination)
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
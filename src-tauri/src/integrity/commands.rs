@@ -0,0 +1,55 @@
+use parking_lot::Mutex;
+use sqlx::{Pool, Sqlite};
+use tauri::{AppHandle, Manager, State};
+
+use crate::{
+  integrity::{
+    models::{IntegrityReport, RepairAction, RepairSummary},
+    service,
+  },
+  models::RecordingState,
+  recording::file::recordings_root,
+};
+
+/// Report mismatches between recording rows and the files on disk
+#[tauri::command]
+pub async fn check_library_integrity(
+  app_handle: AppHandle,
+  pool: State<'_, Pool<Sqlite>>,
+) -> Result<IntegrityReport, String> {
+  let recordings_dir = recordings_root(&app_handle);
+  let active_recording_id = app_handle
+    .state::<Mutex<RecordingState>>()
+    .lock()
+    .recording_id;
+
+  service::check_integrity(&pool, recordings_dir, active_recording_id).await
+}
+
+/// Apply repair actions, continuing past individual failures
+#[tauri::command]
+pub async fn repair_library(
+  app_handle: AppHandle,
+  pool: State<'_, Pool<Sqlite>>,
+  actions: Vec<RepairAction>,
+) -> Result<RepairSummary, String> {
+  if app_handle
+    .state::<Mutex<RecordingState>>()
+    .lock()
+    .is_recording()
+  {
+    return Err("Cannot repair the library while recording".to_string());
+  }
+
+  let recordings_dir = recordings_root(&app_handle);
+
+  let mut summary = RepairSummary::default();
+  for action in &actions {
+    match service::repair(&pool, &recordings_dir, action).await {
+      Ok(()) => summary.repaired += 1,
+      Err(e) => summary.errors.push(e),
+    }
+  }
+
+  Ok(summary)
+}
@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::recording::models::RecordingFile;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(
+  rename_all = "camelCase",
+  rename_all_fields = "camelCase",
+  tag = "kind"
+)]
+pub enum IntegrityIssue {
+  /// Row whose recording directory no longer exists
+  MissingDirectory {
+    recording_id: i64,
+    recording_directory: PathBuf,
+  },
+  /// Screen video missing, the recording can't be opened
+  MissingScreen { recording_id: i64 },
+  /// `has_*` flag doesn't match whether the file exists
+  FlagMismatch {
+    recording_id: i64,
+    file: RecordingFile,
+    flag: bool,
+    exists: bool,
+  },
+  /// Stored size differs from the recorded files on disk
+  SizeMismatch {
+    recording_id: i64,
+    stored_bytes: Option<i64>,
+    actual_bytes: u64,
+  },
+  /// Directory in the recordings folder without a row
+  OrphanedDirectory {
+    path: PathBuf,
+    /// Contains a screen video, so can be imported
+    importable: bool,
+  },
+  /// Leftover pause/resume segment, segment list or partial generated file
+  TemporaryFile { path: PathBuf, bytes: u64 },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+  pub scanned_recordings: usize,
+  pub issues: Vec<IntegrityIssue>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(
+  rename_all = "camelCase",
+  rename_all_fields = "camelCase",
+  tag = "action"
+)]
+pub enum RepairAction {
+  /// Add a row for an orphaned directory
+  ImportOrphan {
+    path: PathBuf,
+  },
+  /// Set `has_*` flags from the files on disk
+  FixFlags {
+    recording_id: i64,
+  },
+  /// Recompute size and length from the files on disk
+  RecomputeMetadata {
+    recording_id: i64,
+  },
+  /// Remove the row of a recording whose directory is gone
+  ForgetRecording {
+    recording_id: i64,
+  },
+  CleanTemporaryFile {
+    path: PathBuf,
+  },
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairSummary {
+  pub repaired: usize,
+  pub errors: Vec<String>,
+}
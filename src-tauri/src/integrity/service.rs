@@ -0,0 +1,311 @@
+use std::{
+  collections::HashSet,
+  path::{Path, PathBuf},
+  time::Duration,
+};
+
+use sqlx::SqlitePool;
+
+use crate::{
+  db::{
+    self,
+    recordings::{NewRecording, RecordingFiles},
+  },
+  integrity::models::{IntegrityIssue, IntegrityReport, RepairAction},
+  recording::{
+    ffmpeg::shortest_video_length,
+    file::{is_offline, is_temporary_file, recorded_size_bytes},
+    models::{RecordingFile, RecordingType},
+  },
+};
+
+/// Temporary files modified more recently may still be written, e.g. by
+/// storage optimization or proxy generation
+const TEMPORARY_FILE_MIN_AGE: Duration = Duration::from_secs(5 * 60);
+
+fn is_abandoned_temporary_file(path: &Path) -> bool {
+  let age = path
+    .metadata()
+    .and_then(|metadata| metadata.modified())
+    .ok()
+    .and_then(|modified| modified.elapsed().ok());
+
+  is_temporary_file(path) && age.is_some_and(|age| age >= TEMPORARY_FILE_MIN_AGE)
+}
+
+fn temporary_files(dir: &Path) -> Vec<(PathBuf, u64)> {
+  let Ok(entries) = std::fs::read_dir(dir) else {
+    return vec![];
+  };
+
+  entries
+    .filter_map(Result::ok)
+    .map(|entry| entry.path())
+    .filter(|path| path.is_file() && is_abandoned_temporary_file(path))
+    .map(|path| {
+      let bytes = path.metadata().map(|m| m.len()).unwrap_or(0);
+      (path, bytes)
+    })
+    .collect()
+}
+
+/// Compare recording rows against the recordings folder.
+///
+/// `active_recording_id` is skipped, its files are still being written.
+pub async fn check_integrity(
+  pool: &SqlitePool,
+  recordings_dir: PathBuf,
+  active_recording_id: Option<i64>,
+) -> Result<IntegrityReport, String> {
+  let recordings = db::recordings::list_recording_files(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  tauri::async_runtime::spawn_blocking(move || {
+    let issues = scan_library(&recordings, &recordings_dir, active_recording_id);
+    IntegrityReport {
+      scanned_recordings: recordings.len(),
+      issues,
+    }
+  })
+  .await
+  .map_err(|e| e.to_string())
+}
+
+fn scan_library(
+  recordings: &[RecordingFiles],
+  recordings_dir: &Path,
+  active_recording_id: Option<i64>,
+) -> Vec<IntegrityIssue> {
+  let mut issues = Vec::new();
+
+  for recording in recordings {
+    if Some(recording.id) == active_recording_id {
+      continue;
+    }
+
+    let dir = &recording.recording_directory;
+    if is_offline(dir) {
+      // Volume not mounted, nothing to compare against
+      continue;
+    }
+
+    if !dir.is_dir() {
+      issues.push(IntegrityIssue::MissingDirectory {
+        recording_id: recording.id,
+        recording_directory: dir.clone(),
+      });
+      continue;
+    }
+
+    if !dir.join(RecordingFile::Screen.as_ref()).exists() {
+      issues.push(IntegrityIssue::MissingScreen {
+        recording_id: recording.id,
+      });
+    }
+
+    let flags = [
+      (RecordingFile::Camera, recording.has_camera),
+      (RecordingFile::SystemAudio, recording.has_system_audio),
+      (RecordingFile::Microphone, recording.has_microphone),
+    ];
+    for (file, flag) in flags {
+      let exists = file.resolve(dir).exists();
+      if flag != exists {
+        issues.push(IntegrityIssue::FlagMismatch {
+          recording_id: recording.id,
+          file,
+          flag,
+          exists,
+        });
+      }
+    }
+
+    let actual_bytes = recorded_size_bytes(dir);
+    if recording.size_bytes != Some(actual_bytes as i64) {
+      issues.push(IntegrityIssue::SizeMismatch {
+        recording_id: recording.id,
+        stored_bytes: recording.size_bytes,
+        actual_bytes,
+      });
+    }
+
+    for (path, bytes) in temporary_files(dir) {
+      issues.push(IntegrityIssue::TemporaryFile { path, bytes });
+    }
+  }
+
+  let known_dirs: HashSet<&PathBuf> = recordings
+    .iter()
+    .map(|recording| &recording.recording_directory)
+    .collect();
+
+  if let Ok(entries) = std::fs::read_dir(recordings_dir) {
+    for path in entries.filter_map(Result::ok).map(|entry| entry.path()) {
+      if !path.is_dir() || known_dirs.contains(&path) {
+        continue;
+      }
+
+      for (path, bytes) in temporary_files(&path) {
+        issues.push(IntegrityIssue::TemporaryFile { path, bytes });
+      }
+
+      issues.push(IntegrityIssue::OrphanedDirectory {
+        importable: path.join(RecordingFile::Screen.as_ref()).exists(),
+        path,
+      });
+    }
+  }
+
+  issues
+}
+
+/// Camera, system audio and microphone files present in `dir`
+fn existing_tracks(dir: &Path) -> (bool, bool, bool) {
+  let exists = |file: RecordingFile| file.resolve(dir).exists();
+  (
+    exists(RecordingFile::Camera),
+    exists(RecordingFile::SystemAudio),
+    exists(RecordingFile::Microphone),
+  )
+}
+
+async fn recompute_metadata(
+  pool: &SqlitePool,
+  recording_id: i64,
+  dir: PathBuf,
+) -> Result<(), String> {
+  let (size_bytes, milliseconds) = tauri::async_runtime::spawn_blocking(move || {
+    let milliseconds = shortest_video_length(vec![
+      dir.join(RecordingFile::Screen.as_ref()),
+      dir.join(RecordingFile::Camera.as_ref()),
+    ]);
+    (recorded_size_bytes(&dir), milliseconds)
+  })
+  .await
+  .map_err(|e| e.to_string())?;
+
+  db::recordings::set_recording_metadata(pool, recording_id, size_bytes, milliseconds)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+pub async fn repair(
+  pool: &SqlitePool,
+  recordings_dir: &Path,
+  action: &RepairAction,
+) -> Result<(), String> {
+  match action {
+    RepairAction::ImportOrphan { path } => {
+      let not_importable = || format!("{} is not an importable recording", path.display());
+      if path.parent() != Some(recordings_dir) {
+        return Err(not_importable());
+      }
+
+      let blocking_path = path.clone();
+      let (has_camera, has_system_audio, has_microphone) =
+        tauri::async_runtime::spawn_blocking(move || {
+          blocking_path
+            .join(RecordingFile::Screen.as_ref())
+            .exists()
+            .then(|| existing_tracks(&blocking_path))
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(not_importable)?;
+
+      let recording_id = db::recordings::insert_recording(
+        pool,
+        &NewRecording {
+          recording_directory: &path.to_string_lossy(),
+          // Capture origin is lost with the row, so mouse events can't be
+          // mapped onto the video and the recording is treated as imported
+          origin_x: 0.0,
+          origin_y: 0.0,
+          scale_factor: 1.0,
+          name: path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+          has_camera,
+          has_system_audio,
+          has_microphone,
+          has_system_cursor: true,
+          r#type: &RecordingType::Imported,
+        },
+      )
+      .await
+      .map_err(|e| e.to_string())?;
+
+      recompute_metadata(pool, recording_id, path.clone()).await
+    }
+    RepairAction::FixFlags { recording_id } => {
+      let dir = db::recordings::get_recording_directory(pool, *recording_id)
+        .await
+        .map_err(|e| e.to_string())?;
+      let (has_camera, has_system_audio, has_microphone) =
+        tauri::async_runtime::spawn_blocking(move || existing_tracks(&dir))
+          .await
+          .map_err(|e| e.to_string())?;
+
+      db::recordings::set_recording_flags(
+        pool,
+        *recording_id,
+        has_camera,
+        has_system_audio,
+        has_microphone,
+      )
+      .await
+      .map_err(|e| e.to_string())
+    }
+    RepairAction::RecomputeMetadata { recording_id } => {
+      let dir = db::recordings::get_recording_directory(pool, *recording_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+      recompute_metadata(pool, *recording_id, dir).await
+    }
+    RepairAction::ForgetRecording { recording_id } => {
+      let dir = db::recordings::get_recording_directory(pool, *recording_id)
+        .await
+        .map_err(|e| e.to_string())?;
+      let blocking_dir = dir.clone();
+      let present = tauri::async_runtime::spawn_blocking(move || {
+        blocking_dir.exists() || is_offline(&blocking_dir)
+      })
+      .await
+      .map_err(|e| e.to_string())?;
+      if present {
+        return Err(format!("{} still exists or is offline", dir.display()));
+      }
+
+      db::recordings::hard_delete_recordings(pool, vec![*recording_id])
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+    }
+    RepairAction::CleanTemporaryFile { path } => {
+      // Only ever remove matching files inside the recordings folder
+      if !path.starts_with(recordings_dir) {
+        return Err(format!(
+          "{} is not an abandoned temporary file",
+          path.display()
+        ));
+      }
+
+      let path = path.clone();
+      tauri::async_runtime::spawn_blocking(move || {
+        if !is_abandoned_temporary_file(&path) {
+          return Err(format!(
+            "{} is not an abandoned temporary file",
+            path.display()
+          ));
+        }
+
+        std::fs::remove_file(&path).map_err(|e| e.to_string())
+      })
+      .await
+      .map_err(|e| e.to_string())?
+    }
+  }
+}
@@ -1,24 +1,38 @@
+mod analysis;
 mod audio;
+mod bundle;
 mod camera;
+mod captions;
+mod compositor;
 mod constants;
 mod db;
 mod export;
 mod global_inputs;
+mod import;
+mod integrity;
+mod library;
+mod markers;
 mod models;
 #[cfg(target_os = "macos")]
 mod permissions;
+mod project;
 mod recording;
 mod recording_management;
 mod recording_sources;
 mod screen_capture;
+mod storage;
 mod system_tray;
+mod waveform;
 mod windows;
 
 use std::sync::{Arc, OnceLock};
 
 use audio::commands::{list_audio_inputs, start_audio_listener, stop_audio_listener};
 use camera::commands::{list_cameras, start_camera_stream, stop_camera_stream};
-use constants::store::{FIRST_RUN, STORE_NAME};
+use constants::store::{
+  EXPORT_PRESETS, FIRST_RUN, GENERATE_PROXY_MEDIA, RETENTION_SETTINGS, STORAGE_OPTIMIZATION,
+  STORE_NAME,
+};
 
 use parking_lot::Mutex;
 use rdev::listen;
@@ -59,14 +73,43 @@ use rdev::set_is_main_thread;
 #[cfg(target_os = "windows")]
 use crate::windows::commands::init_editor;
 use crate::{
-  export::commands::{cancel_export, export_recording, open_path_in_file_browser, path_exists},
+  analysis::commands::suggest_dead_air_edits,
+  bundle::commands::{export_recording_bundle, import_recording_bundle},
+  captions::commands::{get_caption_segments, transcribe_recording, update_caption_segments},
+  export::commands::{
+    cancel_export, delete_export_preset, export_recording, list_export_presets,
+    open_path_in_file_browser, path_exists, render_preview_frame, save_export_preset,
+  },
+  import::commands::import_recording,
+  integrity::commands::{check_library_integrity, repair_library},
+  library::commands::{
+    assign_tags, create_folder, create_tag, delete_folder, delete_tag, list_folders, list_tags,
+    move_recordings_to_folder, rename_folder, rename_tag, unassign_tags,
+  },
+  markers::{
+    commands::{add_marker, delete_marker, list_markers, update_marker},
+    service::spawn_marker_hotkey_listener,
+  },
   models::{EditingState, GlobalState, PreviewState, RecordingState},
-  recording_management::commands::{
-    automated_hard_delete_recordings, get_recording_details, hard_delete_recordings,
-    list_recordings, recording_opened, restore_recordings, soft_delete_recordings,
-    update_recording_name,
+  project::commands::{
+    get_project, list_recorded_windows, patch_project, redo_project, save_project,
+    track_window_redaction, undo_project,
+  },
+  recording_management::{
+    commands::{
+      enforce_retention_policy, generate_proxy_media, get_recording_details,
+      get_retention_settings, hard_delete_recordings, list_recordings, recording_opened,
+      regenerate_thumbnails, restore_recordings, set_poster_time, set_recordings_pinned,
+      set_retention_settings, soft_delete_recordings, update_recording_name,
+    },
+    service,
   },
   recording_sources::commands::{center_window, resize_window},
+  storage::commands::{
+    get_recordings_root, get_storage_optimization_settings, move_library, optimize_storage_now,
+    set_recordings_root, set_storage_optimization_settings,
+  },
+  waveform::commands::get_waveform_peaks,
   windows::{
     commands::{
       init_start_recording_dock, set_region_selector_passthrough, take_display_screenshot,
@@ -87,6 +130,28 @@ async fn setup_store(app: &App) -> Arc<Store<Wry>> {
     store.set(FIRST_RUN, json!(true));
   }
 
+  if store.get(GENERATE_PROXY_MEDIA).is_none() {
+    store.set(GENERATE_PROXY_MEDIA, json!(true));
+  }
+
+  if store.get(RETENTION_SETTINGS).is_none() {
+    store.set(
+      RETENTION_SETTINGS,
+      json!(recording_management::models::RetentionSettings::default()),
+    );
+  }
+
+  if store.get(STORAGE_OPTIMIZATION).is_none() {
+    store.set(
+      STORAGE_OPTIMIZATION,
+      json!(storage::models::StorageOptimizationSettings::default()),
+    );
+  }
+
+  if store.get(EXPORT_PRESETS).is_none() {
+    store.set(EXPORT_PRESETS, json!([]));
+  }
+
   #[cfg(target_os = "macos")]
   {
     if store.get(NATIVE_REQUESTABLE_PERMISSIONS).is_none() {
@@ -190,6 +255,9 @@ pub fn run() {
     path_exists,
     export_recording,
     cancel_export,
+    list_export_presets,
+    save_export_preset,
+    delete_export_preset,
     resize_window,
     #[cfg(target_os = "windows")]
     make_borderless,
@@ -202,7 +270,53 @@ pub fn run() {
     soft_delete_recordings,
     restore_recordings,
     hard_delete_recordings,
-    recording_opened
+    recording_opened,
+    transcribe_recording,
+    get_caption_segments,
+    update_caption_segments,
+    suggest_dead_air_edits,
+    list_markers,
+    add_marker,
+    update_marker,
+    delete_marker,
+    regenerate_thumbnails,
+    set_poster_time,
+    get_waveform_peaks,
+    generate_proxy_media,
+    list_tags,
+    create_tag,
+    rename_tag,
+    delete_tag,
+    assign_tags,
+    unassign_tags,
+    list_folders,
+    create_folder,
+    rename_folder,
+    delete_folder,
+    move_recordings_to_folder,
+    set_recordings_pinned,
+    get_retention_settings,
+    set_retention_settings,
+    enforce_retention_policy,
+    import_recording,
+    check_library_integrity,
+    repair_library,
+    export_recording_bundle,
+    import_recording_bundle,
+    get_recordings_root,
+    set_recordings_root,
+    move_library,
+    get_storage_optimization_settings,
+    set_storage_optimization_settings,
+    optimize_storage_now,
+    get_project,
+    save_project,
+    patch_project,
+    undo_project,
+    redo_project,
+    list_recorded_windows,
+    track_window_redaction,
+    render_preview_frame
   ]);
 
   // State
@@ -263,6 +377,114 @@ pub fn run() {
       sql: include_str!("../migrations/4_recording_metadata.down.sql"),
       kind: MigrationKind::Down,
     },
+    Migration {
+      version: 5,
+      description: "caption_segments",
+      sql: include_str!("../migrations/5_caption_segments.up.sql"),
+      kind: MigrationKind::Up,
+    },
+    Migration {
+      version: 5,
+      description: "caption_segments",
+      sql: include_str!("../migrations/5_caption_segments.down.sql"),
+      kind: MigrationKind::Down,
+    },
+    Migration {
+      version: 6,
+      description: "markers",
+      sql: include_str!("../migrations/6_markers.up.sql"),
+      kind: MigrationKind::Up,
+    },
+    Migration {
+      version: 6,
+      description: "markers",
+      sql: include_str!("../migrations/6_markers.down.sql"),
+      kind: MigrationKind::Down,
+    },
+    Migration {
+      version: 7,
+      description: "recording_poster_time",
+      sql: include_str!("../migrations/7_recording_poster_time.up.sql"),
+      kind: MigrationKind::Up,
+    },
+    Migration {
+      version: 7,
+      description: "recording_poster_time",
+      sql: include_str!("../migrations/7_recording_poster_time.down.sql"),
+      kind: MigrationKind::Down,
+    },
+    Migration {
+      version: 8,
+      description: "recording_search",
+      sql: include_str!("../migrations/8_recording_search.up.sql"),
+      kind: MigrationKind::Up,
+    },
+    Migration {
+      version: 8,
+      description: "recording_search",
+      sql: include_str!("../migrations/8_recording_search.down.sql"),
+      kind: MigrationKind::Down,
+    },
+    Migration {
+      version: 9,
+      description: "tags_and_folders",
+      sql: include_str!("../migrations/9_tags_and_folders.up.sql"),
+      kind: MigrationKind::Up,
+    },
+    Migration {
+      version: 9,
+      description: "tags_and_folders",
+      sql: include_str!("../migrations/9_tags_and_folders.down.sql"),
+      kind: MigrationKind::Down,
+    },
+    Migration {
+      version: 10,
+      description: "recording_pinned",
+      sql: include_str!("../migrations/10_recording_pinned.up.sql"),
+      kind: MigrationKind::Up,
+    },
+    Migration {
+      version: 10,
+      description: "recording_pinned",
+      sql: include_str!("../migrations/10_recording_pinned.down.sql"),
+      kind: MigrationKind::Down,
+    },
+    Migration {
+      version: 11,
+      description: "imported_recording_type",
+      sql: include_str!("../migrations/11_imported_recording_type.up.sql"),
+      kind: MigrationKind::Up,
+    },
+    Migration {
+      version: 11,
+      description: "imported_recording_type",
+      sql: include_str!("../migrations/11_imported_recording_type.down.sql"),
+      kind: MigrationKind::Down,
+    },
+    Migration {
+      version: 12,
+      description: "recording_optimized_at",
+      sql: include_str!("../migrations/12_recording_optimized_at.up.sql"),
+      kind: MigrationKind::Up,
+    },
+    Migration {
+      version: 12,
+      description: "recording_optimized_at",
+      sql: include_str!("../migrations/12_recording_optimized_at.down.sql"),
+      kind: MigrationKind::Down,
+    },
+    Migration {
+      version: 13,
+      description: "recording_projects",
+      sql: include_str!("../migrations/13_recording_projects.up.sql"),
+      kind: MigrationKind::Up,
+    },
+    Migration {
+      version: 13,
+      description: "recording_projects",
+      sql: include_str!("../migrations/13_recording_projects.down.sql"),
+      kind: MigrationKind::Down,
+    },
   ];
 
   // Plugins
@@ -313,6 +535,7 @@ pub fn run() {
 
       init_start_recording_dock(app_handle.clone());
       spawn_window_close_manager(app_handle.clone(), input_event_tx.subscribe());
+      spawn_marker_hotkey_listener(app_handle.clone(), input_event_tx.subscribe());
       editor_close_listener(&app_handle.clone());
 
       tauri::async_runtime::block_on(async {
@@ -361,25 +584,44 @@ pub fn run() {
         }
       });
 
+      let app_handle_for_retention = app_handle.clone();
       tauri::async_runtime::spawn(async move {
+        // Also applied on startup, the app may not be running at the scheduled time
+        if let Err(e) = service::enforce_retention_policy(&app_handle_for_retention).await {
+          log::error!("Failed to enforce retention policy: {e}");
+        }
+
         let mut scheduler = JobScheduler::new().await;
 
         if let Ok(scheduler) = &mut scheduler {
-          // Daily job at 5am to hard delete old recordings
+          // Daily job at 5am to apply trash retention, auto-trash and storage quota,
+          // then re-encode old recordings if storage optimization is enabled
           scheduler
             .add(
               Job::new_async("0 0 5 * * *", |_uuid, _l| {
                 Box::pin(async move {
                   let app_handle = APP_HANDLE.get().unwrap();
-                  automated_hard_delete_recordings(app_handle.state()).await;
+                  if let Err(e) = service::enforce_retention_policy(app_handle).await {
+                    log::error!("Failed to enforce retention policy: {e}");
+                  }
+
+                  if storage::service::storage_optimization_settings(app_handle).enabled {
+                    if let Err(e) = storage::service::optimize_storage(app_handle).await {
+                      log::error!("Failed to optimize storage: {e}");
+                    }
+                  }
                 })
               })
               .unwrap(),
             )
             .await
             .ok();
+
+          if let Err(e) = scheduler.start().await {
+            log::error!("Failed to start scheduler: {e}");
+          }
         } else if let Err(e) = scheduler {
-          eprintln!("Failed to start scheduler: {e}");
+          log::error!("Failed to start scheduler: {e}");
         }
       });
 
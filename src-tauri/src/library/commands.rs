@@ -0,0 +1,110 @@
+use sqlx::{Pool, Sqlite};
+use tauri::State;
+
+use crate::{
+  db,
+  library::models::{Folder, Tag},
+};
+
+#[tauri::command]
+pub async fn list_tags(pool: State<'_, Pool<Sqlite>>) -> Result<Vec<Tag>, String> {
+  db::tags::list_tags(&pool).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_tag(pool: State<'_, Pool<Sqlite>>, name: String) -> Result<Tag, String> {
+  db::tags::insert_tag(&pool, name.trim())
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn rename_tag(
+  pool: State<'_, Pool<Sqlite>>,
+  tag_id: i64,
+  name: String,
+) -> Result<(), String> {
+  db::tags::rename_tag(&pool, tag_id, name.trim())
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_tag(pool: State<'_, Pool<Sqlite>>, tag_id: i64) -> Result<(), String> {
+  db::tags::delete_tag(&pool, tag_id)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn assign_tags(
+  pool: State<'_, Pool<Sqlite>>,
+  recording_ids: Vec<i64>,
+  tag_ids: Vec<i64>,
+) -> Result<(), String> {
+  db::tags::assign_tags(&pool, &recording_ids, &tag_ids)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn unassign_tags(
+  pool: State<'_, Pool<Sqlite>>,
+  recording_ids: Vec<i64>,
+  tag_ids: Vec<i64>,
+) -> Result<(), String> {
+  db::tags::unassign_tags(&pool, &recording_ids, &tag_ids)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_folders(pool: State<'_, Pool<Sqlite>>) -> Result<Vec<Folder>, String> {
+  db::folders::list_folders(&pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_folder(
+  pool: State<'_, Pool<Sqlite>>,
+  name: String,
+  parent_id: Option<i64>,
+) -> Result<Folder, String> {
+  db::folders::insert_folder(&pool, name.trim(), parent_id)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn rename_folder(
+  pool: State<'_, Pool<Sqlite>>,
+  folder_id: i64,
+  name: String,
+) -> Result<(), String> {
+  db::folders::rename_folder(&pool, folder_id, name.trim())
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_folder(
+  pool: State<'_, Pool<Sqlite>>,
+  folder_id: i64,
+  soft_delete_contents: bool,
+) -> Result<(), String> {
+  db::folders::delete_folder(&pool, folder_id, soft_delete_contents)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn move_recordings_to_folder(
+  pool: State<'_, Pool<Sqlite>>,
+  recording_ids: Vec<i64>,
+  folder_id: Option<i64>,
+) -> Result<(), String> {
+  db::folders::move_recordings_to_folder(&pool, &recording_ids, folder_id)
+    .await
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,17 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tag {
+  pub id: i64,
+  pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Folder {
+  pub id: i64,
+  pub name: String,
+  /// `None` for top level folders
+  pub parent_id: Option<i64>,
+}
@@ -0,0 +1,47 @@
+use sqlx::{Pool, Sqlite};
+use tauri::State;
+
+use crate::{
+  db,
+  markers::models::{Marker, MarkerUpdate},
+};
+
+#[tauri::command]
+pub async fn list_markers(
+  pool: State<'_, Pool<Sqlite>>,
+  recording_id: i64,
+) -> Result<Vec<Marker>, String> {
+  db::markers::list_markers(&pool, recording_id)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn add_marker(
+  pool: State<'_, Pool<Sqlite>>,
+  recording_id: i64,
+  position_ms: i64,
+  name: Option<String>,
+) -> Result<Marker, String> {
+  db::markers::insert_marker(&*pool, recording_id, position_ms, name)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_marker(
+  pool: State<'_, Pool<Sqlite>>,
+  marker_id: i64,
+  update: MarkerUpdate,
+) -> Result<(), String> {
+  db::markers::update_marker(&pool, marker_id, &update)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_marker(pool: State<'_, Pool<Sqlite>>, marker_id: i64) -> Result<(), String> {
+  db::markers::delete_marker(&pool, marker_id)
+    .await
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Marker {
+  pub id: i64,
+  pub recording_id: i64,
+  pub position_ms: i64,
+  pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkerUpdate {
+  pub name: String,
+  pub position_ms: i64,
+}
@@ -0,0 +1,166 @@
+use std::{fs::File, io::Write, path::Path};
+
+use parking_lot::Mutex;
+use rdev::{EventType, Key};
+use sqlx::{Pool, Sqlite};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+
+use crate::{constants::Events, db, models::RecordingState};
+
+/// Spawn a thread which adds a marker when the marker hotkey is pressed.
+///
+/// Hotkey is `Cmd+Shift+M` on macOS and `Ctrl+Shift+M` elsewhere, only active
+/// while recording.
+pub fn spawn_marker_hotkey_listener(
+  app_handle: AppHandle,
+  mut input_event_rx: Receiver<rdev::Event>,
+) {
+  std::thread::spawn(move || {
+    let mut modifier_down = false;
+    let mut shift_down = false;
+
+    loop {
+      let event = match input_event_rx.blocking_recv() {
+        Ok(event) => event,
+        // Missed events only risk a stale modifier state
+        Err(RecvError::Lagged(_)) => continue,
+        Err(RecvError::Closed) => break,
+      };
+
+      match event.event_type {
+        EventType::KeyPress(key) if is_modifier_key(key) => modifier_down = true,
+        EventType::KeyRelease(key) if is_modifier_key(key) => modifier_down = false,
+        EventType::KeyPress(Key::ShiftLeft | Key::ShiftRight) => shift_down = true,
+        EventType::KeyRelease(Key::ShiftLeft | Key::ShiftRight) => shift_down = false,
+        EventType::KeyPress(Key::KeyM) if modifier_down && shift_down => {
+          add_marker_at_current_position(app_handle.clone());
+        }
+        _ => {}
+      }
+    }
+  });
+}
+
+fn is_modifier_key(key: Key) -> bool {
+  #[cfg(target_os = "macos")]
+  {
+    matches!(key, Key::MetaLeft | Key::MetaRight)
+  }
+
+  #[cfg(not(target_os = "macos"))]
+  {
+    matches!(key, Key::ControlLeft | Key::ControlRight)
+  }
+}
+
+/// Add a marker at the current position of the active recording.
+///
+/// Emits `MarkerAdded` with the new marker, no-op if not recording.
+pub fn add_marker_at_current_position(app_handle: AppHandle) {
+  let (recording_id, position_ms) = {
+    let recording_state: State<'_, Mutex<RecordingState>> = app_handle.state();
+    let state = recording_state.lock();
+    match (state.recording_id, state.timeline_position_ms()) {
+      (Some(recording_id), Some(position_ms)) if state.is_recording() => {
+        (recording_id, position_ms)
+      }
+      _ => return,
+    }
+  };
+
+  tauri::async_runtime::spawn(async move {
+    let pool: State<'_, Pool<Sqlite>> = app_handle.state();
+    match db::markers::insert_marker(&*pool, recording_id, position_ms as i64, None).await {
+      Ok(marker) => {
+        log::info!("Added marker at {position_ms}ms");
+        let _ = app_handle.emit(Events::MarkerAdded.as_ref(), marker);
+      }
+      Err(e) => log::warn!("Failed to add marker: {e}"),
+    }
+  });
+}
+
+/// Chapter on the exported timeline
+#[derive(Debug, Clone)]
+pub struct Chapter {
+  pub start_ms: u64,
+  pub end_ms: u64,
+  pub title: String,
+}
+
+/// Convert markers to chapters, each running until the next marker.
+///
+/// A leading chapter is added when the first marker isn't at the very start.
+pub fn markers_to_chapters(markers: &[(u64, String)], length_ms: u64) -> Vec<Chapter> {
+  let mut starts: Vec<(u64, String)> = markers
+    .iter()
+    .filter(|(position_ms, _)| *position_ms < length_ms)
+    .cloned()
+    .collect();
+  starts.sort_by_key(|(position_ms, _)| *position_ms);
+  starts.dedup_by_key(|(position_ms, _)| *position_ms);
+
+  if starts
+    .first()
+    .is_some_and(|(position_ms, _)| *position_ms > 0)
+  {
+    starts.insert(0, (0, "Intro".to_string()));
+  }
+
+  starts
+    .iter()
+    .enumerate()
+    .map(|(i, (start_ms, title))| Chapter {
+      start_ms: *start_ms,
+      end_ms: starts.get(i + 1).map_or(length_ms, |(next, _)| *next),
+      title: title.clone(),
+    })
+    .collect()
+}
+
+/// Escape special characters of the ffmetadata format
+fn escape_ffmetadata(value: &str) -> String {
+  let mut escaped = String::with_capacity(value.len());
+  for c in value.chars() {
+    if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+      escaped.push('\\');
+    }
+    escaped.push(c);
+  }
+  escaped
+}
+
+/// Write chapters as an ffmetadata file, used as input with `-map_chapters`
+pub fn write_ffmetadata_chapters(chapters: &[Chapter], path: &Path) -> std::io::Result<()> {
+  let mut file = File::create(path)?;
+  writeln!(file, ";FFMETADATA1")?;
+
+  for chapter in chapters {
+    writeln!(file, "[CHAPTER]")?;
+    writeln!(file, "TIMEBASE=1/1000")?;
+    writeln!(file, "START={}", chapter.start_ms)?;
+    writeln!(file, "END={}", chapter.end_ms)?;
+    writeln!(file, "title={}", escape_ffmetadata(&chapter.title))?;
+  }
+
+  Ok(())
+}
+
+/// Return chapters in the `MM:SS Title` format used by video platform descriptions
+pub fn chapters_to_text(chapters: &[Chapter]) -> String {
+  chapters
+    .iter()
+    .map(|chapter| {
+      let seconds = chapter.start_ms / 1000;
+      let (hours, minutes, seconds) = (seconds / 3600, (seconds % 3600) / 60, seconds % 60);
+      let timestamp = if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+      } else {
+        format!("{minutes:02}:{seconds:02}")
+      };
+
+      format!("{timestamp} {}\n", chapter.title)
+    })
+    .collect()
+}
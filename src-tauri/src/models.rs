@@ -3,6 +3,7 @@ use std::{
   path::PathBuf,
   sync::{atomic::AtomicBool, Arc},
   thread::JoinHandle,
+  time::{Duration, Instant},
 };
 
 use ffmpeg_sidecar::child::FfmpegChild;
@@ -150,6 +151,10 @@ pub struct RecordingState {
   pub stream_handles: Option<Vec<ThreadHandle>>,
   pub screen_capture_details: Option<VideoTrackDetails>,
   pub camera_capture_details: Option<VideoTrackDetails>,
+  // Used to derive the position on the recording timeline, which excludes paused time
+  pub started_at: Option<Instant>,
+  pub paused_at: Option<Instant>,
+  pub paused_duration: Duration,
 }
 
 pub struct VideoTrackStartDetails {
@@ -178,6 +183,9 @@ impl RecordingState {
       stream_handles: None,
       screen_capture_details: None,
       camera_capture_details: None,
+      started_at: None,
+      paused_at: None,
+      paused_duration: Duration::ZERO,
     }
   }
 
@@ -196,6 +204,10 @@ impl RecordingState {
     self.stream_sync = Some(stream_sync);
     self.stream_handles = Some(stream_handles);
 
+    self.started_at = Some(Instant::now());
+    self.paused_at = None;
+    self.paused_duration = Duration::ZERO;
+
     // Separate due to pause/resume creating separate screen files
     self.screen_capture_details = Some(screen_recorder.into_video_track_details());
 
@@ -209,6 +221,8 @@ impl RecordingState {
   pub fn recording_stopped(&mut self) -> StoppedRecording {
     self.is_recording = false;
     self.is_paused = false;
+    self.started_at = None;
+    self.paused_at = None;
 
     if let Some(stream_sync) = self.stream_sync.take() {
       stream_sync
@@ -246,6 +260,7 @@ impl RecordingState {
     }
 
     self.is_paused = true;
+    self.paused_at = Some(Instant::now());
   }
 
   pub fn resume_recording(
@@ -269,6 +284,10 @@ impl RecordingState {
         .store(true, std::sync::atomic::Ordering::SeqCst);
     }
 
+    if let Some(paused_at) = self.paused_at.take() {
+      self.paused_duration += paused_at.elapsed();
+    }
+
     self.is_paused = false;
   }
 
@@ -279,21 +298,47 @@ impl RecordingState {
   pub fn is_paused(&self) -> bool {
     self.is_paused
   }
+
+  /// Milliseconds recorded so far, excluding time spent paused
+  pub fn timeline_position_ms(&self) -> Option<u64> {
+    let started_at = self.started_at?;
+    let until = self.paused_at.unwrap_or_else(Instant::now);
+
+    Some(
+      until
+        .duration_since(started_at)
+        .saturating_sub(self.paused_duration)
+        .as_millis() as u64,
+    )
+  }
 }
 
 pub struct EditingState {
   pub export_process: Option<Arc<Mutex<FfmpegChild>>>,
+  // Distinguishes a user cancel from ffmpeg failing on its own, both exit non-zero
+  pub export_cancelled: bool,
 }
 
 impl EditingState {
   pub fn new() -> Self {
     EditingState {
       export_process: None,
+      export_cancelled: false,
     }
   }
 
   pub fn set_export_process(&mut self, export_process: Arc<Mutex<FfmpegChild>>) {
     self.export_process = Some(export_process);
+    self.export_cancelled = false;
+  }
+
+  pub fn cancel_export(&mut self) -> Option<Arc<Mutex<FfmpegChild>>> {
+    self.export_cancelled = true;
+    self.export_process.take()
+  }
+
+  pub fn is_export_cancelled(&self) -> bool {
+    self.export_cancelled
   }
 
   pub fn take_export_process(&mut self) -> Option<Arc<Mutex<FfmpegChild>>> {
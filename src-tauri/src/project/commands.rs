@@ -0,0 +1,129 @@
+use serde_json::Value;
+use sqlx::{Pool, Sqlite};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::{
+  constants::Events,
+  project::{
+    models::{Project, ProjectDocument, ProjectState, RecordedWindow, Redaction},
+    service,
+  },
+  recording_management::service::regenerate_thumbnails,
+};
+
+/// Update the project, regenerating thumbnails in the background if its
+/// redactions changed
+async fn update_project(
+  app_handle: AppHandle,
+  pool: &Pool<Sqlite>,
+  recording_id: i64,
+  change: impl FnOnce(&mut ProjectDocument) -> Result<bool, String>,
+) -> Result<ProjectState, String> {
+  let mut redactions_changed = false;
+  let state = service::update_project(pool, recording_id, |document| {
+    let previous = document.project.redactions.clone();
+    let changed = change(document)?;
+    redactions_changed = changed && document.project.redactions != previous;
+    Ok(changed)
+  })
+  .await?;
+
+  if redactions_changed {
+    let pool = pool.clone();
+    tauri::async_runtime::spawn(async move {
+      match regenerate_thumbnails(&pool, recording_id).await {
+        Ok(()) => {
+          let _ = app_handle.emit(Events::ThumbnailsGenerated.as_ref(), recording_id);
+        }
+        Err(e) => log::warn!("Failed to regenerate redacted thumbnails: {e}"),
+      }
+    });
+  }
+
+  Ok(state)
+}
+
+#[tauri::command]
+pub async fn get_project(
+  pool: State<'_, Pool<Sqlite>>,
+  recording_id: i64,
+) -> Result<ProjectState, String> {
+  service::load_project_state(&pool, recording_id).await
+}
+
+/// Replace the project, the previous one can be restored with `undo_project`
+#[tauri::command]
+pub async fn save_project(
+  app_handle: AppHandle,
+  pool: State<'_, Pool<Sqlite>>,
+  recording_id: i64,
+  project: Project,
+) -> Result<ProjectState, String> {
+  update_project(app_handle, &pool, recording_id, |document| {
+    document.apply(project);
+    Ok(true)
+  })
+  .await
+}
+
+/// Change part of the project with a JSON merge patch, e.g. `{ "camera": { "visible": false } }`
+#[tauri::command]
+pub async fn patch_project(
+  app_handle: AppHandle,
+  pool: State<'_, Pool<Sqlite>>,
+  recording_id: i64,
+  patch: Value,
+) -> Result<ProjectState, String> {
+  update_project(app_handle, &pool, recording_id, |document| {
+    let project = service::patch_project(&document.project, &patch)?;
+    document.apply(project);
+    Ok(true)
+  })
+  .await
+}
+
+#[tauri::command]
+pub async fn undo_project(
+  app_handle: AppHandle,
+  pool: State<'_, Pool<Sqlite>>,
+  recording_id: i64,
+) -> Result<ProjectState, String> {
+  update_project(app_handle, &pool, recording_id, |document| {
+    Ok(document.undo())
+  })
+  .await
+}
+
+#[tauri::command]
+pub async fn redo_project(
+  app_handle: AppHandle,
+  pool: State<'_, Pool<Sqlite>>,
+  recording_id: i64,
+) -> Result<ProjectState, String> {
+  update_project(app_handle, &pool, recording_id, |document| {
+    Ok(document.redo())
+  })
+  .await
+}
+
+/// Windows on screen at `time_ms`, recorded during capture
+#[tauri::command]
+pub async fn list_recorded_windows(
+  pool: State<'_, Pool<Sqlite>>,
+  recording_id: i64,
+  time_ms: u64,
+) -> Result<Vec<RecordedWindow>, String> {
+  service::list_recorded_windows(&pool, recording_id, time_ms).await
+}
+
+/// Redaction following a recorded window as it moves, to add to the project
+#[tauri::command]
+pub async fn track_window_redaction(
+  pool: State<'_, Pool<Sqlite>>,
+  recording_id: i64,
+  window_id: u32,
+  start_ms: u64,
+  end_ms: u64,
+) -> Result<Redaction, String> {
+  service::window_redaction(&pool, recording_id, window_id, (start_ms, end_ms)).await
+}
@@ -0,0 +1,380 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{captions::models::CaptionExportMode, export::models::EditRange};
+
+/// Increment when the project document changes incompatibly
+pub const PROJECT_VERSION: u32 = 1;
+
+/// Maximum number of undo steps kept per recording
+pub const MAX_UNDO_HISTORY: usize = 100;
+
+/// Editor state of a recording, everything needed to export it.
+///
+/// Missing fields take their default, so older documents and partial projects
+/// from the frontend stay valid.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Project {
+  pub version: u32,
+  pub edits: Vec<EditRange>,
+  pub zooms: Vec<ZoomRange>,
+  /// Hidden parts of the screen, the recording itself is never changed
+  pub redactions: Vec<Redaction>,
+  pub camera: CameraLayout,
+  pub cursor: CursorSettings,
+  pub background: BackgroundSettings,
+  pub audio: AudioSettings,
+  pub separate_audio_tracks: bool,
+  pub separate_camera_file: bool,
+  pub captions: Option<CaptionExportMode>,
+  /// Embed markers as container chapters
+  pub chapters: bool,
+  /// Write markers as a `MM:SS Title` text file next to the export
+  pub chapters_text_file: bool,
+}
+
+impl Default for Project {
+  fn default() -> Self {
+    Self {
+      version: PROJECT_VERSION,
+      edits: vec![],
+      zooms: vec![],
+      redactions: vec![],
+      camera: CameraLayout::default(),
+      cursor: CursorSettings::default(),
+      background: BackgroundSettings::default(),
+      audio: AudioSettings::default(),
+      separate_audio_tracks: false,
+      separate_camera_file: false,
+      captions: None,
+      chapters: false,
+      chapters_text_file: false,
+    }
+  }
+}
+
+/// Zoom into part of the screen during `[start_ms, end_ms)` of the recording timeline
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZoomRange {
+  pub start_ms: u64,
+  pub end_ms: u64,
+  /// Magnification, `2.0` shows half the width and height
+  pub scale: f64,
+  /// Centre of the zoom as a fraction of the screen width
+  #[serde(default = "centre")]
+  pub center_x: f64,
+  /// Centre of the zoom as a fraction of the screen height
+  #[serde(default = "centre")]
+  pub center_y: f64,
+  /// Keep the cursor in the centre instead of `center_x`/`center_y`
+  #[serde(default)]
+  pub follow_cursor: bool,
+}
+
+fn centre() -> f64 {
+  0.5
+}
+
+/// Hide part of the screen during `[start_ms, end_ms)` of the recording timeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Redaction {
+  pub start_ms: u64,
+  pub end_ms: u64,
+  /// Area at `start_ms`
+  pub area: RedactionArea,
+  /// Later positions of an area that moves, e.g. a window being dragged, see
+  /// `project::service::window_redaction`.
+  ///
+  /// The area is interpolated between keyframes and holds the last one.
+  #[serde(default)]
+  pub keyframes: Vec<RedactionKeyframe>,
+  #[serde(default)]
+  pub style: RedactionStyle,
+}
+
+/// Rectangle as fractions of the screen width and height
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionArea {
+  pub x: f64,
+  pub y: f64,
+  pub width: f64,
+  pub height: f64,
+}
+
+/// Window recorded during capture, see `project::service::list_recorded_windows`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedWindow {
+  pub id: u32,
+  pub title: String,
+  pub area: RedactionArea,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionKeyframe {
+  pub time_ms: u64,
+  pub area: RedactionArea,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum RedactionStyle {
+  /// Box blur radius in screen pixels
+  Blur { radius: u32 },
+  /// Size of the square blocks in screen pixels
+  Pixelate { block_size: u32 },
+  /// `#rrggbb` colour
+  Fill { color: String },
+}
+
+impl Default for RedactionStyle {
+  fn default() -> Self {
+    Self::Blur { radius: 24 }
+  }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CameraPosition {
+  TopLeft,
+  TopRight,
+  BottomLeft,
+  #[default]
+  BottomRight,
+}
+
+/// Placement of the camera when burned into the screen video
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CameraLayout {
+  pub visible: bool,
+  pub position: CameraPosition,
+  /// Width in pixels, height keeps the aspect ratio
+  pub width: u32,
+  /// Distance from the screen edges in pixels
+  pub margin: u32,
+}
+
+impl Default for CameraLayout {
+  fn default() -> Self {
+    Self {
+      visible: true,
+      position: CameraPosition::default(),
+      width: 320,
+      margin: 10,
+    }
+  }
+}
+
+/// Cursor drawn from the recorded mouse events
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CursorSettings {
+  /// Draw a cursor, ignored if the system cursor was captured in the recording
+  pub visible: bool,
+  /// Size relative to the system cursor
+  pub scale: f64,
+  /// Expanding ring on every mouse button press
+  pub click_ripples: bool,
+}
+
+impl Default for CursorSettings {
+  fn default() -> Self {
+    Self {
+      visible: true,
+      scale: 1.0,
+      click_ripples: true,
+    }
+  }
+}
+
+/// Gradient the screen is placed on, inset by `padding` with rounded corners
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BackgroundSettings {
+  pub enabled: bool,
+  /// `#rrggbb` colour at the top
+  pub color_top: String,
+  /// `#rrggbb` colour at the bottom
+  pub color_bottom: String,
+  /// Space around the screen in pixels
+  pub padding: u32,
+  pub corner_radius: u32,
+}
+
+impl Default for BackgroundSettings {
+  fn default() -> Self {
+    Self {
+      enabled: false,
+      color_top: "#4f46e5".to_string(),
+      color_bottom: "#0f172a".to_string(),
+      padding: 64,
+      corner_radius: 12,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AudioTrackSettings {
+  pub muted: bool,
+  /// Linear gain, `1.0` leaves the track unchanged
+  pub volume: f64,
+}
+
+impl Default for AudioTrackSettings {
+  fn default() -> Self {
+    Self {
+      muted: false,
+      volume: 1.0,
+    }
+  }
+}
+
+impl AudioTrackSettings {
+  pub fn is_audible(&self) -> bool {
+    !self.muted && self.volume > 0.0
+  }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AudioSettings {
+  pub microphone: AudioTrackSettings,
+  pub system_audio: AudioTrackSettings,
+}
+
+/// Project with its undo/redo history, as stored in `recording_projects`
+#[derive(Debug, Clone, Default)]
+pub struct ProjectDocument {
+  pub project: Project,
+  /// Previous projects, most recent last
+  pub undo_stack: Vec<Project>,
+  /// Undone projects, most recent last
+  pub redo_stack: Vec<Project>,
+}
+
+/// Project returned to the editor after every change
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectState {
+  pub project: Project,
+  pub can_undo: bool,
+  pub can_redo: bool,
+}
+
+impl ProjectDocument {
+  /// Replace the project, recording the previous one for undo
+  pub fn apply(&mut self, mut project: Project) {
+    project.version = PROJECT_VERSION;
+    if project == self.project {
+      return;
+    }
+
+    let previous = std::mem::replace(&mut self.project, project);
+    self.undo_stack.push(previous);
+    if self.undo_stack.len() > MAX_UNDO_HISTORY {
+      self.undo_stack.remove(0);
+    }
+    self.redo_stack.clear();
+  }
+
+  pub fn undo(&mut self) -> bool {
+    let Some(previous) = self.undo_stack.pop() else {
+      return false;
+    };
+
+    let current = std::mem::replace(&mut self.project, previous);
+    self.redo_stack.push(current);
+    true
+  }
+
+  pub fn redo(&mut self) -> bool {
+    let Some(next) = self.redo_stack.pop() else {
+      return false;
+    };
+
+    let current = std::mem::replace(&mut self.project, next);
+    self.undo_stack.push(current);
+    true
+  }
+
+  pub fn state(&self) -> ProjectState {
+    ProjectState {
+      project: self.project.clone(),
+      can_undo: !self.undo_stack.is_empty(),
+      can_redo: !self.redo_stack.is_empty(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn project(padding: u32) -> Project {
+    Project {
+      background: BackgroundSettings {
+        padding,
+        ..Default::default()
+      },
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn undo_and_redo_walk_the_history() {
+    let mut document = ProjectDocument::default();
+    document.apply(project(1));
+    document.apply(project(2));
+
+    assert!(document.undo());
+    assert_eq!(document.project, project(1));
+    assert!(document.undo());
+    assert_eq!(document.project, Project::default());
+    assert!(!document.undo());
+
+    assert!(document.redo());
+    assert_eq!(document.project, project(1));
+    assert!(document.redo());
+    assert_eq!(document.project, project(2));
+    assert!(!document.redo());
+  }
+
+  #[test]
+  fn apply_clears_redo() {
+    let mut document = ProjectDocument::default();
+    document.apply(project(1));
+    document.apply(project(2));
+    document.undo();
+    document.apply(project(3));
+
+    assert!(!document.state().can_redo);
+    assert!(document.undo());
+    assert_eq!(document.project, project(1));
+  }
+
+  #[test]
+  fn apply_skips_unchanged_project() {
+    let mut document = ProjectDocument::default();
+    document.apply(Project::default());
+
+    assert!(!document.state().can_undo);
+  }
+
+  #[test]
+  fn history_drops_oldest_projects() {
+    let mut document = ProjectDocument::default();
+    for padding in 1..=MAX_UNDO_HISTORY as u32 + 10 {
+      document.apply(project(padding));
+    }
+
+    assert_eq!(document.undo_stack.len(), MAX_UNDO_HISTORY);
+    // The default project and the first nine were dropped
+    assert_eq!(document.undo_stack[0], project(10));
+  }
+}
@@ -0,0 +1,320 @@
+use serde_json::Value;
+use sqlx::{Acquire, Sqlite, SqlitePool};
+use tokio::sync::Mutex;
+
+use crate::{
+  compositor::{models::RecordingGeometry, redaction::track_area},
+  db::{self, projects::ProjectRow},
+  project::models::{
+    Project, ProjectDocument, ProjectState, RecordedWindow, Redaction, RedactionArea,
+    PROJECT_VERSION,
+  },
+  recording::{
+    ffmpeg::video_dimensions,
+    models::RecordingFile,
+    window_geometry::{
+      read_window_geometry, WindowBounds, WindowGeometryRecord, WINDOW_SAMPLE_INTERVAL_MS,
+    },
+  },
+};
+
+/// Serializes read-modify-write of projects, e.g. patches sent in quick succession
+static PROJECT_LOCK: Mutex<()> = Mutex::const_new(());
+
+fn decode_project(value: Value) -> Result<Project, String> {
+  let version = value.get("version").and_then(Value::as_u64).unwrap_or(0);
+  if version > PROJECT_VERSION as u64 {
+    return Err(format!(
+      "Project version {version} is newer than supported version {PROJECT_VERSION}"
+    ));
+  }
+
+  serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+fn decode_document(row: ProjectRow) -> Result<ProjectDocument, String> {
+  let decode_stack = |json: &str| -> Result<Vec<Project>, String> {
+    let values: Vec<Value> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    values.into_iter().map(decode_project).collect()
+  };
+
+  Ok(ProjectDocument {
+    project: decode_project(serde_json::from_str(&row.project).map_err(|e| e.to_string())?)?,
+    undo_stack: decode_stack(&row.undo_stack)?,
+    redo_stack: decode_stack(&row.redo_stack)?,
+  })
+}
+
+fn encode_document(document: &ProjectDocument) -> Result<ProjectRow, String> {
+  Ok(ProjectRow {
+    project: serde_json::to_string(&document.project).map_err(|e| e.to_string())?,
+    undo_stack: serde_json::to_string(&document.undo_stack).map_err(|e| e.to_string())?,
+    redo_stack: serde_json::to_string(&document.redo_stack).map_err(|e| e.to_string())?,
+  })
+}
+
+async fn load_document(pool: &SqlitePool, recording_id: i64) -> Result<ProjectDocument, String> {
+  match db::projects::get_project(pool, recording_id)
+    .await
+    .map_err(|e| e.to_string())?
+  {
+    Some(row) => decode_document(row),
+    None => Ok(ProjectDocument::default()),
+  }
+}
+
+/// Saved project of a recording, the default project if never saved
+pub async fn load_project(pool: &SqlitePool, recording_id: i64) -> Result<Project, String> {
+  load_document(pool, recording_id)
+    .await
+    .map(|document| document.project)
+}
+
+pub async fn load_project_state(
+  pool: &SqlitePool,
+  recording_id: i64,
+) -> Result<ProjectState, String> {
+  load_document(pool, recording_id)
+    .await
+    .map(|document| document.state())
+}
+
+/// Load, change and save the project document of a recording.
+///
+/// `change` returns whether the document changed and needs saving.
+pub async fn update_project(
+  pool: &SqlitePool,
+  recording_id: i64,
+  change: impl FnOnce(&mut ProjectDocument) -> Result<bool, String>,
+) -> Result<ProjectState, String> {
+  let _guard = PROJECT_LOCK.lock().await;
+
+  let mut document = load_document(pool, recording_id).await?;
+  if change(&mut document)? {
+    db::projects::upsert_project(pool, recording_id, &encode_document(&document)?)
+      .await
+      .map_err(|e| e.to_string())?;
+  }
+
+  Ok(document.state())
+}
+
+/// Store `project` with a fresh history, e.g. of an imported recording
+pub async fn insert_project(
+  conn: impl Acquire<'_, Database = Sqlite>,
+  recording_id: i64,
+  project: Project,
+) -> Result<(), String> {
+  let document = ProjectDocument {
+    project,
+    ..Default::default()
+  };
+
+  db::projects::upsert_project(conn, recording_id, &encode_document(&document)?)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Apply a JSON merge patch (RFC 7386) to a project
+pub fn patch_project(project: &Project, patch: &Value) -> Result<Project, String> {
+  let mut value = serde_json::to_value(project).map_err(|e| e.to_string())?;
+  merge_patch(&mut value, patch);
+
+  decode_project(value)
+}
+
+fn merge_patch(target: &mut Value, patch: &Value) {
+  let Value::Object(patch) = patch else {
+    *target = patch.clone();
+    return;
+  };
+
+  if !target.is_object() {
+    *target = Value::Object(Default::default());
+  }
+
+  if let Value::Object(target) = target {
+    for (key, value) in patch {
+      if value.is_null() {
+        target.remove(key);
+      } else {
+        merge_patch(target.entry(key.as_str()).or_insert(Value::Null), value);
+      }
+    }
+  }
+}
+
+/// Window positions recorded during capture, with what is needed to place them
+/// on the screen video
+async fn load_window_geometry(
+  pool: &SqlitePool,
+  recording_id: i64,
+) -> Result<(Vec<WindowGeometryRecord>, RecordingGeometry, (u32, u32)), String> {
+  let row = db::recordings::get_recording_row(pool, recording_id)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  let records = read_window_geometry(
+    &row
+      .recording_directory
+      .join(RecordingFile::WindowGeometry.as_ref()),
+  )
+  .map_err(|e| format!("No window positions were recorded: {e}"))?;
+  let screen_size = video_dimensions(&row.recording_directory.join(RecordingFile::Screen.as_ref()))
+    .ok_or("Failed to probe screen size")?;
+
+  Ok((records, RecordingGeometry::from(&row), screen_size))
+}
+
+/// Window bounds as fractions of the screen video
+fn window_area(
+  bounds: &WindowBounds,
+  geometry: &RecordingGeometry,
+  (width, height): (u32, u32),
+) -> RedactionArea {
+  let scale_x = geometry.scale_factor / width.max(1) as f64;
+  let scale_y = geometry.scale_factor / height.max(1) as f64;
+
+  RedactionArea {
+    x: (bounds.x - geometry.origin_x) * scale_x,
+    y: (bounds.y - geometry.origin_y) * scale_y,
+    width: bounds.width * scale_x,
+    height: bounds.height * scale_y,
+  }
+}
+
+/// Windows on screen at `time_ms` of the recording, to pick one to redact
+pub async fn list_recorded_windows(
+  pool: &SqlitePool,
+  recording_id: i64,
+  time_ms: u64,
+) -> Result<Vec<RecordedWindow>, String> {
+  let (records, geometry, screen_size) = load_window_geometry(pool, recording_id).await?;
+  let index = records
+    .partition_point(|record| record.elapsed_ms as u64 <= time_ms)
+    .saturating_sub(1);
+
+  let windows = records.get(index).map_or(vec![], |record| {
+    record
+      .windows
+      .iter()
+      .map(|bounds| RecordedWindow {
+        id: bounds.id,
+        title: bounds.title.clone(),
+        area: window_area(bounds, &geometry, screen_size),
+      })
+      .filter(|window| {
+        let area = window.area;
+        area.x < 1.0 && area.y < 1.0 && area.x + area.width > 0.0 && area.y + area.height > 0.0
+      })
+      .collect()
+  });
+
+  Ok(windows)
+}
+
+/// Redaction during `[start_ms, end_ms)` following window `window_id` as it moves
+pub async fn window_redaction(
+  pool: &SqlitePool,
+  recording_id: i64,
+  window_id: u32,
+  (start_ms, end_ms): (u64, u64),
+) -> Result<Redaction, String> {
+  let (records, geometry, screen_size) = load_window_geometry(pool, recording_id).await?;
+  let samples: Vec<(u64, Option<RedactionArea>)> = records
+    .iter()
+    .map(|record| {
+      (
+        record.elapsed_ms as u64,
+        record
+          .windows
+          .iter()
+          .find(|bounds| bounds.id == window_id)
+          .map(|bounds| window_area(bounds, &geometry, screen_size)),
+      )
+    })
+    .collect();
+
+  track_area(&samples, (start_ms, end_ms), WINDOW_SAMPLE_INTERVAL_MS)
+    .ok_or_else(|| "The window was not on screen during the redaction".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  #[test]
+  fn merge_patch_rfc_7386_examples() {
+    // Appendix A of RFC 7386
+    let cases = [
+      (json!({"a": "b"}), json!({"a": "c"}), json!({"a": "c"})),
+      (
+        json!({"a": "b"}),
+        json!({"b": "c"}),
+        json!({"a": "b", "b": "c"}),
+      ),
+      (json!({"a": "b"}), json!({"a": null}), json!({})),
+      (
+        json!({"a": "b", "b": "c"}),
+        json!({"a": null}),
+        json!({"b": "c"}),
+      ),
+      (json!({"a": ["b"]}), json!({"a": "c"}), json!({"a": "c"})),
+      (json!({"a": "c"}), json!({"a": ["b"]}), json!({"a": ["b"]})),
+      (
+        json!({"a": {"b": "c"}}),
+        json!({"a": {"b": "d", "c": null}}),
+        json!({"a": {"b": "d"}}),
+      ),
+      (
+        json!({"a": [{"b": "c"}]}),
+        json!({"a": [1]}),
+        json!({"a": [1]}),
+      ),
+      (json!(["a", "b"]), json!(["c", "d"]), json!(["c", "d"])),
+      (json!({"a": "b"}), json!(["c"]), json!(["c"])),
+      (json!({"a": "foo"}), json!(null), json!(null)),
+      (json!({"a": "foo"}), json!("bar"), json!("bar")),
+      (
+        json!({"e": null}),
+        json!({"a": 1}),
+        json!({"e": null, "a": 1}),
+      ),
+      (
+        json!([1, 2]),
+        json!({"a": "b", "c": null}),
+        json!({"a": "b"}),
+      ),
+      (
+        json!({}),
+        json!({"a": {"bb": {"ccc": null}}}),
+        json!({"a": {"bb": {}}}),
+      ),
+    ];
+
+    for (mut target, patch, expected) in cases {
+      merge_patch(&mut target, &patch);
+      assert_eq!(target, expected, "patch: {patch}");
+    }
+  }
+
+  #[test]
+  fn patch_project_merges_nested_settings() {
+    let saved = Project {
+      chapters: true,
+      ..Default::default()
+    };
+    let patched = patch_project(
+      &saved,
+      &json!({"background": {"padding": 8}, "chapters": null}),
+    )
+    .unwrap();
+
+    assert_eq!(patched.background.padding, 8);
+    assert_eq!(patched.background.color_top, saved.background.color_top);
+    // Deleted fields take their default
+    assert!(!patched.chapters);
+  }
+}
@@ -8,10 +8,14 @@ use parking_lot::Mutex;
 use serde::Deserialize;
 use sqlx::{Pool, Sqlite};
 use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_store::StoreExt;
 use tokio::sync::broadcast;
 
 use crate::{
-  constants::Events,
+  constants::{
+    store::{GENERATE_PROXY_MEDIA, STORE_NAME},
+    Events,
+  },
   db::{self, recordings::NewRecording},
   models::{
     GlobalState, RecordingState, StoppedRecording, ThreadHandle, VideoTrackDetails,
@@ -21,12 +25,15 @@ use crate::{
     audio::{start_microphone_recorder, start_system_audio_recorder},
     camera::start_camera_recorder,
     ffmpeg::concat_video_segments,
-    file::create_recording_directory,
+    file::{create_recording_directory, recordings_root, LIBRARY_LOCK},
     input_events::start_mouse_event_recorder,
     models::{RecordingFile, RecordingType, Region, StreamSync},
+    proxies::generate_proxies,
     screen::start_screen_recorder,
     video::resume_video_recording,
+    window_geometry::start_window_geometry_recorder,
   },
+  recording_management::service::regenerate_thumbnails,
   system_tray::service::update_system_tray_icon,
   windows::commands::{hide_region_selector, show_and_focus_editor},
 };
@@ -55,7 +62,7 @@ pub fn start_recording(app_handle: AppHandle, options: StartRecordingOptions) ->
   std::thread::spawn(move || {
     log::info!("Starting recording");
 
-    let recording_dir = create_recording_directory(app_handle.path().app_data_dir().unwrap());
+    let recording_dir = create_recording_directory(&recordings_root(&app_handle));
 
     // Calculate number of required barriers
     let mut barrier_count = 2; // For this coordinator + screen
@@ -137,7 +144,7 @@ pub fn start_recording(app_handle: AppHandle, options: StartRecordingOptions) ->
       );
     log::info!("Screen recorder ready");
 
-    log::info!("Starting extra writers: mouse_events, metadata");
+    log::info!("Starting extra writers: mouse_events, window_geometry, metadata");
     let global_state: State<'_, GlobalState> = app_handle.state();
     let input_event_rx = global_state.subscribe_to_input_events();
     let mouse_event_handle = start_mouse_event_recorder(
@@ -146,6 +153,11 @@ pub fn start_recording(app_handle: AppHandle, options: StartRecordingOptions) ->
       input_event_rx,
     );
     recorder_handles.push(Arc::new(Mutex::new(Some(mouse_event_handle))));
+    let window_geometry_handle = start_window_geometry_recorder(
+      recording_dir.join(RecordingFile::WindowGeometry.as_ref()),
+      synchronization.clone(),
+    );
+    recorder_handles.push(Arc::new(Mutex::new(Some(window_geometry_handle))));
     log::info!("Extra writers ready");
 
     log::info!("Waiting for streams to be ready");
@@ -160,7 +172,7 @@ pub fn start_recording(app_handle: AppHandle, options: StartRecordingOptions) ->
 
     let pool: State<'_, Pool<Sqlite>> = app_handle.state();
     let recording_id = tauri::async_runtime::block_on(db::recordings::insert_recording(
-      &pool,
+      &*pool,
       &NewRecording {
         recording_directory: recording_dir.to_str().unwrap(),
         origin_x: recording_origin.x,
@@ -274,7 +286,7 @@ pub async fn stop_recording(app_handle: AppHandle) {
     ]);
 
     db::recordings::set_recording_metadata(
-      &app_handle.state::<Pool<Sqlite>>(),
+      app_handle.state::<Pool<Sqlite>>().inner(),
       recording_id,
       bytes,
       milliseconds,
@@ -283,6 +295,8 @@ pub async fn stop_recording(app_handle: AppHandle) {
     .unwrap();
 
     let _ = app_handle.emit(Events::RecordingComplete.as_ref(), recording_id);
+
+    spawn_post_processing(app_handle.clone(), recording_id, recording_directory);
   };
 
   update_system_tray_icon(
@@ -296,6 +310,48 @@ pub async fn stop_recording(app_handle: AppHandle) {
   let _ = app_handle.set_activation_policy(tauri::ActivationPolicy::Regular);
 }
 
+/// Generate thumbnails and, if enabled, proxy media in the background.
+///
+/// Thumbnails hide the redactions of an existing project, e.g. of an imported bundle.
+pub fn spawn_post_processing(
+  app_handle: AppHandle,
+  recording_id: i64,
+  recording_directory: PathBuf,
+) {
+  tauri::async_runtime::spawn(async move {
+    let pool = app_handle.state::<Pool<Sqlite>>();
+    match regenerate_thumbnails(&pool, recording_id).await {
+      Ok(()) => {
+        let _ = app_handle.emit(Events::ThumbnailsGenerated.as_ref(), recording_id);
+      }
+      Err(e) => log::warn!("Failed to generate thumbnails: {e}"),
+    }
+
+    let generate_proxy_media = app_handle
+      .store(STORE_NAME)
+      .ok()
+      .and_then(|store| store.get(GENERATE_PROXY_MEDIA))
+      .and_then(|value| value.as_bool())
+      .unwrap_or(true);
+
+    if generate_proxy_media {
+      let _library_guard = LIBRARY_LOCK.read().await;
+      let generated =
+        tauri::async_runtime::spawn_blocking(move || generate_proxies(&recording_directory))
+          .await
+          .map_err(|e| e.to_string())
+          .and_then(|generated| generated);
+
+      match generated {
+        Ok(()) => {
+          let _ = app_handle.emit(Events::ProxiesGenerated.as_ref(), recording_id);
+        }
+        Err(e) => log::warn!("Failed to generate proxy media: {e}"),
+      }
+    }
+  });
+}
+
 pub async fn resume_recording(app_handle: AppHandle) {
   log::info!("Resuming recording");
 
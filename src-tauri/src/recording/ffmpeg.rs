@@ -1,8 +1,10 @@
 use std::{
+  collections::VecDeque,
   fs::File,
   io::{BufRead, BufReader},
   path::{Path, PathBuf},
   process::{ChildStderr, ChildStdin, Command},
+  thread::JoinHandle,
 };
 
 use ffmpeg_sidecar::{child::FfmpegChild, command::FfmpegCommand, ffprobe::ffprobe_path};
@@ -191,6 +193,50 @@ pub fn log_ffmpeg_output(stderr: ChildStderr, tag: String) {
   });
 }
 
+/// Drain ffmpeg stderr in a separate thread, keeping the last `max_lines`.
+///
+/// Lines are also logged in debug builds. Join the handle once ffmpeg has exited
+/// to get the tail, e.g. for error reporting.
+pub fn capture_ffmpeg_stderr(
+  stderr: ChildStderr,
+  tag: String,
+  max_lines: usize,
+) -> JoinHandle<Vec<String>> {
+  std::thread::spawn(move || {
+    let reader = BufReader::new(stderr);
+    let mut tail = VecDeque::with_capacity(max_lines);
+
+    for line in reader.lines().map_while(Result::ok) {
+      #[cfg(debug_assertions)]
+      log::debug!("[ffmpeg]{tag}{line}");
+
+      if tail.len() == max_lines {
+        tail.pop_front();
+      }
+      tail.push_back(line);
+    }
+
+    #[cfg(not(debug_assertions))]
+    let _ = tag;
+
+    tail.into()
+  })
+}
+
+/// Spawn ffmpeg and wait for it, returning stderr tail on failure
+pub fn run_to_completion(mut command: FfmpegCommand, tag: &str) -> Result<(), String> {
+  let mut ffmpeg = command.spawn().map_err(|e| e.to_string())?;
+  let stderr_handle = capture_ffmpeg_stderr(ffmpeg.take_stderr().unwrap(), tag.to_string(), 5);
+
+  let status = ffmpeg.wait().map_err(|e| e.to_string())?;
+  let stderr_tail = stderr_handle.join().unwrap_or_default();
+  if status.success() {
+    Ok(())
+  } else {
+    Err(format!("{tag} ffmpeg failed: {}", stderr_tail.join("\n")))
+  }
+}
+
 /// From a list of video paths, return the length of the shortest video
 pub fn shortest_video_length(segments: Vec<PathBuf>) -> Option<u64> {
   let mut shortest: Option<u64> = None;
@@ -222,3 +268,49 @@ pub fn shortest_video_length(segments: Vec<PathBuf>) -> Option<u64> {
 
   shortest
 }
+
+/// Width and height of the first video stream
+pub fn video_dimensions(path: &Path) -> Option<(u32, u32)> {
+  let output = Command::new(ffprobe_path())
+    .args([
+      "-v",
+      "error",
+      "-select_streams",
+      "v:0",
+      "-show_entries",
+      "stream=width,height",
+      "-of",
+      "csv=s=x:p=0",
+      path.to_string_lossy().as_ref(),
+    ])
+    .output()
+    .ok()?;
+
+  if !output.status.success() {
+    return None;
+  }
+
+  let stdout = String::from_utf8_lossy(&output.stdout);
+  let (width, height) = stdout.trim().split_once('x')?;
+  Some((width.parse().ok()?, height.parse().ok()?))
+}
+
+/// Whether the file has at least one audio stream
+pub fn has_audio_stream(path: &Path) -> bool {
+  Command::new(ffprobe_path())
+    .args([
+      "-v",
+      "error",
+      "-select_streams",
+      "a",
+      "-show_entries",
+      "stream=index",
+      "-of",
+      "csv=p=0",
+      path.to_string_lossy().as_ref(),
+    ])
+    .output()
+    .is_ok_and(|output| {
+      output.status.success() && !String::from_utf8_lossy(&output.stdout).trim().is_empty()
+    })
+}
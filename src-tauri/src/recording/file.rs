@@ -1,15 +1,81 @@
 use std::{
   fs::create_dir_all,
   path::{Path, PathBuf},
+  sync::LazyLock,
 };
 
 use chrono::Local;
+use fancy_regex::Regex;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::RwLock;
+
+use crate::{
+  constants::store::{RECORDINGS_ROOT, STORE_NAME},
+  recording::models::RecordingFile,
+};
+
+/// Held for reading by jobs working inside recording directories, e.g. exports,
+/// storage optimization and proxy generation, and for writing while
+/// `storage::commands::move_library` moves them
+pub static LIBRARY_LOCK: RwLock<()> = RwLock::const_new(());
+
+/// Default recordings root inside app data
+pub fn default_recordings_root(app_handle: &AppHandle) -> PathBuf {
+  app_handle
+    .path()
+    .app_data_dir()
+    .expect("Failed to get app_data_dir")
+    .join("Recordings")
+}
+
+/// Root new recordings are created in.
+///
+/// Falls back to the default root when the configured one is unavailable, e.g.
+/// its external volume is not mounted, so recording never fails because of it.
+pub fn recordings_root(app_handle: &AppHandle) -> PathBuf {
+  let configured = app_handle
+    .store(STORE_NAME)
+    .ok()
+    .and_then(|store| store.get(RECORDINGS_ROOT))
+    .and_then(|value| value.as_str().map(PathBuf::from));
+
+  match configured {
+    Some(root) if root.is_dir() => root,
+    Some(root) => {
+      log::warn!(
+        "Recordings root {} is unavailable, using default",
+        root.display()
+      );
+      default_recordings_root(app_handle)
+    }
+    None => default_recordings_root(app_handle),
+  }
+}
+
+/// Whether the recording lives on a volume that is not currently available.
+///
+/// The library root of an unmounted volume is missing as well, unlike a
+/// recording directory that was deleted.
+pub fn is_offline(recording_directory: &Path) -> bool {
+  !recording_directory.exists()
+    && recording_directory
+      .parent()
+      .is_some_and(|root| !root.exists())
+}
 
 /// Create and return current recording path
-pub fn create_recording_directory(app_data_dir: PathBuf) -> PathBuf {
-  let recordings_dir = app_data_dir.join("Recordings");
+pub fn create_recording_directory(recordings_dir: &Path) -> PathBuf {
   let timestamp = Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
-  let session_dir = recordings_dir.join(&timestamp);
+  let mut session_dir = recordings_dir.join(&timestamp);
+
+  // Imports can start within the same second as another recording
+  let mut suffix = 1;
+  while session_dir.exists() {
+    suffix += 1;
+    session_dir = recordings_dir.join(format!("{timestamp}_{suffix}"));
+  }
+
   let _ = create_dir_all(&session_dir);
 
   session_dir
@@ -34,3 +100,47 @@ pub fn folder_size_bytes(path: &Path) -> u64 {
 
   size
 }
+
+/// Pause/resume segments, segment lists and partially written generated files
+static TEMPORARY_FILE_RE: LazyLock<Regex> = LazyLock::new(|| {
+  Regex::new(
+    r"^((screen|camera)-[0-9a-f-]{36}\.mp4|video-segments_[0-9a-f-]{36}\.txt|.+\.tmp(\.[a-z0-9]+)?)$",
+  )
+  .expect("Valid temporary file regex")
+});
+
+pub fn is_temporary_file(path: &Path) -> bool {
+  path
+    .file_name()
+    .map(|name| {
+      TEMPORARY_FILE_RE
+        .is_match(&name.to_string_lossy())
+        .unwrap_or(false)
+    })
+    .unwrap_or(false)
+}
+
+/// Size of the recorded files, excluding generated and temporary files.
+///
+/// Matches the size stored when a recording stops, before thumbnails etc. exist.
+pub fn recorded_size_bytes(dir: &Path) -> u64 {
+  let Ok(entries) = std::fs::read_dir(dir) else {
+    return 0;
+  };
+
+  entries
+    .filter_map(Result::ok)
+    .filter(|entry| {
+      let path = entry.path();
+      let generated = path
+        .file_name()
+        .and_then(|name| name.to_str()?.parse::<RecordingFile>().ok())
+        .is_some_and(|file| file.is_generated());
+
+      !generated && !is_temporary_file(&path)
+    })
+    .filter_map(|entry| entry.metadata().ok())
+    .filter(|metadata| metadata.is_file())
+    .map(|metadata| metadata.len())
+    .sum()
+}
@@ -1,16 +1,17 @@
 use std::{
-  fs::OpenOptions,
-  path::PathBuf,
+  fs::{File, OpenOptions},
+  io::BufReader,
+  path::{Path, PathBuf},
   thread::JoinHandle,
   time::{Duration, Instant, SystemTime},
 };
 
 use rdev::EventType;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::recording::models::StreamSync;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub enum MouseEventRecord {
   Move {
     elapsed_ms: u128,
@@ -27,6 +28,31 @@ pub enum MouseEventRecord {
   },
 }
 
+impl MouseEventRecord {
+  pub fn elapsed_ms(&self) -> u128 {
+    match self {
+      MouseEventRecord::Move { elapsed_ms, .. }
+      | MouseEventRecord::Down { elapsed_ms, .. }
+      | MouseEventRecord::Up { elapsed_ms, .. } => *elapsed_ms,
+    }
+  }
+}
+
+/// Read all events from a `mouse_events.msgpack` file.
+///
+/// Stops at the first record which fails to decode, e.g. a partially written
+/// final record.
+pub fn read_mouse_events(file_path: &Path) -> std::io::Result<Vec<MouseEventRecord>> {
+  let mut reader = BufReader::new(File::open(file_path)?);
+  let mut events = Vec::new();
+
+  while let Ok(event) = rmp_serde::decode::from_read::<_, MouseEventRecord>(&mut reader) {
+    events.push(event);
+  }
+
+  Ok(events)
+}
+
 /// Create and start mouse event recording thread
 ///
 /// A single file, `mouse_events.msgpack`, is generated containing mouse events
@@ -2,8 +2,11 @@ mod audio;
 mod camera;
 pub mod commands;
 pub mod ffmpeg;
-mod file;
-mod input_events;
+pub mod file;
+pub mod input_events;
 pub mod models;
+pub mod proxies;
 mod screen;
+pub mod thumbnails;
 mod video;
+pub mod window_geometry;
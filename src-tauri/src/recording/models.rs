@@ -1,5 +1,5 @@
 use std::{
-  path::PathBuf,
+  path::{Path, PathBuf},
   process::ChildStdin,
   sync::{atomic::AtomicBool, Arc, Barrier},
 };
@@ -40,8 +40,14 @@ pub enum RecordingType {
   Window,
   #[strum(serialize = "screen")]
   Screen,
+  /// Brought in from another tool, see `import`
+  #[strum(serialize = "imported")]
+  Imported,
 }
 
+/// Extensions of audio tracks re-encoded from WAV, see `storage::service`
+pub const OPTIMIZED_AUDIO_EXTENSIONS: [&str; 2] = ["flac", "opus"];
+
 #[derive(EnumString, AsRefStr, Display, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum RecordingFile {
   #[strum(serialize = "screen.mp4")]
@@ -64,9 +70,37 @@ pub enum RecordingFile {
   #[serde(rename = "mouse_events.msgpack")]
   MouseEvents,
 
+  #[strum(serialize = "window_geometry.msgpack")]
+  #[serde(rename = "window_geometry.msgpack")]
+  WindowGeometry,
+
   #[strum(serialize = "metadata.json")]
   #[serde(rename = "metadata.json")]
   Metadata,
+
+  #[strum(serialize = "thumbnail.jpg")]
+  #[serde(rename = "thumbnail.jpg")]
+  Thumbnail,
+
+  #[strum(serialize = "preview.jpg")]
+  #[serde(rename = "preview.jpg")]
+  PreviewSprite,
+
+  #[strum(serialize = "microphone.peaks")]
+  #[serde(rename = "microphone.peaks")]
+  MicrophonePeaks,
+
+  #[strum(serialize = "system_audio.peaks")]
+  #[serde(rename = "system_audio.peaks")]
+  SystemAudioPeaks,
+
+  #[strum(serialize = "screen_proxy.mp4")]
+  #[serde(rename = "screen_proxy.mp4")]
+  ScreenProxy,
+
+  #[strum(serialize = "camera_proxy.mp4")]
+  #[serde(rename = "camera_proxy.mp4")]
+  CameraProxy,
 }
 
 impl RecordingFile {
@@ -85,6 +119,35 @@ impl RecordingFile {
     format!("{prefix}-{uuid}.{ext}")
   }
 
+  /// Whether the file is derived from the recorded files and can be regenerated
+  pub fn is_generated(&self) -> bool {
+    matches!(
+      self,
+      RecordingFile::Thumbnail
+        | RecordingFile::PreviewSprite
+        | RecordingFile::MicrophonePeaks
+        | RecordingFile::SystemAudioPeaks
+        | RecordingFile::ScreenProxy
+        | RecordingFile::CameraProxy
+    )
+  }
+
+  /// Path of the file in `dir`, following audio re-encoded by storage optimization
+  pub fn resolve(&self, dir: &Path) -> PathBuf {
+    let path = dir.join(self.as_ref());
+
+    if matches!(self, RecordingFile::SystemAudio | RecordingFile::Microphone) && !path.exists() {
+      for extension in OPTIMIZED_AUDIO_EXTENSIONS {
+        let optimized = path.with_extension(extension);
+        if optimized.exists() {
+          return optimized;
+        }
+      }
+    }
+
+    path
+  }
+
   /// Generate full path for RecordingFile for given directory
   pub fn complete_path(&self, dir: &str) -> PathBuf {
     format!("{dir}/{}", self.as_ref()).into()
@@ -0,0 +1,56 @@
+use std::path::Path;
+
+use ffmpeg_sidecar::command::FfmpegCommand;
+
+use crate::recording::{ffmpeg::run_to_completion, models::RecordingFile};
+
+const PROXY_MAX_WIDTH: u32 = 960;
+/// Keyframe every 10 frames, seeking never decodes far from the target
+const PROXY_GOP_SIZE: u32 = 10;
+
+/// Generate low resolution proxies of screen and camera for editor preview.
+///
+/// Originals are left untouched and still used for export. Redactions are not
+/// applied: the editor draws them over the preview so what they hide stays
+/// visible to adjust them, and proxies never leave the library as bundles
+/// skip generated files.
+pub fn generate_proxies(recording_dir: &Path) -> Result<(), String> {
+  let sources = [
+    (RecordingFile::Screen, RecordingFile::ScreenProxy),
+    (RecordingFile::Camera, RecordingFile::CameraProxy),
+  ];
+
+  for (source, proxy) in sources {
+    let source_path = recording_dir.join(source.as_ref());
+    if !source_path.exists() {
+      continue;
+    }
+
+    generate_proxy(&source_path, &recording_dir.join(proxy.as_ref()))?;
+  }
+
+  Ok(())
+}
+
+fn generate_proxy(source_path: &Path, proxy_path: &Path) -> Result<(), String> {
+  // Written under a temporary name so a partial proxy is never picked up
+  let temp_path = proxy_path.with_extension("tmp.mp4");
+
+  let mut command = FfmpegCommand::new();
+  command
+    .overwrite()
+    .input(source_path.to_string_lossy())
+    .args(["-vf", &format!("scale='min({PROXY_MAX_WIDTH},iw)':-2")])
+    .codec_video("libx264")
+    .args(["-preset", "veryfast", "-tune", "fastdecode"])
+    .crf(28)
+    .args(["-g", &PROXY_GOP_SIZE.to_string(), "-bf", "0"])
+    .pix_fmt("yuv420p")
+    .arg("-an")
+    .args(["-movflags", "+faststart"])
+    .output(temp_path.to_string_lossy());
+
+  run_to_completion(command, "[proxy]")?;
+
+  std::fs::rename(&temp_path, proxy_path).map_err(|e| e.to_string())
+}
@@ -0,0 +1,135 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+use ffmpeg_sidecar::command::FfmpegCommand;
+use image::codecs::jpeg::JpegEncoder;
+
+use crate::{
+  compositor::{frame::Frame, redaction::Redactions, service::render_redacted_screen},
+  project::models::Redaction,
+  recording::{ffmpeg::run_to_completion, models::RecordingFile},
+};
+
+const POSTER_WIDTH: u32 = 640;
+const PREVIEW_FRAME_WIDTH: u32 = 240;
+/// JPEG quality of thumbnails rendered without ffmpeg, close to its `-q:v 3`
+const POSTER_QUALITY: u8 = 85;
+/// JPEG quality of sprite sheets rendered without ffmpeg, close to its `-q:v 4`
+const PREVIEW_QUALITY: u8 = 80;
+/// Number of frames in the preview sprite sheet, laid out horizontally
+pub const PREVIEW_FRAME_COUNT: u32 = 10;
+
+/// Default poster time, far enough in to skip initial window switching
+fn default_poster_time_ms(length_ms: u64) -> u64 {
+  (length_ms / 10).min(3000)
+}
+
+/// Generate poster frame and preview sprite sheet from `screen.mp4`.
+///
+/// Both are written into the recording directory, replacing existing ones.
+/// Areas hidden by `redactions` are hidden in the thumbnails too.
+pub fn generate_thumbnails(
+  recording_dir: &Path,
+  length_ms: u64,
+  poster_time_ms: Option<u64>,
+  redactions: &[Redaction],
+) -> Result<(), String> {
+  let screen = recording_dir.join(RecordingFile::Screen.as_ref());
+  if !screen.exists() {
+    return Err(format!("Missing screen recording {screen:?}"));
+  }
+
+  let poster_time_ms = poster_time_ms
+    .unwrap_or_else(|| default_poster_time_ms(length_ms))
+    .min(length_ms.saturating_sub(1));
+
+  let redactions = Redactions::new(redactions);
+  if !redactions.is_empty() {
+    return generate_redacted_thumbnails(recording_dir, length_ms, poster_time_ms, &redactions);
+  }
+
+  let mut poster = FfmpegCommand::new();
+  poster
+    .overwrite()
+    .args(["-ss", &format!("{:.3}", poster_time_ms as f64 / 1000.0)])
+    .input(screen.to_string_lossy())
+    .args(["-frames:v", "1"])
+    .args(["-vf", &format!("scale={POSTER_WIDTH}:-2")])
+    .args(["-q:v", "3"])
+    .output(
+      recording_dir
+        .join(RecordingFile::Thumbnail.as_ref())
+        .to_string_lossy(),
+    );
+  run_to_completion(poster, "[thumbnail]")?;
+
+  // Sample frames evenly over the whole recording
+  let frames_per_second = PREVIEW_FRAME_COUNT as f64 / (length_ms.max(1) as f64 / 1000.0);
+  let mut preview = FfmpegCommand::new();
+  preview
+    .overwrite()
+    .input(screen.to_string_lossy())
+    .args([
+      "-vf",
+      &format!(
+        "fps={frames_per_second:.6},scale={PREVIEW_FRAME_WIDTH}:-2,tile={PREVIEW_FRAME_COUNT}x1"
+      ),
+    ])
+    .args(["-frames:v", "1"])
+    .args(["-q:v", "4"])
+    .output(
+      recording_dir
+        .join(RecordingFile::PreviewSprite.as_ref())
+        .to_string_lossy(),
+    );
+  run_to_completion(preview, "[preview sprite]")
+}
+
+/// Render thumbnails with the compositor, which applies the redactions
+fn generate_redacted_thumbnails(
+  recording_dir: &Path,
+  length_ms: u64,
+  poster_time_ms: u64,
+  redactions: &Redactions,
+) -> Result<(), String> {
+  let poster = render_redacted_screen(recording_dir, redactions, poster_time_ms, POSTER_WIDTH)?;
+  write_jpeg(
+    poster,
+    &recording_dir.join(RecordingFile::Thumbnail.as_ref()),
+    POSTER_QUALITY,
+  )?;
+
+  // Sample frames evenly over the whole recording
+  let step_ms = length_ms / PREVIEW_FRAME_COUNT as u64;
+  let mut sprite: Option<Frame> = None;
+  for i in 0..PREVIEW_FRAME_COUNT {
+    let frame = render_redacted_screen(
+      recording_dir,
+      redactions,
+      i as u64 * step_ms,
+      PREVIEW_FRAME_WIDTH,
+    )?;
+    sprite
+      .get_or_insert_with(|| Frame::new(frame.width * PREVIEW_FRAME_COUNT, frame.height))
+      .blit(&frame, i * frame.width, 0);
+  }
+
+  match sprite {
+    Some(sprite) => write_jpeg(
+      sprite,
+      &recording_dir.join(RecordingFile::PreviewSprite.as_ref()),
+      PREVIEW_QUALITY,
+    ),
+    None => Ok(()),
+  }
+}
+
+fn write_jpeg(frame: Frame, path: &Path, quality: u8) -> Result<(), String> {
+  let image = image::RgbaImage::from_raw(frame.width, frame.height, frame.data)
+    .ok_or("Frame size does not match its dimensions")?;
+  let image = image::DynamicImage::ImageRgba8(image).into_rgb8();
+
+  let file = File::create(path).map_err(|e| e.to_string())?;
+  JpegEncoder::new_with_quality(BufWriter::new(file), quality)
+    .encode_image(&image)
+    .map_err(|e| e.to_string())
+}
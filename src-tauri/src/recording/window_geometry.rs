@@ -0,0 +1,151 @@
+use std::{
+  fs::{File, OpenOptions},
+  io::BufReader,
+  path::{Path, PathBuf},
+  thread::JoinHandle,
+  time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::recording::models::StreamSync;
+#[cfg(target_os = "windows")]
+use crate::recording_sources::service::get_os_visible_windows;
+
+/// How often window bounds are sampled, a moving window is followed at this rate
+pub const WINDOW_SAMPLE_INTERVAL_MS: u64 = 100;
+
+/// Bounds of the visible windows from `elapsed_ms` until the next record
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometryRecord {
+  pub elapsed_ms: u128,
+  pub windows: Vec<WindowBounds>,
+}
+
+/// Window position and size in the coordinates of the recorded mouse events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowBounds {
+  pub id: u32,
+  pub title: String,
+  pub x: f64,
+  pub y: f64,
+  pub width: f64,
+  pub height: f64,
+}
+
+/// Read all records from a `window_geometry.msgpack` file.
+///
+/// Stops at the first record which fails to decode, e.g. a partially written
+/// final record.
+pub fn read_window_geometry(file_path: &Path) -> std::io::Result<Vec<WindowGeometryRecord>> {
+  let mut reader = BufReader::new(File::open(file_path)?);
+  let mut records = Vec::new();
+
+  while let Ok(record) = rmp_serde::decode::from_read::<_, WindowGeometryRecord>(&mut reader) {
+    records.push(record);
+  }
+
+  Ok(records)
+}
+
+/// Bounds of the visible windows, `None` if they could not be queried.
+///
+/// A window list query, much cheaper than listing ScreenCaptureKit shareable
+/// content, as it runs alongside the encoders for the whole recording.
+#[cfg(target_os = "macos")]
+fn visible_window_bounds() -> Option<Vec<WindowBounds>> {
+  let windows = xcap::Window::all()
+    .map_err(|e| log::warn!("Failed to list windows: {e}"))
+    .ok()?;
+
+  let bounds = windows
+    .iter()
+    .filter(|window| !window.is_minimized().unwrap_or(true))
+    .filter_map(|window| {
+      Some(WindowBounds {
+        id: window.id().ok()?,
+        title: window.title().ok().filter(|title| !title.is_empty())?,
+        x: window.x().ok()? as f64,
+        y: window.y().ok()? as f64,
+        width: window.width().ok()? as f64,
+        height: window.height().ok()? as f64,
+      })
+    })
+    .collect();
+
+  Some(bounds)
+}
+
+#[cfg(target_os = "windows")]
+fn visible_window_bounds() -> Option<Vec<WindowBounds>> {
+  let bounds = get_os_visible_windows()
+    .into_iter()
+    .map(|window| WindowBounds {
+      id: window.id,
+      title: window.title,
+      x: window.position.x,
+      y: window.position.y,
+      width: window.size.width,
+      height: window.size.height,
+    })
+    .collect();
+
+  Some(bounds)
+}
+
+/// Create and start window geometry recording thread
+///
+/// A single file, `window_geometry.msgpack`, is generated with the bounds of the
+/// visible windows, written whenever they change. Redactions derive keyframes
+/// from it to follow a window as it moves.
+pub fn start_window_geometry_recorder(
+  file_path: PathBuf,
+  synchronization: StreamSync,
+) -> JoinHandle<()> {
+  let mut window_geometry_file = OpenOptions::new()
+    .create(true)
+    .append(true)
+    .open(file_path)
+    .expect("Failed to open window geometry message pack file");
+
+  std::thread::spawn(move || {
+    let log_prefix = "[window geometry]";
+    log::info!("{log_prefix} Started window geometry recorder");
+
+    // Same timeline as the mouse events
+    let start_time = SystemTime::now();
+    let mut last_windows = None;
+
+    let mut stop_rx = synchronization.stop_tx.subscribe();
+    loop {
+      if stop_rx.try_recv().is_ok() {
+        log::info!("{log_prefix} Window geometry recorder received stop message, finishing");
+        break;
+      }
+
+      if synchronization
+        .should_write
+        .load(std::sync::atomic::Ordering::SeqCst)
+      {
+        // A failed query keeps the last known bounds
+        let windows = visible_window_bounds();
+        if let Some(windows) = windows.filter(|windows| last_windows.as_ref() != Some(windows)) {
+          let record = WindowGeometryRecord {
+            elapsed_ms: SystemTime::now()
+              .duration_since(start_time)
+              .unwrap_or_default()
+              .as_millis(),
+            windows: windows.clone(),
+          };
+
+          if let Err(e) = rmp_serde::encode::write(&mut window_geometry_file, &record) {
+            log::warn!("{log_prefix} Failed to write window geometry: {e}");
+          }
+          last_windows = Some(windows);
+        }
+      }
+
+      std::thread::sleep(Duration::from_millis(WINDOW_SAMPLE_INTERVAL_MS));
+    }
+  })
+}
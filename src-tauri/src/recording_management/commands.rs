@@ -1,15 +1,23 @@
 use sqlx::{Pool, Sqlite};
-use tauri::State;
+use tauri::{AppHandle, State};
+use tauri_plugin_store::StoreExt;
 
-use crate::db::recordings::{RecordingDetails, RecordingMetadata};
+use crate::{
+  constants::store::{RETENTION_SETTINGS, STORE_NAME},
+  db::recordings::{RecordingDetails, RecordingPage, RecordingQuery},
+  recording::{file::LIBRARY_LOCK, proxies::generate_proxies},
+  recording_management::{
+    models::{RetentionReport, RetentionSettings},
+    service,
+  },
+};
 
 #[tauri::command]
 pub async fn list_recordings(
   pool: State<'_, Pool<Sqlite>>,
-) -> Result<Vec<RecordingMetadata>, String> {
-  automated_hard_delete_recordings(pool.clone()).await; // Clean up old recordings on each listing
-
-  crate::db::recordings::list_recordings(&pool)
+  query: Option<RecordingQuery>,
+) -> Result<RecordingPage, String> {
+  crate::db::recordings::list_recordings(&pool, &query.unwrap_or_default())
     .await
     .map_err(|e| e.to_string())
 }
@@ -73,29 +81,87 @@ pub async fn hard_delete_recordings(
   Ok(())
 }
 
-pub async fn automated_hard_delete_recordings(pool: State<'_, Pool<Sqlite>>) {
-  let recording_ids = crate::db::recordings::get_recordings_to_hard_delete(&pool)
+#[tauri::command]
+pub async fn restore_recordings(
+  pool: State<'_, Pool<Sqlite>>,
+  recording_ids: Vec<i64>,
+) -> Result<(), String> {
+  crate::db::recordings::restore_recordings(&pool, recording_ids)
     .await
-    .unwrap_or_default();
+    .map_err(|e| e.to_string())
+}
 
-  println!(
-    "Automated hard delete check: found {} recordings to delete",
-    recording_ids.len()
-  );
+/// Regenerate poster frame and preview sprite sheet of a recording
+#[tauri::command]
+pub async fn regenerate_thumbnails(
+  pool: State<'_, Pool<Sqlite>>,
+  recording_id: i64,
+) -> Result<(), String> {
+  service::regenerate_thumbnails(&pool, recording_id).await
+}
 
-  if !recording_ids.is_empty() {
-    if let Err(e) = hard_delete_recordings(pool, recording_ids).await {
-      eprintln!("Error during scheduled hard delete: {e}");
-    }
-  }
+/// Set a custom poster time and regenerate thumbnails
+#[tauri::command]
+pub async fn set_poster_time(
+  pool: State<'_, Pool<Sqlite>>,
+  recording_id: i64,
+  poster_time_ms: u64,
+) -> Result<(), String> {
+  crate::db::recordings::set_poster_time(&*pool, recording_id, poster_time_ms)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  regenerate_thumbnails(pool, recording_id).await
 }
 
+/// Generate, or regenerate, proxy media used by the editor preview
 #[tauri::command]
-pub async fn restore_recordings(
+pub async fn generate_proxy_media(
+  pool: State<'_, Pool<Sqlite>>,
+  recording_id: i64,
+) -> Result<(), String> {
+  let _library_guard = LIBRARY_LOCK.read().await;
+  let recording_directory = crate::db::recordings::get_recording_directory(&pool, recording_id)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  tauri::async_runtime::spawn_blocking(move || generate_proxies(&recording_directory))
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn set_recordings_pinned(
   pool: State<'_, Pool<Sqlite>>,
   recording_ids: Vec<i64>,
+  pinned: bool,
 ) -> Result<(), String> {
-  crate::db::recordings::restore_recordings(&pool, recording_ids)
+  crate::db::recordings::set_recordings_pinned(&pool, &recording_ids, pinned)
     .await
     .map_err(|e| e.to_string())
 }
+
+#[tauri::command]
+pub fn get_retention_settings(app_handle: AppHandle) -> RetentionSettings {
+  service::retention_settings(&app_handle)
+}
+
+#[tauri::command]
+pub fn set_retention_settings(
+  app_handle: AppHandle,
+  settings: RetentionSettings,
+) -> Result<(), String> {
+  let store = app_handle.store(STORE_NAME).map_err(|e| e.to_string())?;
+  store.set(
+    RETENTION_SETTINGS,
+    serde_json::to_value(settings).map_err(|e| e.to_string())?,
+  );
+
+  Ok(())
+}
+
+/// Enforce the retention policy now rather than waiting for the daily job
+#[tauri::command]
+pub async fn enforce_retention_policy(app_handle: AppHandle) -> Result<RetentionReport, String> {
+  service::enforce_retention_policy(&app_handle).await
+}
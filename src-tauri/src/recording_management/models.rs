@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+/// User configurable retention, stored under `store::RETENTION_SETTINGS`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionSettings {
+  /// Days a recording stays in the trash before being hard deleted
+  pub trash_retention_days: u32,
+  /// Move unpinned recordings older than this to the trash
+  pub auto_trash_after_days: Option<u32>,
+  /// Max bytes of recordings not in the trash, oldest unpinned are trashed first
+  pub storage_quota_bytes: Option<u64>,
+}
+
+impl Default for RetentionSettings {
+  fn default() -> Self {
+    Self {
+      trash_retention_days: 30,
+      auto_trash_after_days: None,
+      storage_quota_bytes: None,
+    }
+  }
+}
+
+/// Outcome of enforcing the retention policy, payload of `Events::RetentionApplied`
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionReport {
+  pub hard_deleted: Vec<i64>,
+  pub trashed_by_age: Vec<i64>,
+  pub trashed_by_quota: Vec<i64>,
+  /// Bytes used by recordings not in the trash, after enforcement
+  pub storage_used_bytes: u64,
+  pub storage_quota_bytes: Option<u64>,
+}
@@ -0,0 +1,133 @@
+use sqlx::{Pool, Sqlite};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_store::StoreExt;
+
+use crate::{
+  constants::{
+    store::{RETENTION_SETTINGS, STORE_NAME},
+    Events,
+  },
+  db,
+  project::service::load_project,
+  recording::thumbnails::generate_thumbnails,
+  recording_management::models::{RetentionReport, RetentionSettings},
+};
+
+/// Stored retention settings, defaults if missing or invalid
+pub fn retention_settings(app_handle: &AppHandle) -> RetentionSettings {
+  app_handle
+    .store(STORE_NAME)
+    .ok()
+    .and_then(|store| store.get(RETENTION_SETTINGS))
+    .and_then(|value| serde_json::from_value(value).ok())
+    .unwrap_or_default()
+}
+
+/// Regenerate poster frame and preview sprite sheet, hiding the project's redactions
+pub async fn regenerate_thumbnails(pool: &Pool<Sqlite>, recording_id: i64) -> Result<(), String> {
+  let source = db::recordings::get_thumbnail_source(pool, recording_id)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  let length_ms = source
+    .length_ms
+    .ok_or("Recording length is unknown".to_string())?;
+  let redactions = load_project(pool, recording_id).await?.redactions;
+
+  tauri::async_runtime::spawn_blocking(move || {
+    generate_thumbnails(
+      &source.recording_directory,
+      length_ms,
+      source.poster_time_ms,
+      &redactions,
+    )
+  })
+  .await
+  .map_err(|e| e.to_string())?
+}
+
+/// Hard delete recordings past the trash retention period
+pub async fn purge_trash(
+  pool: &Pool<Sqlite>,
+  settings: &RetentionSettings,
+) -> Result<Vec<i64>, String> {
+  let recording_ids =
+    db::recordings::get_recordings_to_hard_delete(pool, settings.trash_retention_days)
+      .await
+      .map_err(|e| e.to_string())?;
+
+  if recording_ids.is_empty() {
+    return Ok(recording_ids);
+  }
+
+  let dirs = db::recordings::hard_delete_recordings(pool, recording_ids.clone())
+    .await
+    .map_err(|e| e.to_string())?;
+
+  for dir in dirs {
+    if let Err(e) = tokio::fs::remove_dir_all(&dir).await {
+      log::warn!("Failed to delete recording directory {dir:?}: {e}");
+    }
+  }
+
+  Ok(recording_ids)
+}
+
+/// Apply trash retention, auto-trash by age and the storage quota.
+///
+/// Pinned recordings are never trashed automatically. Emits `Events::RetentionApplied`.
+pub async fn enforce_retention_policy(app_handle: &AppHandle) -> Result<RetentionReport, String> {
+  let pool = app_handle.state::<Pool<Sqlite>>();
+  let settings = retention_settings(app_handle);
+
+  let mut report = RetentionReport {
+    hard_deleted: purge_trash(&pool, &settings).await?,
+    storage_quota_bytes: settings.storage_quota_bytes,
+    ..Default::default()
+  };
+
+  if let Some(days) = settings.auto_trash_after_days {
+    report.trashed_by_age = db::recordings::get_recordings_to_auto_trash(&pool, days)
+      .await
+      .map_err(|e| e.to_string())?;
+
+    if !report.trashed_by_age.is_empty() {
+      db::recordings::soft_delete_recordings(&pool, report.trashed_by_age.clone())
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+  }
+
+  let usage = db::recordings::list_storage_usage(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+  report.storage_used_bytes = usage.iter().map(|entry| entry.size_bytes).sum();
+
+  if let Some(quota) = settings.storage_quota_bytes {
+    for entry in usage.iter().filter(|entry| !entry.pinned) {
+      if report.storage_used_bytes <= quota {
+        break;
+      }
+
+      report.trashed_by_quota.push(entry.id);
+      report.storage_used_bytes -= entry.size_bytes;
+    }
+
+    if !report.trashed_by_quota.is_empty() {
+      db::recordings::soft_delete_recordings(&pool, report.trashed_by_quota.clone())
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+  }
+
+  log::info!(
+    "Retention applied: {} hard deleted, {} trashed by age, {} trashed by quota",
+    report.hard_deleted.len(),
+    report.trashed_by_age.len(),
+    report.trashed_by_quota.len()
+  );
+
+  let _ = app_handle.emit(Events::RetentionApplied.as_ref(), &report);
+
+  Ok(report)
+}
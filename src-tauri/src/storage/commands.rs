@@ -0,0 +1,208 @@
+use std::path::PathBuf;
+
+use parking_lot::Mutex;
+use serde_json::json;
+use sqlx::{Pool, Sqlite};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_store::StoreExt;
+
+use crate::{
+  constants::{
+    store::{RECORDINGS_ROOT, STORAGE_OPTIMIZATION, STORE_NAME},
+    Events,
+  },
+  db,
+  models::RecordingState,
+  recording::file::{folder_size_bytes, is_offline, recordings_root, LIBRARY_LOCK},
+  storage::{
+    models::{
+      LibraryMoveFailure, LibraryMoveProgress, LibraryMoveSummary, StorageOptimizationReport,
+      StorageOptimizationSettings,
+    },
+    service,
+  },
+};
+
+#[tauri::command]
+pub fn get_recordings_root(app_handle: AppHandle) -> PathBuf {
+  recordings_root(&app_handle)
+}
+
+/// Set where new recordings are created, `None` restores the default.
+///
+/// Existing recordings stay where they are, see `move_library`.
+#[tauri::command]
+pub fn set_recordings_root(app_handle: AppHandle, path: Option<PathBuf>) -> Result<(), String> {
+  let store = app_handle.store(STORE_NAME).map_err(|e| e.to_string())?;
+
+  match path {
+    Some(path) => {
+      std::fs::create_dir_all(&path).map_err(|e| e.to_string())?;
+      store.set(RECORDINGS_ROOT, json!(path));
+    }
+    None => {
+      store.delete(RECORDINGS_ROOT);
+    }
+  }
+
+  Ok(())
+}
+
+/// Move every available recording into `new_root` and make it the recordings root.
+///
+/// Emits `Events::LibraryMoveProgress`. Recordings on unmounted volumes are left
+/// in place and reported, so they can be moved once available. The root only
+/// changes once every available recording moved.
+#[tauri::command]
+pub async fn move_library(
+  app_handle: AppHandle,
+  pool: State<'_, Pool<Sqlite>>,
+  new_root: PathBuf,
+) -> Result<LibraryMoveSummary, String> {
+  if app_handle
+    .state::<Mutex<RecordingState>>()
+    .lock()
+    .is_recording()
+  {
+    return Err("Cannot move the library while recording".to_string());
+  }
+
+  let _library_guard = LIBRARY_LOCK.try_write().map_err(|_| {
+    "Cannot move the library while recordings are being exported or processed".to_string()
+  })?;
+
+  std::fs::create_dir_all(&new_root).map_err(|e| e.to_string())?;
+
+  let recordings = db::recordings::list_recording_files(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  let mut summary = LibraryMoveSummary::default();
+  let mut to_move = Vec::new();
+  for recording in recordings {
+    let dir = recording.recording_directory;
+    if dir.parent() == Some(new_root.as_path()) {
+      continue;
+    }
+
+    if is_offline(&dir) {
+      summary.offline.push(recording.id);
+    } else if dir.is_dir() {
+      to_move.push((recording.id, dir));
+    }
+  }
+
+  let total = to_move.len();
+  let total_bytes: u64 = to_move.iter().map(|(_, dir)| folder_size_bytes(dir)).sum();
+  let mut bytes_moved = 0;
+
+  for (completed, (recording_id, source)) in to_move.into_iter().enumerate() {
+    let destination = service::unique_destination(&source, &new_root);
+
+    let progress_handle = app_handle.clone();
+    let staged_source = source.clone();
+    let staged_destination = destination.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+      let mut bytes = bytes_moved;
+      service::stage_recording_move(&staged_source, &staged_destination, |moved| {
+        bytes += moved;
+        let _ = progress_handle.emit(
+          Events::LibraryMoveProgress.as_ref(),
+          LibraryMoveProgress {
+            recording_id,
+            completed,
+            total,
+            bytes_moved: bytes,
+            total_bytes,
+          },
+        );
+      })
+      .map(|staged| (staged, bytes))
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    match result {
+      Ok((staged, bytes)) => {
+        bytes_moved = bytes;
+
+        if let Err(e) = db::recordings::set_recording_directory(
+          &pool,
+          recording_id,
+          &destination.to_string_lossy(),
+        )
+        .await
+        {
+          // Keep the row pointing at real files
+          log::error!("Failed to update directory of recording {recording_id}: {e}");
+          if let Err(e) = service::revert_recording_move(&source, &destination, staged) {
+            log::error!("Failed to revert move of {}: {e}", source.display());
+          }
+
+          summary.failed.push(LibraryMoveFailure {
+            recording_id,
+            error: e.to_string(),
+          });
+          continue;
+        }
+
+        service::finish_recording_move(&source, staged);
+        summary.moved.push(recording_id);
+      }
+      Err(e) => summary.failed.push(LibraryMoveFailure {
+        recording_id,
+        error: e.to_string(),
+      }),
+    }
+
+    let _ = app_handle.emit(
+      Events::LibraryMoveProgress.as_ref(),
+      LibraryMoveProgress {
+        recording_id,
+        completed: completed + 1,
+        total,
+        bytes_moved,
+        total_bytes,
+      },
+    );
+  }
+
+  // Retrying moves what is left, new recordings meanwhile stay with the others
+  if summary.failed.is_empty() {
+    app_handle
+      .store(STORE_NAME)
+      .map_err(|e| e.to_string())?
+      .set(RECORDINGS_ROOT, json!(new_root));
+  }
+
+  Ok(summary)
+}
+
+#[tauri::command]
+pub fn get_storage_optimization_settings(app_handle: AppHandle) -> StorageOptimizationSettings {
+  service::storage_optimization_settings(&app_handle)
+}
+
+#[tauri::command]
+pub fn set_storage_optimization_settings(
+  app_handle: AppHandle,
+  settings: StorageOptimizationSettings,
+) -> Result<(), String> {
+  let store = app_handle.store(STORE_NAME).map_err(|e| e.to_string())?;
+  store.set(
+    STORAGE_OPTIMIZATION,
+    serde_json::to_value(settings).map_err(|e| e.to_string())?,
+  );
+
+  Ok(())
+}
+
+/// Optimize eligible recordings now rather than waiting for the daily job.
+///
+/// Runs even when the daily job is disabled.
+#[tauri::command]
+pub async fn optimize_storage_now(
+  app_handle: AppHandle,
+) -> Result<StorageOptimizationReport, String> {
+  service::optimize_storage(&app_handle).await
+}
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+/// Payload of `Events::LibraryMoveProgress`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryMoveProgress {
+  pub recording_id: i64,
+  /// Recordings finished, including the current one once done
+  pub completed: usize,
+  pub total: usize,
+  pub bytes_moved: u64,
+  pub total_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryMoveFailure {
+  pub recording_id: i64,
+  pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryMoveSummary {
+  pub moved: Vec<i64>,
+  /// Left in place as their volume is not mounted
+  pub offline: Vec<i64>,
+  pub failed: Vec<LibraryMoveFailure>,
+}
+
+/// How a recording directory reached its destination, see `storage::service::stage_recording_move`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StagedMove {
+  Renamed,
+  /// Copied and verified, the source still exists
+  Copied,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OptimizedAudioCodec {
+  /// Lossless, about half the size of WAV
+  #[default]
+  Flac,
+  /// Lossy, much smaller, fine for speech
+  Opus,
+}
+
+impl OptimizedAudioCodec {
+  pub fn extension(&self) -> &'static str {
+    match self {
+      OptimizedAudioCodec::Flac => "flac",
+      OptimizedAudioCodec::Opus => "opus",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OptimizedVideoCodec {
+  #[default]
+  Hevc,
+  Av1,
+}
+
+/// Opt-in re-encoding of raw recordings, stored under `store::STORAGE_OPTIMIZATION`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageOptimizationSettings {
+  pub enabled: bool,
+  /// Only recordings not opened for this many days are optimized
+  pub unopened_days: u32,
+  pub audio_codec: OptimizedAudioCodec,
+  pub video_codec: OptimizedVideoCodec,
+}
+
+impl Default for StorageOptimizationSettings {
+  fn default() -> Self {
+    Self {
+      enabled: false,
+      unopened_days: 30,
+      audio_codec: OptimizedAudioCodec::default(),
+      video_codec: OptimizedVideoCodec::default(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageOptimizationFailure {
+  pub recording_id: i64,
+  pub error: String,
+}
+
+/// Payload of `Events::StorageOptimized`
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageOptimizationReport {
+  pub optimized: Vec<i64>,
+  pub failed: Vec<StorageOptimizationFailure>,
+  pub bytes_saved: u64,
+}
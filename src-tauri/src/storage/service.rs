@@ -0,0 +1,406 @@
+use std::{
+  collections::HashMap,
+  fs, io,
+  path::{Path, PathBuf},
+  process::Command,
+};
+
+use ffmpeg_sidecar::{command::FfmpegCommand, ffprobe::ffprobe_path};
+use parking_lot::Mutex;
+use sqlx::{Pool, Sqlite};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_store::StoreExt;
+
+use crate::{
+  constants::{
+    store::{STORAGE_OPTIMIZATION, STORE_NAME},
+    Events,
+  },
+  db,
+  models::RecordingState,
+  recording::{
+    ffmpeg::run_to_completion,
+    file::{is_offline, recorded_size_bytes, LIBRARY_LOCK},
+    models::RecordingFile,
+  },
+  storage::models::{
+    OptimizedAudioCodec, OptimizedVideoCodec, StagedMove, StorageOptimizationFailure,
+    StorageOptimizationReport, StorageOptimizationSettings,
+  },
+};
+
+/// Allowed difference between original and re-encoded durations
+const DURATION_TOLERANCE_SECONDS: f64 = 0.05;
+
+/// Destination for `source` inside `root`, suffixed if the name is taken
+pub fn unique_destination(source: &Path, root: &Path) -> PathBuf {
+  let name = source
+    .file_name()
+    .map(|name| name.to_string_lossy().to_string())
+    .unwrap_or_else(|| "Recording".to_string());
+
+  let mut destination = root.join(&name);
+  let mut suffix = 1;
+  while destination.exists() {
+    suffix += 1;
+    destination = root.join(format!("{name}_{suffix}"));
+  }
+
+  destination
+}
+
+fn copy_dir(source: &Path, destination: &Path, on_copied: &mut impl FnMut(u64)) -> io::Result<()> {
+  fs::create_dir_all(destination)?;
+
+  for entry in fs::read_dir(source)? {
+    let entry = entry?;
+    let path = entry.path();
+    let target = destination.join(entry.file_name());
+
+    if path.is_dir() {
+      copy_dir(&path, &target, on_copied)?;
+    } else {
+      on_copied(fs::copy(&path, &target)?);
+    }
+  }
+
+  Ok(())
+}
+
+/// Check every file in `source` exists in `destination` with the same size
+fn verify_copy(source: &Path, destination: &Path) -> io::Result<()> {
+  for entry in fs::read_dir(source)? {
+    let entry = entry?;
+    let path = entry.path();
+    let target = destination.join(entry.file_name());
+
+    if path.is_dir() {
+      verify_copy(&path, &target)?;
+    } else if fs::metadata(&target)?.len() != entry.metadata()?.len() {
+      return Err(io::Error::other(format!(
+        "Size mismatch after copying {}",
+        path.display()
+      )));
+    }
+  }
+
+  Ok(())
+}
+
+/// First step of moving a recording directory, renaming when on the same volume.
+///
+/// Across volumes files are copied and verified, leaving the source in place
+/// until the move is finished with `finish_recording_move`. A failed copy is
+/// cleaned up.
+pub fn stage_recording_move(
+  source: &Path,
+  destination: &Path,
+  mut on_moved: impl FnMut(u64),
+) -> io::Result<StagedMove> {
+  if fs::rename(source, destination).is_ok() {
+    on_moved(crate::recording::file::folder_size_bytes(destination));
+    return Ok(StagedMove::Renamed);
+  }
+
+  let copied =
+    copy_dir(source, destination, &mut on_moved).and_then(|_| verify_copy(source, destination));
+  if let Err(e) = copied {
+    let _ = fs::remove_dir_all(destination);
+    return Err(e);
+  }
+
+  Ok(StagedMove::Copied)
+}
+
+/// Remove the source of a verified copy once the recording points at the copy.
+///
+/// Failing only leaves a stale duplicate behind, so it is not an error.
+pub fn finish_recording_move(source: &Path, staged: StagedMove) {
+  if staged == StagedMove::Copied {
+    if let Err(e) = fs::remove_dir_all(source) {
+      log::warn!("Failed to remove {} after moving it: {e}", source.display());
+    }
+  }
+}
+
+/// Undo a staged move, keeping the source
+pub fn revert_recording_move(
+  source: &Path,
+  destination: &Path,
+  staged: StagedMove,
+) -> io::Result<()> {
+  match staged {
+    StagedMove::Renamed => fs::rename(destination, source),
+    StagedMove::Copied => fs::remove_dir_all(destination),
+  }
+}
+
+/// Stored storage optimization settings, defaults if missing or invalid
+pub fn storage_optimization_settings(app_handle: &AppHandle) -> StorageOptimizationSettings {
+  app_handle
+    .store(STORE_NAME)
+    .ok()
+    .and_then(|store| store.get(STORAGE_OPTIMIZATION))
+    .and_then(|value| serde_json::from_value(value).ok())
+    .unwrap_or_default()
+}
+
+#[derive(Debug, PartialEq)]
+struct ProbedStream {
+  start_time: f64,
+  duration: f64,
+  packets: Option<u64>,
+}
+
+/// Probe timing of the first stream of `stream_type` (`v` or `a`)
+fn probe_stream(path: &Path, stream_type: &str) -> Result<ProbedStream, String> {
+  let output = Command::new(ffprobe_path())
+    .args([
+      "-v",
+      "error",
+      "-select_streams",
+      &format!("{stream_type}:0"),
+      "-count_packets",
+      "-show_entries",
+      "stream=start_time,nb_read_packets:format=duration",
+      "-of",
+      "default=noprint_wrappers=1",
+      path.to_string_lossy().as_ref(),
+    ])
+    .output()
+    .map_err(|e| e.to_string())?;
+
+  if !output.status.success() {
+    return Err(format!(
+      "Failed to probe {}: {}",
+      path.display(),
+      String::from_utf8_lossy(&output.stderr).trim()
+    ));
+  }
+
+  let stdout = String::from_utf8_lossy(&output.stdout);
+  let values: HashMap<&str, &str> = stdout
+    .lines()
+    .filter_map(|line| line.trim().split_once('='))
+    .collect();
+
+  let duration = values
+    .get("duration")
+    .and_then(|v| v.parse().ok())
+    .ok_or_else(|| format!("{} has no {stream_type} stream", path.display()))?;
+
+  Ok(ProbedStream {
+    start_time: values
+      .get("start_time")
+      .and_then(|v| v.parse().ok())
+      .unwrap_or(0.0),
+    duration,
+    packets: values.get("nb_read_packets").and_then(|v| v.parse().ok()),
+  })
+}
+
+/// Re-encode `source` with `encoder_args` to `temp`, then verify timing against it.
+///
+/// Video must keep every frame and its start time so `mouse_events.msgpack`
+/// stays aligned, audio only needs to keep its duration.
+fn encode_and_verify(
+  source: &Path,
+  temp: &Path,
+  stream_type: &str,
+  encoder_args: &[&str],
+) -> Result<(), String> {
+  let original = probe_stream(source, stream_type)?;
+
+  let mut command = FfmpegCommand::new();
+  command
+    .args(["-copyts"])
+    .input(source.to_string_lossy())
+    .args(["-map", &format!("0:{stream_type}:0")])
+    .args(encoder_args)
+    .overwrite()
+    .output(temp.to_string_lossy());
+
+  let verified = run_to_completion(command, "[optimize]").and_then(|_| {
+    let encoded = probe_stream(temp, stream_type)?;
+
+    let duration_matches =
+      (encoded.duration - original.duration).abs() <= DURATION_TOLERANCE_SECONDS;
+    let timing_matches = stream_type == "a"
+      || (encoded.packets == original.packets
+        && (encoded.start_time - original.start_time).abs() < 0.001);
+
+    if duration_matches && timing_matches {
+      Ok(())
+    } else {
+      Err(format!(
+        "Re-encoded {} does not match original: {original:?} != {encoded:?}",
+        source.display()
+      ))
+    }
+  });
+
+  if verified.is_err() {
+    let _ = fs::remove_file(temp);
+  }
+
+  verified
+}
+
+fn optimize_video(
+  recording_dir: &Path,
+  file: RecordingFile,
+  codec: OptimizedVideoCodec,
+) -> Result<(), String> {
+  let source = recording_dir.join(file.as_ref());
+  if !source.exists() {
+    return Ok(());
+  }
+
+  let encoder_args: &[&str] = match codec {
+    OptimizedVideoCodec::Hevc => &[
+      "-c:v", "libx265", "-crf", "26", "-preset", "medium", "-tag:v", "hvc1",
+    ],
+    OptimizedVideoCodec::Av1 => &["-c:v", "libsvtav1", "-crf", "32", "-preset", "8"],
+  };
+
+  let temp = source.with_extension("tmp.mp4");
+  let args = [
+    encoder_args,
+    &[
+      // Keep original frame timestamps, without dropping or duplicating
+      "-fps_mode",
+      "passthrough",
+      "-enc_time_base",
+      "demux",
+      "-pix_fmt",
+      "yuv420p",
+      "-movflags",
+      "+faststart",
+    ],
+  ]
+  .concat();
+  encode_and_verify(&source, &temp, "v", &args)?;
+
+  // Recordings already in an efficient codec may not shrink
+  if fs::metadata(&temp).map_err(|e| e.to_string())?.len()
+    >= fs::metadata(&source).map_err(|e| e.to_string())?.len()
+  {
+    let _ = fs::remove_file(&temp);
+    return Ok(());
+  }
+
+  fs::rename(&temp, &source).map_err(|e| e.to_string())
+}
+
+fn optimize_audio(
+  recording_dir: &Path,
+  file: RecordingFile,
+  codec: OptimizedAudioCodec,
+) -> Result<(), String> {
+  let source = recording_dir.join(file.as_ref());
+  if !source.exists() {
+    return Ok(());
+  }
+
+  let encoder_args: &[&str] = match codec {
+    OptimizedAudioCodec::Flac => &["-c:a", "flac", "-compression_level", "8"],
+    OptimizedAudioCodec::Opus => &["-c:a", "libopus", "-b:a", "128k"],
+  };
+
+  let extension = codec.extension();
+  let temp = source.with_extension(format!("tmp.{extension}"));
+  encode_and_verify(&source, &temp, "a", encoder_args)?;
+
+  fs::rename(&temp, source.with_extension(extension)).map_err(|e| e.to_string())?;
+  fs::remove_file(&source).map_err(|e| e.to_string())
+}
+
+/// Re-encode the recorded files of a recording in place.
+///
+/// Each file is written to a temporary file and verified before replacing the
+/// original. Audio moves from `.wav` to `.flac` or `.opus`, see `RecordingFile::resolve`.
+pub fn optimize_recording(
+  recording_dir: &Path,
+  settings: &StorageOptimizationSettings,
+) -> Result<(), String> {
+  for file in [RecordingFile::Screen, RecordingFile::Camera] {
+    optimize_video(recording_dir, file, settings.video_codec)?;
+  }
+
+  for file in [RecordingFile::SystemAudio, RecordingFile::Microphone] {
+    optimize_audio(recording_dir, file, settings.audio_codec)?;
+  }
+
+  Ok(())
+}
+
+/// Optimize recordings not opened within the configured number of days.
+///
+/// Skipped while recording, as encoding competes with capture. Emits
+/// `Events::StorageOptimized`.
+pub async fn optimize_storage(app_handle: &AppHandle) -> Result<StorageOptimizationReport, String> {
+  if app_handle
+    .state::<Mutex<RecordingState>>()
+    .lock()
+    .is_recording()
+  {
+    return Err("Cannot optimize storage while recording".to_string());
+  }
+
+  let _library_guard = LIBRARY_LOCK.read().await;
+
+  let pool = app_handle.state::<Pool<Sqlite>>();
+  let settings = storage_optimization_settings(app_handle);
+
+  let recording_ids = db::recordings::get_recordings_to_optimize(&pool, settings.unopened_days)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  let mut report = StorageOptimizationReport::default();
+  for recording_id in recording_ids {
+    let recording_dir = db::recordings::get_recording_directory(&pool, recording_id)
+      .await
+      .map_err(|e| e.to_string())?;
+    if is_offline(&recording_dir) || !recording_dir.is_dir() {
+      continue;
+    }
+
+    let blocking_settings = settings.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+      let before = recorded_size_bytes(&recording_dir);
+      optimize_recording(&recording_dir, &blocking_settings)
+        .map(|_| (before, recorded_size_bytes(&recording_dir)))
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    match result {
+      Ok((before, after)) => {
+        db::recordings::set_recording_optimized(&pool, recording_id, after)
+          .await
+          .map_err(|e| e.to_string())?;
+
+        report.optimized.push(recording_id);
+        report.bytes_saved += before.saturating_sub(after);
+      }
+      Err(error) => {
+        log::warn!("Failed to optimize recording {recording_id}: {error}");
+        report.failed.push(StorageOptimizationFailure {
+          recording_id,
+          error,
+        });
+      }
+    }
+  }
+
+  log::info!(
+    "Storage optimized: {} recordings, {} bytes saved, {} failed",
+    report.optimized.len(),
+    report.bytes_saved,
+    report.failed.len()
+  );
+
+  let _ = app_handle.emit(Events::StorageOptimized.as_ref(), &report);
+
+  Ok(report)
+}
@@ -5,6 +5,7 @@ use tauri::menu::{Menu, MenuBuilder, MenuItem};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconEvent};
 use tauri::{AppHandle, Manager, State};
 
+use crate::markers::service::add_marker_at_current_position;
 use crate::models::RecordingState;
 use crate::recording::commands::{pause_recording, resume_recording, stop_recording};
 use crate::windows::commands::{
@@ -15,6 +16,26 @@ pub fn init_system_tray(app_handle: AppHandle) -> tauri::Result<()> {
   let tray = app_handle.tray_by_id("tray_icon").unwrap();
   add_tray_menu(app_handle.clone(), &tray);
   tray.set_show_menu_on_left_click(false)?;
+  tray.on_menu_event(|app, event| match event.id.as_ref() {
+    "open_editor" => {
+      show_and_focus_editor(app.clone(), app.state());
+    }
+    "quit" => {
+      app.exit(0);
+    }
+    "add_marker" => {
+      add_marker_at_current_position(app.clone());
+    }
+    "stop_recording" => {
+      let recording_state: State<'_, Mutex<RecordingState>> = app.state();
+      if recording_state.lock().is_recording() {
+        tauri::async_runtime::block_on(stop_recording(app.clone()));
+      }
+    }
+    _ => {
+      println!("menu item {:?} not handled", event.id);
+    }
+  });
   tray.on_tray_icon_event(|tray, event| {
     // Wait for recording source selector to be initialized
     if INIT_RECORDING_SOURCE_SELECTOR.is_completed() {
@@ -39,18 +60,6 @@ pub fn init_system_tray(app_handle: AppHandle) -> tauri::Result<()> {
           show_start_recording_dock(app_handle.clone(), app_handle.state(), app_handle.state());
         }
       }
-
-      if let TrayIconEvent::Click {
-        button: MouseButton::Right,
-        button_state: MouseButtonState::Up,
-        ..
-      } = event
-      {
-        let recording_state: State<'_, Mutex<RecordingState>> = tray.app_handle().state();
-        if recording_state.lock().is_recording() {
-          tauri::async_runtime::block_on(stop_recording(tray.app_handle().clone()));
-        }
-      }
     }
   });
 
@@ -77,17 +86,29 @@ fn add_tray_menu(app_handle: AppHandle, tray: &TrayIcon) {
     .build()
     .unwrap();
 
-  tray.on_menu_event(|app, event| match event.id.as_ref() {
-    "open_editor" => {
-      show_and_focus_editor(app.clone(), app.state());
-    }
-    "quit" => {
-      app.exit(0);
-    }
-    _ => {
-      println!("menu item {:?} not handled", event.id);
-    }
-  });
+  let _ = tray.set_menu(Some(menu));
+}
+
+/// Menu while recording, left click still pauses and resumes
+fn add_recording_tray_menu(app_handle: AppHandle, tray: &TrayIcon) {
+  let add_marker =
+    MenuItem::with_id(&app_handle, "add_marker", "Add Marker", true, None::<&str>).unwrap();
+
+  let stop_recording_i = MenuItem::with_id(
+    &app_handle,
+    "stop_recording",
+    "Stop Recording",
+    true,
+    None::<&str>,
+  )
+  .unwrap();
+
+  let menu = MenuBuilder::new(&app_handle)
+    .item(&add_marker)
+    .separator()
+    .item(&stop_recording_i)
+    .build()
+    .unwrap();
 
   let _ = tray.set_menu(Some(menu));
 }
@@ -114,10 +135,12 @@ pub fn update_system_tray_icon(app_handle: AppHandle, icon: SystemTrayIcon) {
     SystemTrayIcon::Loading => include_bytes!("../../icons/system-tray-loading.ico"),
   };
 
-  if icon == SystemTrayIcon::Default {
-    add_tray_menu(app_handle.clone(), &tray);
-  } else {
-    remove_tray_menu(&tray);
+  match icon {
+    SystemTrayIcon::Default => add_tray_menu(app_handle.clone(), &tray),
+    SystemTrayIcon::Recording | SystemTrayIcon::Paused => {
+      add_recording_tray_menu(app_handle.clone(), &tray)
+    }
+    SystemTrayIcon::Loading => remove_tray_menu(&tray),
   }
 
   // Apply icon
@@ -0,0 +1,34 @@
+use sqlx::{Pool, Sqlite};
+use tauri::State;
+
+use crate::{
+  db,
+  waveform::{
+    models::{AudioTrack, WaveformPeaks},
+    service::{load_or_generate_peaks, peaks_for_range},
+  },
+};
+
+/// Return waveform min/max peaks of a track, one pair per pixel of `width`.
+///
+/// Peaks are generated on first use and cached next to the audio file.
+#[tauri::command]
+pub async fn get_waveform_peaks(
+  pool: State<'_, Pool<Sqlite>>,
+  recording_id: i64,
+  track: AudioTrack,
+  start_ms: u64,
+  end_ms: u64,
+  width: u32,
+) -> Result<WaveformPeaks, String> {
+  let recording_directory = db::recordings::get_recording_directory(&pool, recording_id)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  tauri::async_runtime::spawn_blocking(move || {
+    let peak_file = load_or_generate_peaks(&recording_directory, track)?;
+    Ok(peaks_for_range(&peak_file, start_ms, end_ms, width))
+  })
+  .await
+  .map_err(|e| e.to_string())?
+}
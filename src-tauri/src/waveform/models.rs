@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+use crate::recording::models::RecordingFile;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AudioTrack {
+  Microphone,
+  SystemAudio,
+}
+
+impl AudioTrack {
+  pub fn audio_file(&self) -> RecordingFile {
+    match self {
+      AudioTrack::Microphone => RecordingFile::Microphone,
+      AudioTrack::SystemAudio => RecordingFile::SystemAudio,
+    }
+  }
+
+  pub fn peaks_file(&self) -> RecordingFile {
+    match self {
+      AudioTrack::Microphone => RecordingFile::MicrophonePeaks,
+      AudioTrack::SystemAudio => RecordingFile::SystemAudioPeaks,
+    }
+  }
+}
+
+/// Min/max pairs at a single resolution, quantized to `i16`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PeakLevel {
+  pub samples_per_peak: u32,
+  pub min: Vec<i16>,
+  pub max: Vec<i16>,
+}
+
+/// Cached peaks of an audio file, `levels[0]` is the finest resolution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeakFile {
+  pub version: u32,
+  pub sample_rate: u32,
+  /// Size and modification time of the source when generated, to detect changes
+  pub source_size: u64,
+  pub source_modified_ms: u128,
+  /// Samples covered by `levels[0]`, the last peak may be partial
+  pub total_samples: u64,
+  pub levels: Vec<PeakLevel>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WaveformPeaks {
+  /// One entry per pixel, normalized to `-1.0..=1.0`
+  pub min: Vec<f32>,
+  pub max: Vec<f32>,
+  pub start_ms: u64,
+  pub end_ms: u64,
+}
@@ -0,0 +1,372 @@
+use std::{
+  fs::File,
+  io::{BufReader, BufWriter, Read},
+  path::Path,
+  time::UNIX_EPOCH,
+};
+
+use ffmpeg_sidecar::command::FfmpegCommand;
+use parking_lot::Mutex;
+
+use crate::{
+  recording::ffmpeg::{capture_ffmpeg_stderr, shortest_video_length},
+  waveform::models::{AudioTrack, PeakFile, PeakLevel, WaveformPeaks},
+};
+
+const PEAK_FILE_VERSION: u32 = 1;
+/// Audio is downmixed and resampled to this rate before peak detection
+const PEAK_SAMPLE_RATE: u32 = 48_000;
+/// Finest resolution, ~2.7ms per peak
+const BASE_SAMPLES_PER_PEAK: u32 = 128;
+/// Coarser levels are generated until fewer than this many peaks remain
+const MIN_LEVEL_PEAKS: usize = 512;
+/// Most columns returned by `peaks_for_range`, wider than any timeline
+const MAX_COLUMNS: u32 = 16_384;
+
+// Avoids two requests generating the same peak file at once
+static GENERATION_LOCK: Mutex<()> = Mutex::new(());
+
+/// Return peaks for a track, generating or updating the cache as needed.
+pub fn load_or_generate_peaks(recording_dir: &Path, track: AudioTrack) -> Result<PeakFile, String> {
+  let _guard = GENERATION_LOCK.lock();
+
+  let audio_path = track.audio_file().resolve(recording_dir);
+  let peaks_path = recording_dir.join(track.peaks_file().as_ref());
+
+  let metadata = std::fs::metadata(&audio_path).map_err(|e| e.to_string())?;
+  let source_size = metadata.len();
+  let source_modified_ms = metadata
+    .modified()
+    .ok()
+    .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+    .map(|d| d.as_millis())
+    .unwrap_or_default();
+
+  let cached = read_peak_file(&peaks_path).filter(|p| {
+    p.version == PEAK_FILE_VERSION && p.sample_rate == PEAK_SAMPLE_RATE && !p.levels.is_empty()
+  });
+
+  if let Some(cached) = &cached {
+    if cached.source_size == source_size && cached.source_modified_ms == source_modified_ms {
+      return Ok(cached.clone());
+    }
+  }
+
+  let (base, total_samples) =
+    update_base_level(&audio_path, cached.map(|mut c| c.levels.swap_remove(0)))?;
+
+  let peak_file = PeakFile {
+    version: PEAK_FILE_VERSION,
+    sample_rate: PEAK_SAMPLE_RATE,
+    source_size,
+    source_modified_ms,
+    total_samples,
+    levels: build_levels(base),
+  };
+
+  if let Err(e) = write_peak_file(&peaks_path, &peak_file) {
+    log::warn!("Failed to cache waveform peaks {peaks_path:?}: {e}");
+  }
+
+  Ok(peak_file)
+}
+
+/// Return the finest level, reusing still valid peaks of `previous`.
+///
+/// Recovered recordings only grow and trimmed recordings are cut at the end, so
+/// peaks covering samples still present are kept and only the rest is decoded.
+fn update_base_level(
+  audio_path: &Path,
+  previous: Option<PeakLevel>,
+) -> Result<(PeakLevel, u64), String> {
+  let mut base = previous.unwrap_or(PeakLevel {
+    samples_per_peak: BASE_SAMPLES_PER_PEAK,
+    ..Default::default()
+  });
+
+  let source_samples = shortest_video_length(vec![audio_path.to_path_buf()])
+    .map(|ms| ms * PEAK_SAMPLE_RATE as u64 / 1000)
+    .unwrap_or(u64::MAX);
+
+  // The last peak may be partial, always decode it again
+  let reusable_peaks = (base.min.len().saturating_sub(1) as u64)
+    .min(source_samples / BASE_SAMPLES_PER_PEAK as u64) as usize;
+  base.min.truncate(reusable_peaks);
+  base.max.truncate(reusable_peaks);
+
+  let start_sample = reusable_peaks as u64 * BASE_SAMPLES_PER_PEAK as u64;
+  let decoded_samples = decode_peaks(audio_path, start_sample, &mut base)?;
+
+  Ok((base, start_sample + decoded_samples))
+}
+
+/// Decode mono samples from `start_sample` onwards, appending peaks to `level`.
+///
+/// Returns number of samples decoded.
+fn decode_peaks(
+  audio_path: &Path,
+  start_sample: u64,
+  level: &mut PeakLevel,
+) -> Result<u64, String> {
+  let mut command = FfmpegCommand::new();
+  command
+    .input(audio_path.to_string_lossy())
+    .args([
+      "-af",
+      // Resample before trimming so `start_sample` is at `PEAK_SAMPLE_RATE`
+      &format!(
+        "aresample={PEAK_SAMPLE_RATE},aformat=sample_fmts=flt:channel_layouts=mono,atrim=start_sample={start_sample}"
+      ),
+    ])
+    .format("f32le")
+    .output("-");
+
+  let mut ffmpeg = command.spawn().map_err(|e| e.to_string())?;
+  let stderr_handle =
+    capture_ffmpeg_stderr(ffmpeg.take_stderr().unwrap(), "[waveform]".to_string(), 5);
+  let mut stdout = ffmpeg.take_stdout().unwrap();
+
+  let samples_per_peak = level.samples_per_peak as u64;
+  let mut buffer = vec![0u8; 64 * 1024];
+  let mut leftover: Vec<u8> = Vec::with_capacity(4);
+  let mut decoded = 0u64;
+  let (mut current_min, mut current_max) = (f32::MAX, f32::MIN);
+
+  loop {
+    let read = stdout.read(&mut buffer).map_err(|e| e.to_string())?;
+    if read == 0 {
+      break;
+    }
+
+    leftover.extend_from_slice(&buffer[..read]);
+    let complete = leftover.len() / 4 * 4;
+
+    for bytes in leftover[..complete].chunks_exact(4) {
+      let sample = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+      current_min = current_min.min(sample);
+      current_max = current_max.max(sample);
+      decoded += 1;
+
+      if decoded % samples_per_peak == 0 {
+        level.min.push(quantize(current_min));
+        level.max.push(quantize(current_max));
+        (current_min, current_max) = (f32::MAX, f32::MIN);
+      }
+    }
+
+    leftover.drain(..complete);
+  }
+
+  // Partial final peak
+  if decoded % samples_per_peak != 0 {
+    level.min.push(quantize(current_min));
+    level.max.push(quantize(current_max));
+  }
+
+  let status = ffmpeg.wait().map_err(|e| e.to_string())?;
+  let stderr_tail = stderr_handle.join().unwrap_or_default();
+  if !status.success() {
+    return Err(format!(
+      "Failed to decode audio: {}",
+      stderr_tail.join("\n")
+    ));
+  }
+
+  Ok(decoded)
+}
+
+fn quantize(sample: f32) -> i16 {
+  (sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16
+}
+
+fn dequantize(value: i16) -> f32 {
+  value as f32 / i16::MAX as f32
+}
+
+/// Build progressively halved levels from the finest one
+fn build_levels(base: PeakLevel) -> Vec<PeakLevel> {
+  let mut levels = vec![base];
+
+  while let Some(previous) = levels.last() {
+    if previous.min.len() < MIN_LEVEL_PEAKS * 2 {
+      break;
+    }
+
+    let next = PeakLevel {
+      samples_per_peak: previous.samples_per_peak * 2,
+      min: previous
+        .min
+        .chunks(2)
+        .map(|c| c.iter().copied().min().unwrap_or_default())
+        .collect(),
+      max: previous
+        .max
+        .chunks(2)
+        .map(|c| c.iter().copied().max().unwrap_or_default())
+        .collect(),
+    };
+    levels.push(next);
+  }
+
+  levels
+}
+
+fn read_peak_file(path: &Path) -> Option<PeakFile> {
+  let file = File::open(path).ok()?;
+  rmp_serde::decode::from_read(BufReader::new(file)).ok()
+}
+
+fn write_peak_file(path: &Path, peak_file: &PeakFile) -> Result<(), String> {
+  // Write then rename so a crash never leaves a truncated cache behind
+  let temp_path = path.with_extension("peaks.tmp");
+  {
+    let mut writer = BufWriter::new(File::create(&temp_path).map_err(|e| e.to_string())?);
+    rmp_serde::encode::write(&mut writer, peak_file).map_err(|e| e.to_string())?;
+  }
+  std::fs::rename(&temp_path, path).map_err(|e| e.to_string())
+}
+
+/// Coarsest level which still has at least one peak per column
+fn select_level(peak_file: &PeakFile, samples_per_column: f64) -> &PeakLevel {
+  peak_file
+    .levels
+    .iter()
+    .rev()
+    .find(|level| level.samples_per_peak as f64 <= samples_per_column)
+    .unwrap_or(&peak_file.levels[0])
+}
+
+/// Return `width` min/max columns covering `[start_ms, end_ms)`, at most
+/// `MAX_COLUMNS`.
+pub fn peaks_for_range(
+  peak_file: &PeakFile,
+  start_ms: u64,
+  end_ms: u64,
+  width: u32,
+) -> WaveformPeaks {
+  let width = width.clamp(1, MAX_COLUMNS) as usize;
+  let sample_rate = peak_file.sample_rate as u64;
+  let start_sample = start_ms * sample_rate / 1000;
+  let end_sample = (end_ms * sample_rate / 1000).max(start_sample + 1);
+  let samples_per_column = (end_sample - start_sample) as f64 / width as f64;
+
+  let level = select_level(peak_file, samples_per_column);
+
+  let samples_per_peak = level.samples_per_peak as f64;
+  let mut min = Vec::with_capacity(width);
+  let mut max = Vec::with_capacity(width);
+
+  for column in 0..width {
+    let column_start = start_sample as f64 + column as f64 * samples_per_column;
+    let column_end = column_start + samples_per_column;
+
+    let first = (column_start / samples_per_peak).floor() as usize;
+    let last = ((column_end / samples_per_peak).ceil() as usize).max(first + 1);
+
+    let range = first.min(level.min.len())..last.min(level.min.len());
+    if range.is_empty() {
+      // Outside of the audio
+      min.push(0.0);
+      max.push(0.0);
+      continue;
+    }
+
+    min.push(dequantize(
+      level.min[range.clone()]
+        .iter()
+        .copied()
+        .min()
+        .unwrap_or_default(),
+    ));
+    max.push(dequantize(
+      level.max[range].iter().copied().max().unwrap_or_default(),
+    ));
+  }
+
+  WaveformPeaks {
+    min,
+    max,
+    start_ms,
+    end_ms,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Peak file at one peak per millisecond, peak `i` reaching `±(i % 300) * 100`
+  fn peak_file(peaks: i16) -> PeakFile {
+    let base = PeakLevel {
+      samples_per_peak: BASE_SAMPLES_PER_PEAK,
+      min: (0..peaks).map(|i| -(i % 300) * 100).collect(),
+      max: (0..peaks).map(|i| (i % 300) * 100).collect(),
+    };
+
+    PeakFile {
+      version: PEAK_FILE_VERSION,
+      sample_rate: BASE_SAMPLES_PER_PEAK * 1000,
+      source_size: 0,
+      source_modified_ms: 0,
+      total_samples: peaks as u64 * BASE_SAMPLES_PER_PEAK as u64,
+      levels: build_levels(base),
+    }
+  }
+
+  #[test]
+  fn build_levels_halves_until_few_peaks_remain() {
+    let levels = peak_file(4096).levels;
+
+    let sizes: Vec<(u32, usize)> = levels
+      .iter()
+      .map(|level| (level.samples_per_peak, level.min.len()))
+      .collect();
+    assert_eq!(sizes, [(128, 4096), (256, 2048), (512, 1024), (1024, 512)]);
+    assert_eq!((levels[1].min[1], levels[1].max[1]), (-300, 300));
+  }
+
+  #[test]
+  fn select_level_keeps_a_peak_per_column() {
+    let file = peak_file(4096);
+
+    assert_eq!(select_level(&file, 64.0).samples_per_peak, 128);
+    assert_eq!(select_level(&file, 128.0).samples_per_peak, 128);
+    assert_eq!(select_level(&file, 511.0).samples_per_peak, 256);
+    assert_eq!(select_level(&file, 1024.0).samples_per_peak, 1024);
+    assert_eq!(select_level(&file, 100_000.0).samples_per_peak, 1024);
+  }
+
+  #[test]
+  fn columns_cover_every_peak_in_range() {
+    let file = peak_file(8);
+
+    assert_eq!(
+      peaks_for_range(&file, 0, 8, 4).max,
+      [100, 300, 500, 700].map(dequantize)
+    );
+    // Columns ending mid peak include it
+    assert_eq!(
+      peaks_for_range(&file, 0, 3, 2).max,
+      [100, 200].map(dequantize)
+    );
+  }
+
+  #[test]
+  fn columns_past_the_audio_are_silent() {
+    let peaks = peaks_for_range(&peak_file(8), 6, 10, 4);
+
+    assert_eq!(peaks.max, [dequantize(600), dequantize(700), 0.0, 0.0]);
+    assert_eq!(peaks.min, [dequantize(-600), dequantize(-700), 0.0, 0.0]);
+  }
+
+  #[test]
+  fn width_is_clamped() {
+    let file = peak_file(8);
+
+    assert_eq!(peaks_for_range(&file, 0, 8, 0).max.len(), 1);
+    assert_eq!(
+      peaks_for_range(&file, 0, 8, u32::MAX).max.len(),
+      MAX_COLUMNS as usize
+    );
+  }
+}
@@ -170,6 +170,7 @@ export const Editor = () => {
                   .split("/")
                   .slice(0, -1)
                   .join("/")}
+                recordingId={recordingDetails.id}
               />
             </Dialog>
           </Modal>
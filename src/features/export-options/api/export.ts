@@ -12,12 +12,23 @@ export const pathExists = async (path: string): Promise<boolean> =>
 type ExportRecordingOptions = {
   destinationFilePath: string;
   openFolderAfterExport: boolean;
+  recordingId: number;
   separateAudioTracks: boolean;
   separateCameraFile: boolean;
   sourceFolderPath: string;
 };
-export const exportRecording = (options: ExportRecordingOptions) => {
-  void invoke(Commands.ExportRecording, { options });
+export const exportRecording = ({
+  separateAudioTracks,
+  separateCameraFile,
+  ...options
+}: ExportRecordingOptions) => {
+  // Merged over the saved project of the recording
+  void invoke(Commands.ExportRecording, {
+    options: {
+      ...options,
+      project: { separateAudioTracks, separateCameraFile },
+    },
+  });
 };
 
 export const cancelExport = () => {
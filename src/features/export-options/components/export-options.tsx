@@ -29,6 +29,7 @@ type ExportOptionsProps = {
   defaultFilename: string;
   hasCamera: boolean;
   recordingDirectory: string;
+  recordingId: number;
   onCancel?: () => void;
 };
 
@@ -37,6 +38,7 @@ export const ExportOptions = ({
   hasCamera,
   onCancel,
   recordingDirectory,
+  recordingId,
 }: ExportOptionsProps) => {
   const state = useExportPreferencesStore(useShallow((state) => state));
 
@@ -66,6 +68,7 @@ export const ExportOptions = ({
     exportRecording({
       destinationFilePath: filePath,
       openFolderAfterExport,
+      recordingId,
       separateAudioTracks,
       separateCameraFile: hasCamera && separateCameraFile,
       sourceFolderPath: recordingDirectory,
@@ -4,7 +4,7 @@ import z from "zod";
 import { Commands } from "../../../types/api";
 
 // TODO remove and generalise - separate ticket to refactor enum use
-const RecordingTypeSchema = z.enum(["screen", "region", "window"]);
+const RecordingTypeSchema = z.enum(["screen", "region", "window", "imported"]);
 
 const RecordingMetadataSchema = z.object({
   createdAt: z
@@ -35,11 +35,14 @@ const RecordingMetadataSchema = z.object({
   type: RecordingTypeSchema.nullable().optional(),
 });
 
-const RecordingMetadataArraySchema = z.array(RecordingMetadataSchema);
+const RecordingPageSchema = z.object({
+  nextCursor: z.string().nullable(),
+  recordings: z.array(RecordingMetadataSchema),
+});
 
 export type RecordingMetadata = z.infer<typeof RecordingMetadataSchema>;
 
 export const listRecordings = async (): Promise<RecordingMetadata[]> => {
   const data = await invoke(Commands.ListRecordings);
-  return RecordingMetadataArraySchema.parse(data);
+  return RecordingPageSchema.parse(data).recordings;
 };